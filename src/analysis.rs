@@ -1,12 +1,16 @@
 //! Analyses from the fileinfo service.
 
+use std::io::Write;
 use std::time::Duration;
 
+use connection::APIArguments;
 use connection::APIConnection;
 use connection::APIResponse;
+use error::ErrorKind;
 use error::Result;
 use error::ResultExt;
 use file::File;
+use file::StreamedFile;
 use resource::Resource;
 
 /// Arguments for a file analysis.
@@ -30,6 +34,7 @@ pub struct AnalysisArguments {
     output_format: Option<String>,
     verbose: Option<bool>,
     input_file: Option<File>,
+    streamed_input_file: Option<StreamedFile>,
 }
 
 impl AnalysisArguments {
@@ -64,6 +69,16 @@ impl AnalysisArguments {
         self
     }
 
+    /// Sets the file to be analyzed, streaming its content when it is being
+    /// uploaded.
+    ///
+    /// Use this instead of `with_input_file()` for large files that you do not
+    /// want to load into memory. It replaces a previously set input file.
+    pub fn with_streamed_input_file(mut self, input_file: StreamedFile) -> Self {
+        self.set_streamed_input_file(input_file);
+        self
+    }
+
     /// Sets the format of the output from the analysis.
     ///
     /// Available values are: `plain` (default), `json`.
@@ -85,6 +100,16 @@ impl AnalysisArguments {
     /// This parameter is required. Without it, there is nothing to analyze.
     pub fn set_input_file(&mut self, input_file: File) {
         self.input_file = Some(input_file);
+        self.streamed_input_file = None;
+    }
+
+    /// Sets the file to be analyzed, streaming its content when it is being
+    /// uploaded.
+    ///
+    /// It replaces a previously set input file.
+    pub fn set_streamed_input_file(&mut self, input_file: StreamedFile) {
+        self.streamed_input_file = Some(input_file);
+        self.input_file = None;
     }
 
     /// Returns the output format.
@@ -112,6 +137,16 @@ impl AnalysisArguments {
     pub fn take_input_file(&mut self) -> Option<File> {
         self.input_file.take()
     }
+
+    /// Returns the file to be analyzed via streaming.
+    pub fn streamed_input_file(&self) -> Option<&StreamedFile> {
+        self.streamed_input_file.as_ref()
+    }
+
+    /// Takes ownership of the streamed input file and sets it to `None`.
+    pub fn take_streamed_input_file(&mut self) -> Option<StreamedFile> {
+        self.streamed_input_file.take()
+    }
 }
 
 /// Analysis from the fileinfo service.
@@ -253,7 +288,35 @@ impl Analysis {
     /// Accesses the API.
     pub fn get_output_as_file(&mut self) -> Result<File> {
         let response = self.get_output_response()?;
-        response.body_as_file()
+        // Name of the file used when the API does not provide one.
+        let fallback_name = format!("{}.output", self.resource.id);
+        Ok(response.body_as_file_or_named(fallback_name))
+    }
+
+    /// Writes the output from the analysis into the given writer.
+    ///
+    /// Unlike `get_output()`, the output is not kept in memory but written
+    /// into the writer as it is being received. Returns the number of written
+    /// bytes.
+    ///
+    /// Accesses the API.
+    pub fn write_output_to<W>(&mut self, writer: &mut W) -> Result<u64>
+        where W: Write
+    {
+        self.ensure_analysis_has_succeeded()?;
+        let output_url = format!("{}/output", self.resource.base_url);
+        let (response, written) = self.resource.conn.send_get_request_into(
+            &output_url,
+            APIArguments::new(),
+            writer
+        )?;
+        if response.failed() {
+            bail!(ErrorKind::RequestFailed(
+                response.status_code(),
+                format!("request to {} failed: {}", output_url, response.error_reason())
+            ));
+        }
+        Ok(written)
     }
 
     fn get_output_response(&mut self) -> Result<APIResponse> {
@@ -274,10 +337,10 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use connection::tests::APIArgumentsBuilder;
-    use connection::tests::APIConnectionMock;
-    use connection::tests::APIConnectionMockWrapper;
-    use connection::tests::APIResponseBuilder;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionMock;
+    use testing::APIConnectionMockWrapper;
+    use testing::APIResponseBuilder;
     use settings::Settings;
 
     fn create_analysis() -> (Rc<RefCell<APIConnectionMock>>, Analysis) {
@@ -346,6 +409,34 @@ mod tests {
         conn.borrow_mut().reset();
     }
 
+    #[test]
+    fn analysis_arguments_streamed_input_file_replaces_input_file() {
+        let streamed_file = StreamedFile::from_reader(
+            ::std::io::Cursor::new(b"content"), 7, "file.exe"
+        );
+
+        let args = AnalysisArguments::new()
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"))
+            .with_streamed_input_file(streamed_file);
+
+        assert!(args.input_file().is_none());
+        assert_eq!(args.streamed_input_file().unwrap().name(), "file.exe");
+    }
+
+    #[test]
+    fn analysis_arguments_input_file_replaces_streamed_input_file() {
+        let streamed_file = StreamedFile::from_reader(
+            ::std::io::Cursor::new(b"content"), 7, "file.exe"
+        );
+
+        let args = AnalysisArguments::new()
+            .with_streamed_input_file(streamed_file)
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"));
+
+        assert!(args.streamed_input_file().is_none());
+        assert_eq!(args.input_file().unwrap().name(), "file.exe");
+    }
+
     #[test]
     fn analysis_id_returns_id_of_analysis() {
         let (_, analysis) = create_analysis();
@@ -680,4 +771,71 @@ mod tests {
                 .build()
         ));
     }
+
+    #[test]
+    fn analysis_get_output_as_file_uses_fallback_name_when_api_provides_none() {
+        let (conn, mut analysis) = create_analysis();
+        make_analysis_succeed(&conn, &mut analysis);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(b"Output from analysis")
+                    .build()
+            )
+        );
+
+        let output_file = analysis.get_output_as_file()
+            .expect("get_output_as_file() should have succeeded");
+
+        assert_eq!(output_file.name(), "ID.output");
+    }
+
+    #[test]
+    fn analysis_write_output_to_writes_output_into_writer() {
+        let (conn, mut analysis) = create_analysis();
+        make_analysis_succeed(&conn, &mut analysis);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(b"Output from analysis")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let written = analysis.write_output_to(&mut output)
+            .expect("write_output_to() should have succeeded");
+
+        assert_eq!(written, 20);
+        assert_eq!(output, b"Output from analysis");
+    }
+
+    #[test]
+    fn analysis_write_output_to_returns_error_and_writes_nothing_when_request_fails() {
+        let (conn, mut analysis) = create_analysis();
+        make_analysis_succeed(&conn, &mut analysis);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(404)
+                    .with_status_message("Not Found")
+                    .with_body(b"{}")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let result = analysis.write_output_to(&mut output);
+
+        assert!(result.is_err());
+        assert!(output.is_empty());
+    }
 }
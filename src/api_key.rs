@@ -0,0 +1,243 @@
+//! API keys.
+
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+
+use error::Result;
+use error::ResultExt;
+
+const REDACTED: &'static str = "<redacted>";
+
+/// An API key.
+///
+/// The key never appears in the output of `Debug` or `Display`, so it does
+/// not leak into logs or error messages by accident. Use `expose()` to get
+/// the actual key.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::api_key::ApiKey;
+///
+/// let key = ApiKey::new("MY-API-KEY");
+///
+/// assert_eq!(key.expose(), "MY-API-KEY");
+/// assert_eq!(format!("{}", key), "<redacted>");
+/// assert_eq!(format!("{:?}", key), "ApiKey(<redacted>)");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    key: String,
+}
+
+impl ApiKey {
+    /// Creates an API key.
+    pub fn new<K: Into<String>>(key: K) -> Self {
+        ApiKey { key: key.into() }
+    }
+
+    /// Reads an API key from the given file.
+    ///
+    /// Leading and trailing whitespace (e.g. the final newline) is stripped.
+    /// An empty file is an error. Use `is_world_readable()` to check whether
+    /// the file is protected well enough.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .chain_err(|| format!("failed to read the API key from {:?}", path))?;
+        Self::from_output(&content)
+            .chain_err(|| format!("no API key in {:?}", path))
+    }
+
+    /// Obtains an API key from the standard output of the given command.
+    ///
+    /// The command is run via the system shell (`sh -c` or `cmd /C`), so it
+    /// may be e.g. `pass show retdec` or `security find-generic-password -w
+    /// -s retdec`. Its standard error is passed through. The command has to
+    /// succeed and print a non-empty key.
+    pub fn from_command(command: &str) -> Result<Self> {
+        let output = shell_command(command)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .chain_err(|| format!("failed to run the API key command '{}'", command))?;
+        if !output.status.success() {
+            bail!("the API key command '{}' failed ({})", command, output.status);
+        }
+        let stdout = String::from_utf8(output.stdout)
+            .chain_err(|| format!("the API key command '{}' printed invalid UTF-8", command))?;
+        Self::from_output(&stdout)
+            .chain_err(|| format!("the API key command '{}' printed no API key", command))
+    }
+
+    /// Returns the actual key.
+    pub fn expose(&self) -> &str {
+        &self.key
+    }
+
+    fn from_output(output: &str) -> Result<Self> {
+        let key = output.trim();
+        if key.is_empty() {
+            bail!("the API key is empty");
+        }
+        Ok(ApiKey::new(key))
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        ApiKey::new(key)
+    }
+}
+
+impl<'a> From<&'a str> for ApiKey {
+    fn from(key: &'a str) -> Self {
+        ApiKey::new(key)
+    }
+}
+
+/// Can anybody read the given file?
+///
+/// Files with API keys should be readable only by their owner. On platforms
+/// without Unix permissions, it always returns `false`.
+#[cfg(unix)]
+pub fn is_world_readable<P: AsRef<Path>>(path: P) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match fs::metadata(path) {
+        Ok(metadata) => metadata.permissions().mode() & 0o004 != 0,
+        Err(_) => false,
+    }
+}
+
+/// Can anybody read the given file?
+///
+/// Files with API keys should be readable only by their owner. On platforms
+/// without Unix permissions, it always returns `false`.
+#[cfg(not(unix))]
+pub fn is_world_readable<P: AsRef<Path>>(_path: P) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use tempdir::TempDir;
+
+    fn write_file(path: &Path, content: &str) {
+        fs::File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn api_key_is_redacted_in_debug_and_display() {
+        let key = ApiKey::new("SECRET");
+
+        assert!(!format!("{:?}", key).contains("SECRET"));
+        assert!(!format!("{}", key).contains("SECRET"));
+    }
+
+    #[test]
+    fn api_key_expose_returns_actual_key() {
+        let key = ApiKey::from("SECRET");
+
+        assert_eq!(key.expose(), "SECRET");
+    }
+
+    #[test]
+    fn from_file_returns_key_without_surrounding_whitespace() {
+        let tmp_dir = TempDir::new("retdec-api-key").unwrap();
+        let path = tmp_dir.path().join("key");
+        write_file(&path, "  SECRET\n");
+
+        let key = ApiKey::from_file(&path).unwrap();
+
+        assert_eq!(key.expose(), "SECRET");
+    }
+
+    #[test]
+    fn from_file_returns_error_when_file_is_empty() {
+        let tmp_dir = TempDir::new("retdec-api-key").unwrap();
+        let path = tmp_dir.path().join("key");
+        write_file(&path, "\n");
+
+        assert!(ApiKey::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn from_file_returns_error_when_file_does_not_exist() {
+        let tmp_dir = TempDir::new("retdec-api-key").unwrap();
+
+        assert!(ApiKey::from_file(tmp_dir.path().join("key")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn from_command_returns_key_printed_by_command() {
+        let key = ApiKey::from_command("echo SECRET").unwrap();
+
+        assert_eq!(key.expose(), "SECRET");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn from_command_returns_error_when_command_fails() {
+        assert!(ApiKey::from_command("echo SECRET; exit 1").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn from_command_returns_error_when_command_prints_nothing() {
+        assert!(ApiKey::from_command("true").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn is_world_readable_checks_permissions_of_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new("retdec-api-key").unwrap();
+        let path = tmp_dir.path().join("key");
+        write_file(&path, "SECRET");
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(is_world_readable(&path));
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(!is_world_readable(&path));
+    }
+}
@@ -0,0 +1,500 @@
+//! Decompilation of many files with a bounded number of concurrently running
+//! decompilations.
+
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use decompilation::Decompilation;
+use decompilation::DecompilationArguments;
+use decompiler::Decompiler;
+use error::Error;
+use error::ErrorKind;
+use error::Result;
+use error::ResultExt;
+use error::chain_contains;
+
+/// Default maximal number of decompilations that run at the same time.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Default time between two polls of statuses of running decompilations.
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+
+/// Number of consecutive transient failures to obtain the status of a
+/// decompilation (e.g. HTTP 429 or 503) after which the decompilation is
+/// reported as failed.
+const MAX_TRANSIENT_POLL_FAILURES: u32 = 5;
+
+/// Result of a single decompilation from a batch.
+pub struct BatchResult {
+    /// Index of the arguments of the decompilation in the batch.
+    pub index: usize,
+
+    /// ID of the decompilation, or `None` when the decompilation could not be
+    /// started.
+    pub id: Option<String>,
+
+    /// The successfully finished decompilation, or an error when the
+    /// decompilation could not be started, its status could not be obtained,
+    /// or it failed.
+    pub result: Result<Decompilation>,
+}
+
+/// A batch of decompilations.
+///
+/// At most [`max_in_flight()`](#method.max_in_flight) decompilations run at
+/// the same time. Statuses of all running decompilations are polled together
+/// from the current thread. The batch is an iterator that yields results in
+/// the order in which the decompilations finish. An error concerning a single
+/// decompilation does not abort the rest of the batch. Transient failures to
+/// obtain the status of a decompilation (HTTP 429 or 5xx, or a broken
+/// connection) are retried during the following polls.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use retdec::error::Result;
+/// # fn test() -> Result<()> {
+/// use retdec::decompilation::DecompilationArguments;
+/// use retdec::decompiler::Decompiler;
+/// use retdec::file::File;
+/// use retdec::settings::Settings;
+///
+/// let decompiler = Decompiler::new(Settings::new().with_api_key("MY-API-KEY"));
+/// let mut args = Vec::new();
+/// for path in &["a.exe", "b.exe", "c.exe"] {
+///     args.push(DecompilationArguments::new().with_input_file(File::from_path(path)?));
+/// }
+/// for result in decompiler.start_batch(args).with_max_in_flight(2) {
+///     match result.result {
+///         Ok(mut decompilation) => print!("{}", decompilation.get_output_hll_code()?),
+///         Err(err) => eprintln!("decompilation #{} failed: {}", result.index, err),
+///     }
+/// }
+/// # Ok(()) } fn main() { test().unwrap() }
+/// ```
+pub struct DecompilationBatch<'a> {
+    decompiler: &'a Decompiler,
+    pending: Box<Iterator<Item = DecompilationArguments> + 'a>,
+    next_index: usize,
+    running: Vec<(usize, Decompilation, u32)>,
+    completed: VecDeque<BatchResult>,
+    max_in_flight: usize,
+    poll_interval: Duration,
+}
+
+impl<'a> DecompilationBatch<'a> {
+    /// Creates a batch of decompilations with the given arguments that will be
+    /// started via the given decompiler.
+    ///
+    /// No decompilation is started until the batch is iterated. The arguments
+    /// are taken from the given iterator lazily, only when there is room for
+    /// another decompilation, so input files do not have to be loaded into
+    /// memory all at once.
+    pub fn new<I>(decompiler: &'a Decompiler, args: I) -> Self
+        where I: IntoIterator<Item = DecompilationArguments>,
+              I::IntoIter: 'a
+    {
+        DecompilationBatch {
+            decompiler: decompiler,
+            pending: Box::new(args.into_iter().fuse()),
+            next_index: 0,
+            running: Vec::new(),
+            completed: VecDeque::new(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            poll_interval: Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+        }
+    }
+
+    /// Sets the maximal number of decompilations that run at the same time.
+    ///
+    /// Zero is treated as one.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.set_max_in_flight(max_in_flight);
+        self
+    }
+
+    /// Sets the time between two polls of statuses of running
+    /// decompilations.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.set_poll_interval(poll_interval);
+        self
+    }
+
+    /// Sets the maximal number of decompilations that run at the same time.
+    ///
+    /// Zero is treated as one.
+    pub fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        self.max_in_flight = if max_in_flight == 0 { 1 } else { max_in_flight };
+    }
+
+    /// Sets the time between two polls of statuses of running
+    /// decompilations.
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) {
+        self.poll_interval = poll_interval;
+    }
+
+    /// Returns the maximal number of decompilations that run at the same
+    /// time.
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    /// Returns the time between two polls of statuses of running
+    /// decompilations.
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Returns the number of decompilations that have not been started yet.
+    ///
+    /// The arguments are taken from the given iterator lazily, so when the
+    /// iterator does not know its length, this is only a lower bound.
+    pub fn pending_count(&self) -> usize {
+        self.pending.size_hint().0
+    }
+
+    /// Returns the number of decompilations that are currently running.
+    pub fn running_count(&self) -> usize {
+        self.running.len()
+    }
+
+    fn start_pending_decompilations(&mut self) {
+        while self.running.len() < self.max_in_flight {
+            let args = match self.pending.next() {
+                Some(args) => args,
+                None => break,
+            };
+            let index = self.next_index;
+            self.next_index += 1;
+            match self.decompiler.start_decompilation(args) {
+                Ok(decompilation) => self.running.push((index, decompilation, 0)),
+                Err(err) => self.completed.push_back(BatchResult {
+                    index: index,
+                    id: None,
+                    result: Err(err),
+                }),
+            }
+        }
+    }
+
+    fn poll_running_decompilations(&mut self) {
+        let mut still_running = Vec::with_capacity(self.running.len());
+        for (index, mut decompilation, failed_polls) in self.running.drain(..) {
+            let id = Some(decompilation.id().to_string());
+            match finished_decompilation_result(&mut decompilation) {
+                Ok(false) => still_running.push((index, decompilation, 0)),
+                Err(ref err) if is_transient_error(err) &&
+                        failed_polls + 1 < MAX_TRANSIENT_POLL_FAILURES => {
+                    still_running.push((index, decompilation, failed_polls + 1));
+                }
+                Ok(true) => self.completed.push_back(BatchResult {
+                    index: index,
+                    id: id,
+                    result: Ok(decompilation),
+                }),
+                Err(err) => self.completed.push_back(BatchResult {
+                    index: index,
+                    id: id,
+                    result: Err(err),
+                }),
+            }
+        }
+        self.running = still_running;
+    }
+}
+
+impl<'a> Iterator for DecompilationBatch<'a> {
+    type Item = BatchResult;
+
+    fn next(&mut self) -> Option<BatchResult> {
+        loop {
+            if let Some(result) = self.completed.pop_front() {
+                return Some(result);
+            }
+
+            self.start_pending_decompilations();
+            if !self.completed.is_empty() {
+                continue;
+            }
+            if self.running.is_empty() {
+                return None;
+            }
+
+            self.poll_running_decompilations();
+            if self.completed.is_empty() {
+                thread::sleep(self.poll_interval);
+            }
+        }
+    }
+}
+
+/// Updates the status of the given decompilation and returns whether it has
+/// successfully finished.
+///
+/// Returns an error when the decompilation has failed.
+fn finished_decompilation_result(decompilation: &mut Decompilation) -> Result<bool> {
+    let finished = decompilation.has_finished()
+        .chain_err(|| format!("failed to update status of decompilation {}", decompilation.id()))?;
+    if !finished {
+        return Ok(false);
+    }
+    if !decompilation.has_succeeded()? {
+        bail!(ErrorKind::JobFailed(format!(
+            "decompilation {} failed: {}",
+            decompilation.id(),
+            decompilation.error().unwrap_or("unknown error")
+        )));
+    }
+    Ok(true)
+}
+
+/// Is the given error likely to go away when the request is repeated?
+fn is_transient_error(err: &Error) -> bool {
+    chain_contains(err, |kind| match *kind {
+        ErrorKind::ConnectionFailed(_) => true,
+        ErrorKind::RequestFailed(status_code, _) => {
+            status_code == 429 || status_code >= 500
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use file::File;
+    use settings::Settings;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
+
+    const API_URL: &'static str = "https://retdec.com/service/api";
+
+    fn create_decompiler() -> (Rc<RefCell<APIConnectionMock>>, Decompiler) {
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url(API_URL);
+        let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings)));
+        let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
+        (conn, Decompiler::with_transport(conn_factory))
+    }
+
+    fn args_for(name: &str) -> DecompilationArguments {
+        DecompilationArguments::new()
+            .with_input_file(File::from_content_with_name(b"content", name))
+    }
+
+    fn add_start_response(conn: &Rc<RefCell<APIConnectionMock>>, id: &str) {
+        conn.borrow_mut().add_response(
+            "POST",
+            format!("{}/decompiler/decompilations", API_URL),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(format!(r#"{{"id": "{}"}}"#, id).as_bytes())
+                    .build()
+            )
+        );
+    }
+
+    fn add_status_response(conn: &Rc<RefCell<APIConnectionMock>>,
+                           id: &str,
+                           finished: bool,
+                           succeeded: bool) {
+        let body = format!(
+            r#"{{"finished": {}, "succeeded": {}, "failed": {}, "error": {}}}"#,
+            finished,
+            succeeded,
+            finished && !succeeded,
+            if finished && !succeeded { r#""decompilation error""# } else { "null" }
+        );
+        conn.borrow_mut().add_response(
+            "GET",
+            format!("{}/decompiler/decompilations/{}/status", API_URL, id),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(body.as_bytes())
+                    .build()
+            )
+        );
+    }
+
+    fn add_failed_status_response(conn: &Rc<RefCell<APIConnectionMock>>,
+                                  id: &str,
+                                  status_code: u16) {
+        conn.borrow_mut().add_response(
+            "GET",
+            format!("{}/decompiler/decompilations/{}/status", API_URL, id),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(status_code)
+                    .with_body(br#"{"code": 0, "message": "error", "description": "error"}"#)
+                    .build()
+            )
+        );
+    }
+
+    fn sent_urls(conn: &Rc<RefCell<APIConnectionMock>>) -> Vec<String> {
+        conn.borrow().sent_requests().iter()
+            .map(|r| format!("{} {}", r.method(), &r.url()[API_URL.len()..]))
+            .collect()
+    }
+
+    #[test]
+    fn batch_yields_results_in_order_of_completion() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_start_response(&conn, "B");
+        add_status_response(&conn, "A", false, false);
+        add_status_response(&conn, "B", true, true);
+        add_status_response(&conn, "A", true, true);
+        let batch = decompiler.start_batch(vec![args_for("a.exe"), args_for("b.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].index, 1);
+        assert_eq!(results[0].result.as_ref().unwrap().id(), "B");
+        assert_eq!(results[1].index, 0);
+        assert_eq!(results[1].result.as_ref().unwrap().id(), "A");
+    }
+
+    #[test]
+    fn batch_keeps_at_most_max_in_flight_decompilations_running() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_start_response(&conn, "B");
+        add_status_response(&conn, "A", true, true);
+        add_status_response(&conn, "B", true, true);
+        let batch = decompiler.start_batch(vec![args_for("a.exe"), args_for("b.exe")])
+            .with_max_in_flight(1)
+            .with_poll_interval(Duration::from_millis(0));
+
+        assert_eq!(batch.count(), 2);
+        assert_eq!(sent_urls(&conn), vec![
+            "POST /decompiler/decompilations",
+            "GET /decompiler/decompilations/A/status",
+            "POST /decompiler/decompilations",
+            "GET /decompiler/decompilations/B/status",
+        ]);
+    }
+
+    #[test]
+    fn batch_reports_failed_decompilation_and_continues() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_start_response(&conn, "B");
+        add_status_response(&conn, "A", true, false);
+        add_status_response(&conn, "B", true, true);
+        let batch = decompiler.start_batch(vec![args_for("a.exe"), args_for("b.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 2);
+        let err = results[0].result.as_ref().err().unwrap();
+        assert_eq!(err.to_string(), "decompilation A failed: decompilation error");
+        assert_eq!(results[0].id, Some("A".to_string()));
+        assert!(results[1].result.is_ok());
+    }
+
+    #[test]
+    fn batch_retries_transient_failures_to_obtain_status() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_failed_status_response(&conn, "A", 429);
+        add_failed_status_response(&conn, "A", 503);
+        add_status_response(&conn, "A", true, true);
+        let batch = decompiler.start_batch(vec![args_for("a.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].result.is_ok());
+    }
+
+    #[test]
+    fn batch_reports_decompilation_whose_status_cannot_be_obtained_repeatedly() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        for _ in 0..MAX_TRANSIENT_POLL_FAILURES {
+            add_failed_status_response(&conn, "A", 503);
+        }
+        let batch = decompiler.start_batch(vec![args_for("a.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].result.is_err());
+    }
+
+    #[test]
+    fn batch_does_not_retry_non_transient_failures_to_obtain_status() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_failed_status_response(&conn, "A", 404);
+        let batch = decompiler.start_batch(vec![args_for("a.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].result.is_err());
+        assert_eq!(sent_urls(&conn).len(), 2);
+    }
+
+    #[test]
+    fn pending_count_returns_number_of_decompilations_not_started_yet() {
+        let (_, decompiler) = create_decompiler();
+
+        let batch = decompiler.start_batch(vec![args_for("a.exe"), args_for("b.exe")]);
+
+        assert_eq!(batch.pending_count(), 2);
+    }
+
+    #[test]
+    fn batch_reports_decompilation_that_cannot_be_started_and_continues() {
+        let (conn, decompiler) = create_decompiler();
+        add_start_response(&conn, "A");
+        add_status_response(&conn, "A", true, true);
+        let batch = decompiler.start_batch(vec![DecompilationArguments::new(), args_for("a.exe")])
+            .with_poll_interval(Duration::from_millis(0));
+
+        let results: Vec<_> = batch.collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].index, 0);
+        assert!(results[0].id.is_none());
+        assert!(results[0].result.is_err());
+        assert_eq!(results[1].index, 1);
+        assert!(results[1].result.is_ok());
+    }
+
+    #[test]
+    fn empty_batch_yields_nothing() {
+        let (conn, decompiler) = create_decompiler();
+
+        let mut batch = decompiler.start_batch(Vec::new());
+
+        assert!(batch.next().is_none());
+        assert!(conn.borrow().no_requests_sent());
+    }
+
+    #[test]
+    fn set_max_in_flight_treats_zero_as_one() {
+        let (_, decompiler) = create_decompiler();
+
+        let batch = decompiler.start_batch(Vec::new()).with_max_in_flight(0);
+
+        assert_eq!(batch.max_in_flight(), 1);
+    }
+}
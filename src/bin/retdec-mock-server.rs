@@ -0,0 +1,10 @@
+//! A local server mimicking the [retdec.com](https://retdec.com) public [REST
+//! API](https://retdec.com/api/). It allows running the
+//! [retdec-rust](https://github.com/s3rvac/retdec-rust) library and tools
+//! offline.
+
+extern crate retdec;
+
+fn main() {
+    retdec::tools::mock_server::main();
+}
@@ -0,0 +1,10 @@
+//! A program for checking authentication to and connectivity with the
+//! [retdec.com](https://retdec.com) public [REST
+//! API](https://retdec.com/api/). Internally, it uses the
+//! [retdec-rust](https://github.com/s3rvac/retdec-rust) library.
+
+extern crate retdec;
+
+fn main() {
+    retdec::tools::test::main();
+}
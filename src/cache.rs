@@ -0,0 +1,853 @@
+//! On-disk cache of results of decompilations and analyses.
+//!
+//! The cache is keyed by a digest of the API URL, the API key, the arguments,
+//! and the content of the input files. When a decompilation or analysis with the same
+//! key has already finished successfully, it is not started again. Instead,
+//! its status and outputs are served from the cache without accessing the
+//! network. Outputs that have not been downloaded before are requested from
+//! the original decompilation or analysis (and cached afterwards).
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use retdec::error::Result;
+//! # fn test() -> Result<()> {
+//! use retdec::cache::ResultCache;
+//! use retdec::decompiler::Decompiler;
+//! use retdec::settings::Settings;
+//!
+//! let decompiler = Decompiler::new(Settings::new())
+//!     .with_cache(ResultCache::new("cache").with_max_size(100 * 1024 * 1024));
+//! // Use the decompiler as usual...
+//! # Ok(()) } fn main() { test().unwrap() }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use connection::APIArguments;
+use connection::APIConnection;
+use connection::APIConnectionFactory;
+use connection::APIResponse;
+use connection::Header;
+use connection::Headers;
+use content_disposition::content_disposition_with_file_name;
+use content_disposition::file_name_from_content_disposition;
+use error::Result;
+use error::ResultExt;
+use file::File;
+use utils::Sha256Writer;
+use utils::non_empty_env_var;
+
+/// Default maximal size of the cache (in bytes).
+pub const DEFAULT_MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+/// URLs (relative to the API URL) to which requests starting decompilations
+/// and analyses are sent.
+const START_PATHS: &'static [&'static str] = &[
+    "/decompiler/decompilations",
+    "/fileinfo/analyses",
+];
+
+/// Name of the file in which the ID of a cached job is stored. It is written
+/// last, so an entry is complete if and only if it contains this file.
+const ID_FILE_NAME: &'static str = "id";
+
+/// Name of the file in which the final status of a cached job is stored.
+const STATUS_FILE_NAME: &'static str = "status";
+
+/// Name of the file in which the name of the input file of a cached job is
+/// stored.
+const INPUT_NAME_FILE_NAME: &'static str = "input-name";
+
+/// Suffix of files in which `Content-Disposition` headers are stored.
+const DISPOSITION_SUFFIX: &'static str = ".disposition";
+
+/// On-disk cache of results of decompilations and analyses.
+///
+/// Only jobs that have finished successfully are cached. When the total size
+/// of the cache exceeds its maximal size, the least recently used entries are
+/// evicted.
+#[derive(Clone, Debug)]
+pub struct ResultCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+
+impl ResultCache {
+    /// Creates a cache stored in the given directory.
+    ///
+    /// The directory is created when the first result is stored. The maximal
+    /// size of the cache is [`DEFAULT_MAX_SIZE`](constant.DEFAULT_MAX_SIZE.html).
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ResultCache {
+            dir: dir.into(),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Returns the default directory of the cache.
+    ///
+    /// It is taken from the `RETDEC_CACHE_DIR` environment variable. If it is
+    /// not set, `retdec` in the user's cache directory is used (e.g.
+    /// `~/.cache/retdec` on Linux). Returns `None` when no directory can be
+    /// determined.
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(dir) = non_empty_env_var("RETDEC_CACHE_DIR") {
+            return Some(PathBuf::from(dir));
+        }
+        if let Some(dir) = non_empty_env_var("XDG_CACHE_HOME") {
+            return Some(Path::new(&dir).join("retdec"));
+        }
+        if cfg!(windows) {
+            if let Some(dir) = non_empty_env_var("LOCALAPPDATA") {
+                return Some(Path::new(&dir).join("retdec").join("cache"));
+            }
+        }
+        if cfg!(target_os = "macos") {
+            if let Some(dir) = non_empty_env_var("HOME") {
+                return Some(Path::new(&dir).join("Library").join("Caches").join("retdec"));
+            }
+        }
+        non_empty_env_var("HOME")
+            .map(|dir| Path::new(&dir).join(".cache").join("retdec"))
+    }
+
+    /// Sets the maximal size of the cache (in bytes).
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.set_max_size(max_size);
+        self
+    }
+
+    /// Sets the maximal size of the cache (in bytes).
+    pub fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = max_size;
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the maximal size of the cache (in bytes).
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Returns the total size of all cached results (in bytes).
+    pub fn size(&self) -> Result<u64> {
+        Ok(self.entries()?.iter().map(|e| e.size).sum())
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .chain_err(|| format!("failed to remove {:?}", self.dir))?;
+        }
+        Ok(())
+    }
+
+    /// Evicts the least recently used entries until the cache fits into its
+    /// maximal size.
+    pub fn evict(&self) -> Result<()> {
+        let mut entries = self.entries()?;
+        let mut size: u64 = entries.iter().map(|e| e.size).sum();
+        entries.sort_by_key(|e| e.last_used);
+        for entry in entries {
+            if size <= self.max_size {
+                break;
+            }
+            fs::remove_dir_all(&entry.path)
+                .chain_err(|| format!("failed to remove {:?}", entry.path))?;
+            size -= entry.size;
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<EntryInfo>> {
+        let mut entries = Vec::new();
+        if !self.dir.exists() {
+            return Ok(entries);
+        }
+        let dir_entries = fs::read_dir(&self.dir)
+            .chain_err(|| format!("failed to read {:?}", self.dir))?;
+        for dir_entry in dir_entries {
+            let path = dir_entry
+                .chain_err(|| format!("failed to read {:?}", self.dir))?
+                .path();
+            if path.is_dir() {
+                entries.push(EntryInfo::read(path)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entry_dir(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+
+    /// Returns the ID of the job with the given key when the cache contains a
+    /// complete entry for it.
+    fn cached_id(&self, key: &str) -> Option<String> {
+        let id_path = self.entry_dir(key).join(ID_FILE_NAME);
+        let id = File::from_path(&id_path).ok()?.content_as_text().ok()?.to_string();
+        // Rewriting the file marks the entry as recently used.
+        let _ = File::from_content_with_name(id.as_bytes(), ID_FILE_NAME).save_as(&id_path);
+        Some(id)
+    }
+}
+
+/// Information about a single entry of the cache.
+struct EntryInfo {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+impl EntryInfo {
+    fn read(path: PathBuf) -> Result<Self> {
+        let mut size = 0;
+        let mut last_used = UNIX_EPOCH;
+        let files = fs::read_dir(&path)
+            .chain_err(|| format!("failed to read {:?}", path))?;
+        for file in files {
+            let metadata = file
+                .and_then(|f| f.metadata())
+                .chain_err(|| format!("failed to read {:?}", path))?;
+            size += metadata.len();
+            if let Ok(modified) = metadata.modified() {
+                if modified > last_used {
+                    last_used = modified;
+                }
+            }
+        }
+        Ok(EntryInfo {
+            path: path,
+            size: size,
+            last_used: last_used,
+        })
+    }
+}
+
+/// A job whose results are being served from or stored into the cache.
+struct CachedJob {
+    dir: PathBuf,
+    base_url: String,
+    input_name: Option<String>,
+}
+
+impl CachedJob {
+    /// Returns the path to the file in which the response for the given URL
+    /// is stored, or `None` when the URL does not belong to the job.
+    fn path_for(&self, url: &str) -> Option<PathBuf> {
+        if !url.starts_with(&self.base_url) {
+            return None;
+        }
+        let suffix = &url[self.base_url.len()..];
+        if !suffix.starts_with('/') || suffix.len() < 2 {
+            return None;
+        }
+        let name = suffix[1..].replace('/', "-");
+        let is_safe = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_safe || name == ID_FILE_NAME || name == INPUT_NAME_FILE_NAME {
+            return None;
+        }
+        Some(self.dir.join(name))
+    }
+
+    fn load(&self, url: &str) -> Option<APIResponse> {
+        let path = self.path_for(url)?;
+        let body = File::from_path(&path).ok()?;
+        let mut headers = Headers::new();
+        if let Some(file_name) = self.cached_file_name(&path) {
+            headers.add(Header::new(
+                "Content-Disposition",
+                content_disposition_with_file_name(&file_name)
+            ));
+        }
+        Some(APIResponse::new(url, 200, "OK", headers, body.content().to_vec()))
+    }
+
+    /// Returns the name of the cached output stored in the given path,
+    /// adjusted to the current input file.
+    ///
+    /// The entry may have been created for an input with the same content
+    /// but a different name (e.g. `a.exe` instead of `b.exe`). The API names
+    /// outputs after the input, so the name of the original input is
+    /// replaced with the name of the current one (`a.c` becomes `b.c`). When
+    /// this is not possible, `None` is returned and the caller falls back to
+    /// its default name.
+    fn cached_file_name(&self, path: &Path) -> Option<String> {
+        let disposition = File::from_path(disposition_path(path)).ok()?;
+        let file_name = file_name_from_content_disposition(
+            disposition.content_as_text().ok()?
+        )?;
+        let cached_input_name = File::from_path(self.dir.join(INPUT_NAME_FILE_NAME)).ok()
+            .and_then(|f| f.content_as_text().ok().map(str::to_string));
+        match (cached_input_name, self.input_name.as_ref()) {
+            (None, None) => Some(file_name),
+            (Some(ref cached), Some(current)) if cached == current => Some(file_name),
+            (Some(cached), Some(current)) => {
+                let cached_stem = file_stem(&cached);
+                if cached_stem.is_empty() || !file_name.starts_with(cached_stem) {
+                    return None;
+                }
+                Some(format!("{}{}", file_stem(current), &file_name[cached_stem.len()..]))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Connection that serves responses from the cache and stores successful
+/// responses into it.
+struct CachingAPIConnection {
+    conn: Box<APIConnection>,
+    cache: ResultCache,
+    job: Option<CachedJob>,
+}
+
+impl CachingAPIConnection {
+    fn is_start_url(&self, url: &str) -> bool {
+        let api_url = self.conn.api_url();
+        url.starts_with(api_url) && START_PATHS.iter().any(|p| *p == &url[api_url.len()..])
+    }
+
+    fn start_job(&mut self, url: &str, args: APIArguments) -> Result<APIResponse> {
+        let key = cache_key(url, self.conn.api_key(), &args)?;
+        let input_name = input_file_name(&args);
+        if let Some(id) = self.cache.cached_id(&key) {
+            self.job = Some(CachedJob {
+                dir: self.cache.entry_dir(&key),
+                base_url: format!("{}/{}", url, id),
+                input_name: input_name,
+            });
+            let body = object!{ "id" => id }.dump().into_bytes();
+            return Ok(APIResponse::new(url, 200, "OK", Headers::new(), body));
+        }
+
+        let response = self.conn.send_post_request(url, args)?;
+        if let Some(id) = response.json_value_as_string("id") {
+            self.job = Some(CachedJob {
+                dir: self.cache.entry_dir(&key),
+                base_url: format!("{}/{}", url, id),
+                input_name: input_name,
+            });
+        }
+        Ok(response)
+    }
+
+    fn store(&self, path: &Path, response: &APIResponse) -> Result<()> {
+        let job = match self.job {
+            Some(ref job) => job,
+            None => return Ok(()),
+        };
+        let is_status = path.file_name().map_or(false, |name| name == STATUS_FILE_NAME);
+        if is_status {
+            // Only finished, successful jobs are cached. Their ID is stored
+            // last, which makes the entry complete.
+            let finished = response.json_value_as_bool("finished").unwrap_or(false);
+            let succeeded = response.json_value_as_bool("succeeded").unwrap_or(false);
+            if !finished || !succeeded {
+                return Ok(());
+            }
+            self.save(path, response.body())?;
+            if let Some(ref input_name) = job.input_name {
+                self.save(&job.dir.join(INPUT_NAME_FILE_NAME), input_name.as_bytes())?;
+            }
+            let id = job.base_url.rsplit('/').next().unwrap_or("");
+            self.save(&job.dir.join(ID_FILE_NAME), id.as_bytes())?;
+        } else {
+            self.store_disposition(path, response)?;
+            self.save(path, response.body())?;
+        }
+        self.cache.evict()
+    }
+
+    fn store_disposition(&self, path: &Path, response: &APIResponse) -> Result<()> {
+        if let Some(disposition) = response.headers().first_value_for("Content-Disposition") {
+            self.save(&disposition_path(path), disposition.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, path: &Path, content: &[u8]) -> Result<()> {
+        let dir = path.parent().unwrap_or(self.cache.dir());
+        fs::create_dir_all(dir)
+            .chain_err(|| format!("failed to create {:?}", dir))?;
+        File::from_content_with_name(content, "").save_as(path)
+    }
+
+    fn download_into(&mut self,
+                     path: &Path,
+                     url: &str,
+                     args: APIArguments,
+                     writer: &mut Write) -> Result<(APIResponse, u64)> {
+        let tmp_path = path.with_extension("part");
+        let cache_file = path.parent()
+            .and_then(|dir| fs::create_dir_all(dir).ok())
+            .and_then(|_| fs::File::create(&tmp_path).ok());
+        let mut tee = TeeWriter {
+            writer: writer,
+            cache_file: cache_file,
+        };
+        let result = self.conn.send_get_request_into(url, args, &mut tee);
+        // The cache file has to be closed before it is moved into place.
+        let cached = tee.cache_file.take().map_or(false, |f| f.sync_all().is_ok());
+        match result {
+            Ok((ref response, _)) if response.succeeded() && cached => {
+                // The cache is only an optimization, so failures to store
+                // responses into it are not reported.
+                let _ = self.store_disposition(path, response)
+                    .and_then(|_| {
+                        fs::rename(&tmp_path, path)
+                            .chain_err(|| format!("failed to move {:?}", tmp_path))
+                    })
+                    .and_then(|_| self.cache.evict());
+            }
+            _ => {
+                let _ = fs::remove_file(&tmp_path);
+            }
+        }
+        result
+    }
+}
+
+impl APIConnection for CachingAPIConnection {
+    fn api_url(&self) -> &str {
+        self.conn.api_url()
+    }
+
+    fn api_key(&self) -> Option<&str> {
+        self.conn.api_key()
+    }
+
+    fn send_get_request(&mut self,
+                        url: &str,
+                        args: APIArguments) -> Result<APIResponse> {
+        let path = match self.job {
+            Some(ref job) => {
+                if let Some(response) = job.load(url) {
+                    return Ok(response);
+                }
+                job.path_for(url)
+            }
+            None => None,
+        };
+        let response = self.conn.send_get_request(url, args)?;
+        if let Some(path) = path {
+            if response.succeeded() {
+                // The cache is only an optimization, so failures to store
+                // responses into it are not reported.
+                let _ = self.store(&path, &response);
+            }
+        }
+        Ok(response)
+    }
+
+    fn send_post_request(&mut self,
+                         url: &str,
+                         args: APIArguments) -> Result<APIResponse> {
+        if self.is_start_url(url) {
+            return self.start_job(url, args);
+        }
+        self.conn.send_post_request(url, args)
+    }
+
+    fn send_get_request_into(&mut self,
+                             url: &str,
+                             args: APIArguments,
+                             writer: &mut Write) -> Result<(APIResponse, u64)> {
+        let path = match self.job {
+            Some(ref job) => {
+                if let Some(response) = job.load(url) {
+                    writer.write_all(response.body())
+                        .chain_err(|| format!("failed to write the body of a response from {}", url))?;
+                    let written = response.body().len() as u64;
+                    let response = APIResponse::new(
+                        url, 200, "OK", response.headers().clone(), Vec::new()
+                    );
+                    return Ok((response, written));
+                }
+                job.path_for(url)
+            }
+            None => None,
+        };
+        match path {
+            Some(ref path) if path.file_name().map_or(false, |n| n != STATUS_FILE_NAME) => {
+                self.download_into(path, url, args, writer)
+            }
+            _ => self.conn.send_get_request_into(url, args, writer),
+        }
+    }
+}
+
+/// Factory for connections that serve results of already finished
+/// decompilations and analyses from a cache.
+///
+/// It wraps another factory, whose connections are used for requests that
+/// cannot be served from the cache. Usually, it is not used directly but via
+/// `Decompiler::with_cache()` or `Fileinfo::with_cache()`.
+pub struct CachingAPIConnectionFactory {
+    conn_factory: Box<APIConnectionFactory>,
+    cache: ResultCache,
+}
+
+impl CachingAPIConnectionFactory {
+    /// Creates a new factory that wraps the given factory and uses the given
+    /// cache.
+    pub fn new(conn_factory: Box<APIConnectionFactory>, cache: ResultCache) -> Self {
+        CachingAPIConnectionFactory {
+            conn_factory: conn_factory,
+            cache: cache,
+        }
+    }
+}
+
+impl APIConnectionFactory for CachingAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(CachingAPIConnection {
+            conn: self.conn_factory.new_connection(),
+            cache: self.cache.clone(),
+            job: None,
+        })
+    }
+}
+
+/// A writer that writes into the given writer and a cache file.
+///
+/// Failures to write into the cache file are not reported; the file is
+/// merely dropped.
+struct TeeWriter<'a> {
+    writer: &'a mut Write,
+    cache_file: Option<fs::File>,
+}
+
+impl<'a> Write for TeeWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        let failed = match self.cache_file {
+            Some(ref mut file) => file.write_all(&buf[..written]).is_err(),
+            None => false,
+        };
+        if failed {
+            self.cache_file = None;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Computes the key of a job started by sending the given arguments to the
+/// given URL with the given API key.
+///
+/// Arguments are normalized (sorted by their names) and files are identified
+/// by their content (their names do not matter). The API key is included so
+/// that jobs of different accounts sharing the cache are kept apart.
+fn cache_key(url: &str, api_key: Option<&str>, args: &APIArguments) -> Result<String> {
+    let mut digest = Sha256Writer::new();
+    let write_err = "failed to compute a digest of arguments";
+    writeln!(digest, "{}", url).chain_err(|| write_err)?;
+    let mut api_key_digest = Sha256Writer::new();
+    api_key_digest.write_all(api_key.unwrap_or("").as_bytes()).chain_err(|| write_err)?;
+    writeln!(digest, "api key {}", api_key_digest.hex_digest()).chain_err(|| write_err)?;
+    let sorted_args: BTreeMap<_, _> = args.args().collect();
+    for (name, value) in sorted_args {
+        writeln!(digest, "arg {:?}={:?}", name, value).chain_err(|| write_err)?;
+    }
+    let sorted_files: BTreeMap<_, _> = args.files().collect();
+    for (name, file) in sorted_files {
+        writeln!(digest, "file {:?} {}", name, file.content_len()).chain_err(|| write_err)?;
+        digest.write_all(file.content()).chain_err(|| write_err)?;
+    }
+    let sorted_streamed_files: BTreeMap<_, _> = args.streamed_files().collect();
+    for (name, file) in sorted_streamed_files {
+        writeln!(digest, "file {:?} {}", name, file.len()).chain_err(|| write_err)?;
+        file.copy_to(&mut digest)?;
+    }
+    Ok(digest.hex_digest())
+}
+
+/// Returns the name of the input file in the given arguments (if any).
+fn input_file_name(args: &APIArguments) -> Option<String> {
+    args.files()
+        .find(|&(name, _)| name == "input")
+        .map(|(_, file)| file.name().to_string())
+        .or_else(|| {
+            args.streamed_files()
+                .find(|&(name, _)| name == "input")
+                .map(|(_, file)| file.name().to_string())
+        })
+}
+
+/// Returns the name of the given file without its extension.
+fn file_stem(name: &str) -> &str {
+    Path::new(name).file_stem().and_then(|s| s.to_str()).unwrap_or("")
+}
+
+fn disposition_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name.push_str(DISPOSITION_SUFFIX);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use tempdir::TempDir;
+
+    use decompilation::DecompilationArguments;
+    use decompilation::OutputKind;
+    use decompiler::Decompiler;
+    use settings::Settings;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
+
+    const API_URL: &'static str = "https://retdec.com/service/api";
+    const BASE_URL: &'static str =
+        "https://retdec.com/service/api/decompiler/decompilations/ID";
+
+    fn create_decompiler(cache: &ResultCache) -> (Rc<RefCell<APIConnectionMock>>, Decompiler) {
+        create_decompiler_with_api_key(cache, "test")
+    }
+
+    fn create_decompiler_with_api_key(cache: &ResultCache,
+                                      api_key: &str) -> (Rc<RefCell<APIConnectionMock>>, Decompiler) {
+        let settings = Settings::new()
+            .with_api_key(api_key)
+            .with_api_url(API_URL);
+        let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings)));
+        let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
+        let decompiler = Decompiler::with_transport(conn_factory)
+            .with_cache(cache.clone());
+        (conn, decompiler)
+    }
+
+    fn add_successful_decompilation(conn: &Rc<RefCell<APIConnectionMock>>) {
+        let mut conn = conn.borrow_mut();
+        conn.add_response(
+            "POST",
+            format!("{}/decompiler/decompilations", API_URL),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{"id": "ID"}"#)
+                    .build()
+            )
+        );
+        conn.add_response(
+            "GET",
+            format!("{}/status", BASE_URL),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false,
+                        "error": null
+                    }"#)
+                    .build()
+            )
+        );
+        conn.add_response(
+            "GET",
+            format!("{}/outputs/hll", BASE_URL),
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_header("Content-Disposition", "attachment; filename=file.c")
+                    .with_body(b"int main() {}")
+                    .build()
+            )
+        );
+    }
+
+    fn decompile(decompiler: &Decompiler, content: &[u8]) -> File {
+        decompile_named(decompiler, content, "file.exe")
+    }
+
+    fn decompile_named(decompiler: &Decompiler, content: &[u8], name: &str) -> File {
+        let args = DecompilationArguments::new()
+            .with_input_file(File::from_content_with_name(content, name));
+        let mut decompilation = decompiler.start_decompilation(args).unwrap();
+        decompilation.wait_until_finished().unwrap();
+        decompilation.get_output_hll_code_as_file().unwrap()
+    }
+
+    #[test]
+    fn second_decompilation_of_same_file_is_served_from_cache() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler(&cache);
+        add_successful_decompilation(&conn);
+        decompile(&decompiler, b"content");
+        conn.borrow_mut().reset();
+
+        let output = decompile(&decompiler, b"content");
+
+        assert!(conn.borrow().no_requests_sent());
+        assert_eq!(output.name(), "file.c");
+        assert_eq!(output.content(), b"int main() {}");
+    }
+
+    #[test]
+    fn decompilation_of_different_file_is_not_served_from_cache() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler(&cache);
+        add_successful_decompilation(&conn);
+        decompile(&decompiler, b"content");
+        conn.borrow_mut().reset();
+        add_successful_decompilation(&conn);
+
+        decompile(&decompiler, b"other content");
+
+        assert_eq!(conn.borrow().sent_requests().len(), 3);
+    }
+
+    #[test]
+    fn output_of_cached_decompilation_is_named_after_current_input_file() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler(&cache);
+        add_successful_decompilation(&conn);
+        decompile_named(&decompiler, b"content", "file.exe");
+        conn.borrow_mut().reset();
+
+        let output = decompile_named(&decompiler, b"content", "other.exe");
+
+        assert!(conn.borrow().no_requests_sent());
+        assert_eq!(output.name(), "other.c");
+        assert_eq!(output.content(), b"int main() {}");
+    }
+
+    #[test]
+    fn decompilation_with_different_api_key_is_not_served_from_cache() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler_with_api_key(&cache, "key1");
+        add_successful_decompilation(&conn);
+        decompile(&decompiler, b"content");
+        let (conn, decompiler) = create_decompiler_with_api_key(&cache, "key2");
+        add_successful_decompilation(&conn);
+
+        decompile(&decompiler, b"content");
+
+        assert_eq!(conn.borrow().sent_requests().len(), 3);
+    }
+
+    #[test]
+    fn unfinished_decompilation_is_not_cached() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler(&cache);
+        add_successful_decompilation(&conn);
+        let args = DecompilationArguments::new()
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"));
+
+        decompiler.start_decompilation(args).unwrap();
+
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+
+    #[test]
+    fn written_output_is_cached() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path());
+        let (conn, decompiler) = create_decompiler(&cache);
+        add_successful_decompilation(&conn);
+        let args = DecompilationArguments::new()
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"));
+        let mut decompilation = decompiler.start_decompilation(args.clone()).unwrap();
+        decompilation.wait_until_finished().unwrap();
+        decompilation.write_output_to(OutputKind::Hll, &mut Vec::new()).unwrap();
+        conn.borrow_mut().reset();
+
+        let mut decompilation = decompiler.start_decompilation(args).unwrap();
+        decompilation.wait_until_finished().unwrap();
+        let mut output = Vec::new();
+        let written = decompilation.write_output_to(OutputKind::Hll, &mut output).unwrap();
+
+        assert!(conn.borrow().no_requests_sent());
+        assert_eq!(written, 13);
+        assert_eq!(output, b"int main() {}");
+    }
+
+    #[test]
+    fn evict_removes_least_recently_used_entries_when_cache_is_too_large() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path()).with_max_size(15);
+        fs::create_dir(tmp_dir.path().join("old")).unwrap();
+        File::from_content_with_name(b"0123456789", "id")
+            .save_as(tmp_dir.path().join("old").join("id")).unwrap();
+        ::std::thread::sleep(::std::time::Duration::from_millis(20));
+        fs::create_dir(tmp_dir.path().join("new")).unwrap();
+        File::from_content_with_name(b"0123456789", "id")
+            .save_as(tmp_dir.path().join("new").join("id")).unwrap();
+
+        cache.evict().unwrap();
+
+        assert!(!tmp_dir.path().join("old").exists());
+        assert!(tmp_dir.path().join("new").exists());
+        assert_eq!(cache.size().unwrap(), 10);
+    }
+
+    #[test]
+    fn clear_removes_all_entries() {
+        let tmp_dir = TempDir::new("retdec-cache-test").unwrap();
+        let cache = ResultCache::new(tmp_dir.path().join("cache"));
+        fs::create_dir_all(tmp_dir.path().join("cache").join("key")).unwrap();
+
+        cache.clear().unwrap();
+
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+
+    #[test]
+    fn cache_key_does_not_depend_on_file_names() {
+        let mut args1 = APIArguments::new();
+        args1.add_file("input", File::from_content_with_name(b"content", "a.exe"));
+        let mut args2 = APIArguments::new();
+        args2.add_file("input", File::from_content_with_name(b"content", "b.exe"));
+
+        assert_eq!(cache_key("url", None, &args1).unwrap(), cache_key("url", None, &args2).unwrap());
+    }
+
+    #[test]
+    fn cache_key_depends_on_api_key() {
+        let args = APIArguments::new();
+
+        assert_ne!(
+            cache_key("url", Some("key1"), &args).unwrap(),
+            cache_key("url", Some("key2"), &args).unwrap()
+        );
+    }
+
+    #[test]
+    fn cache_key_depends_on_arguments() {
+        let mut args1 = APIArguments::new();
+        args1.add_string_arg("mode", "bin");
+        let mut args2 = APIArguments::new();
+        args2.add_string_arg("mode", "raw");
+
+        assert_ne!(cache_key("url", None, &args1).unwrap(), cache_key("url", None, &args2).unwrap());
+    }
+}
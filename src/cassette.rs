@@ -0,0 +1,774 @@
+//! Recording and replaying of sessions with the API.
+//!
+//! `RecordingAPIConnectionFactory` wraps another transport and writes every
+//! request/response pair into a cassette file. `ReplayingAPIConnectionFactory`
+//! then serves the recorded responses without accessing the network, which
+//! makes it possible to run deterministic tests in environments without an API
+//! key.
+//!
+//! The cassette is a JSON file. Input files are not stored in it; only their
+//! names, sizes, and SHA-256 digests are recorded. Every occurrence of the API
+//! key is redacted.
+//!
+//! # Examples
+//!
+//! Recording a session:
+//!
+//! ```no_run
+//! # use retdec::error::Result;
+//! # fn test() -> Result<()> {
+//! use retdec::cassette::RecordingAPIConnectionFactory;
+//! use retdec::connection::HyperAPIConnectionFactory;
+//! use retdec::decompiler::Decompiler;
+//! use retdec::settings::Settings;
+//!
+//! let settings = Settings::new()
+//!     .with_api_key("MY-API-KEY");
+//! let decompiler = Decompiler::with_transport(
+//!     Box::new(RecordingAPIConnectionFactory::new(
+//!         Box::new(HyperAPIConnectionFactory::new(settings.clone())),
+//!         "session.json",
+//!         &settings
+//!     ))
+//! );
+//! // Use the decompiler as usual...
+//! # Ok(()) } fn main() { test().unwrap() }
+//! ```
+//!
+//! Replaying it:
+//!
+//! ```no_run
+//! # use retdec::error::Result;
+//! # fn test() -> Result<()> {
+//! use retdec::cassette::ReplayingAPIConnectionFactory;
+//! use retdec::decompiler::Decompiler;
+//!
+//! let decompiler = Decompiler::with_transport(
+//!     Box::new(ReplayingAPIConnectionFactory::from_file("session.json")?)
+//! );
+//! // Use the decompiler as usual...
+//! # Ok(()) } fn main() { test().unwrap() }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str;
+
+use json::JsonValue;
+use json;
+
+use connection::APIArguments;
+use connection::APIConnection;
+use connection::APIConnectionFactory;
+use connection::APIResponse;
+use connection::Header;
+use connection::Headers;
+use error::Result;
+use error::ResultExt;
+use settings::Settings;
+use utils::Sha256Writer;
+use utils::sha256_hex;
+
+/// Replacement for the API key in recorded sessions.
+const REDACTED: &'static str = "<REDACTED>";
+
+/// A file that was sent in a recorded request.
+#[derive(Clone, Debug, PartialEq)]
+struct RecordedFile {
+    name: String,
+    size: usize,
+    sha256: String,
+}
+
+/// A recorded request.
+#[derive(Clone, Debug, PartialEq)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    args: BTreeMap<String, String>,
+    files: BTreeMap<String, RecordedFile>,
+}
+
+impl RecordedRequest {
+    fn new(method: &str, url: &str, args: &APIArguments) -> Self {
+        RecordedRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            args: args.args()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            files: args.files()
+                .map(|(k, f)| {
+                    (k.clone(), RecordedFile {
+                        name: f.name().to_string(),
+                        size: f.content_len(),
+                        sha256: sha256_hex(f.content()),
+                    })
+                })
+                .chain(args.streamed_files().map(|(k, f)| {
+                    // When the file cannot be read, the request itself fails,
+                    // so the digest does not matter.
+                    let mut digest = Sha256Writer::new();
+                    let sha256 = match f.copy_to(&mut digest) {
+                        Ok(_) => digest.hex_digest(),
+                        Err(_) => String::new(),
+                    };
+                    (k.clone(), RecordedFile {
+                        name: f.name().to_string(),
+                        size: f.len() as usize,
+                        sha256: sha256,
+                    })
+                }))
+                .collect(),
+        }
+    }
+
+    fn redacted(mut self, api_key: Option<&str>) -> Self {
+        self.url = redact(&self.url, api_key);
+        for value in self.args.values_mut() {
+            *value = redact(value, api_key);
+        }
+        self
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut args = JsonValue::new_object();
+        for (name, value) in &self.args {
+            args[name.as_str()] = value.as_str().into();
+        }
+        let mut files = JsonValue::new_object();
+        for (name, file) in &self.files {
+            files[name.as_str()] = object!{
+                "name" => file.name.as_str(),
+                "size" => file.size,
+                "sha256" => file.sha256.as_str()
+            };
+        }
+        object!{
+            "method" => self.method.as_str(),
+            "url" => self.url.as_str(),
+            "args" => args,
+            "files" => files
+        }
+    }
+
+    fn from_json(json: &JsonValue) -> Result<Self> {
+        let mut args = BTreeMap::new();
+        for (name, value) in json["args"].entries() {
+            args.insert(name.to_string(), json_str(value, "args")?.to_string());
+        }
+        let mut files = BTreeMap::new();
+        for (name, file) in json["files"].entries() {
+            files.insert(name.to_string(), RecordedFile {
+                name: json_str(&file["name"], "name")?.to_string(),
+                size: file["size"].as_usize()
+                    .ok_or("invalid cassette: invalid or missing size")?,
+                sha256: json_str(&file["sha256"], "sha256")?.to_string(),
+            });
+        }
+        Ok(RecordedRequest {
+            method: json_str(&json["method"], "method")?.to_string(),
+            url: json_str(&json["url"], "url")?.to_string(),
+            args: args,
+            files: files,
+        })
+    }
+}
+
+/// A recorded response.
+#[derive(Clone, Debug)]
+struct RecordedResponse {
+    status_code: u16,
+    status_message: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl RecordedResponse {
+    fn new(response: &APIResponse, api_key: Option<&str>) -> Self {
+        let body = match str::from_utf8(response.body()) {
+            Ok(body) => redact(body, api_key).into_bytes(),
+            Err(_) => response.body().clone(),
+        };
+        RecordedResponse {
+            status_code: response.status_code(),
+            status_message: response.status_message().to_string(),
+            headers: response.headers().iter()
+                .map(|h| (h.name.clone(), redact(&h.value, api_key)))
+                .collect(),
+            body: body,
+        }
+    }
+
+    fn to_api_response(&self, request_url: &str) -> APIResponse {
+        let mut headers = Headers::new();
+        for &(ref name, ref value) in &self.headers {
+            headers.add(Header::new(name.as_str(), value.as_str()));
+        }
+        APIResponse::new(
+            request_url,
+            self.status_code,
+            self.status_message.as_str(),
+            headers,
+            self.body.clone()
+        )
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let headers: Vec<JsonValue> = self.headers.iter()
+            .map(|&(ref name, ref value)| array![name.as_str(), value.as_str()])
+            .collect();
+        let mut json = object!{
+            "status_code" => self.status_code,
+            "status_message" => self.status_message.as_str(),
+            "headers" => JsonValue::Array(headers)
+        };
+        // Text bodies are stored as they are so that cassettes are readable.
+        // Binary bodies (e.g. archives) are stored in hexadecimal.
+        match str::from_utf8(&self.body) {
+            Ok(body) => json["body"] = body.into(),
+            Err(_) => json["body_hex"] = to_hex(&self.body).into(),
+        }
+        json
+    }
+
+    fn from_json(json: &JsonValue) -> Result<Self> {
+        let mut headers = Vec::new();
+        for header in json["headers"].members() {
+            headers.push((
+                json_str(&header[0], "headers")?.to_string(),
+                json_str(&header[1], "headers")?.to_string()
+            ));
+        }
+        let body = if json.has_key("body_hex") {
+            from_hex(json_str(&json["body_hex"], "body_hex")?)?
+        } else {
+            json_str(&json["body"], "body")?.as_bytes().to_vec()
+        };
+        Ok(RecordedResponse {
+            status_code: json["status_code"].as_u16()
+                .ok_or("invalid cassette: invalid or missing status_code")?,
+            status_message: json_str(&json["status_message"], "status_message")?.to_string(),
+            headers: headers,
+            body: body,
+        })
+    }
+}
+
+/// A single request/response pair.
+#[derive(Clone, Debug)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+    used: bool,
+}
+
+/// A recorded session with the API.
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    api_url: String,
+    interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Creates an empty cassette for the given API URL.
+    pub fn new<U: Into<String>>(api_url: U) -> Self {
+        Cassette {
+            api_url: api_url.into(),
+            interactions: Vec::new(),
+        }
+    }
+
+    /// Loads a cassette from the given file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .chain_err(|| format!("failed to read cassette {:?}", path))?;
+        Self::from_json_str(&content)
+            .chain_err(|| format!("failed to parse cassette {:?}", path))
+    }
+
+    /// Stores the cassette into the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let mut file = fs::File::create(path)
+            .chain_err(|| format!("failed to open {:?} for writing", path))?;
+        file.write_all(self.to_json().pretty(2).as_bytes())
+            .chain_err(|| format!("failed to write cassette into {:?}", path))?;
+        Ok(())
+    }
+
+    /// Returns the URL to the API that was used during recording.
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Returns the number of recorded interactions (request/response pairs).
+    pub fn len(&self) -> usize {
+        self.interactions.len()
+    }
+
+    /// Is the cassette empty?
+    pub fn is_empty(&self) -> bool {
+        self.interactions.is_empty()
+    }
+
+    /// Returns the number of interactions that have not been replayed yet.
+    pub fn unused_interactions_count(&self) -> usize {
+        self.interactions.iter().filter(|i| !i.used).count()
+    }
+
+    fn record(&mut self, request: RecordedRequest, response: RecordedResponse) {
+        self.interactions.push(Interaction {
+            request: request,
+            response: response,
+            used: false,
+        });
+    }
+
+    fn replay(&mut self, request: &RecordedRequest) -> Option<&RecordedResponse> {
+        // Identical requests (e.g. status checks) are answered in the order in
+        // which they were recorded.
+        for interaction in &mut self.interactions {
+            if !interaction.used && interaction.request == *request {
+                interaction.used = true;
+                return Some(&interaction.response);
+            }
+        }
+        None
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let interactions: Vec<JsonValue> = self.interactions.iter()
+            .map(|i| object!{
+                "request" => i.request.to_json(),
+                "response" => i.response.to_json()
+            })
+            .collect();
+        object!{
+            "api_url" => self.api_url.as_str(),
+            "interactions" => JsonValue::Array(interactions)
+        }
+    }
+
+    fn from_json_str(content: &str) -> Result<Self> {
+        let json = json::parse(content)
+            .chain_err(|| "invalid cassette: not a JSON file")?;
+        let mut cassette = Cassette::new(json_str(&json["api_url"], "api_url")?);
+        for interaction in json["interactions"].members() {
+            cassette.record(
+                RecordedRequest::from_json(&interaction["request"])?,
+                RecordedResponse::from_json(&interaction["response"])?
+            );
+        }
+        Ok(cassette)
+    }
+}
+
+/// Connection that records all requests and responses into a cassette.
+struct RecordingAPIConnection {
+    conn: Box<APIConnection>,
+    recorder: Rc<RefCell<Recorder>>,
+}
+
+impl RecordingAPIConnection {
+    fn record(&self,
+              method: &str,
+              url: &str,
+              args: &APIArguments,
+              response: Result<APIResponse>) -> Result<APIResponse> {
+        let response = response?;
+        self.recorder.borrow_mut().record(
+            RecordedRequest::new(method, url, args),
+            &response
+        )?;
+        Ok(response)
+    }
+}
+
+impl APIConnection for RecordingAPIConnection {
+    fn api_url(&self) -> &str {
+        self.conn.api_url()
+    }
+
+    fn api_key(&self) -> Option<&str> {
+        self.conn.api_key()
+    }
+
+    fn send_get_request(&mut self,
+                        url: &str,
+                        args: APIArguments) -> Result<APIResponse> {
+        let response = self.conn.send_get_request(url, args.clone());
+        self.record("GET", url, &args, response)
+    }
+
+    fn send_post_request(&mut self,
+                         url: &str,
+                         args: APIArguments) -> Result<APIResponse> {
+        let recorded_args = args.clone();
+        let response = self.conn.send_post_request(url, args);
+        self.record("POST", url, &recorded_args, response)
+    }
+}
+
+/// A cassette that is being recorded.
+struct Recorder {
+    cassette: Cassette,
+    path: PathBuf,
+    api_key: Option<String>,
+}
+
+impl Recorder {
+    fn record(&mut self, request: RecordedRequest, response: &APIResponse) -> Result<()> {
+        let api_key = self.api_key.as_ref().map(String::as_str);
+        self.cassette.record(
+            request.redacted(api_key),
+            RecordedResponse::new(response, api_key)
+        );
+        // The cassette is saved after every interaction so that nothing is
+        // lost when the recorded program terminates prematurely.
+        self.cassette.save(&self.path)
+    }
+}
+
+/// Factory for connections that record all requests and responses into a
+/// cassette file.
+///
+/// The connections are created by the wrapped factory. The cassette file is
+/// rewritten after every interaction.
+pub struct RecordingAPIConnectionFactory {
+    conn_factory: Box<APIConnectionFactory>,
+    recorder: Rc<RefCell<Recorder>>,
+}
+
+impl RecordingAPIConnectionFactory {
+    /// Creates a new factory that wraps the given factory and records into
+    /// the given path.
+    ///
+    /// The API key from the given settings is redacted from everything that
+    /// is recorded.
+    pub fn new<P>(conn_factory: Box<APIConnectionFactory>,
+                  cassette_path: P,
+                  settings: &Settings) -> Self
+        where P: Into<PathBuf>
+    {
+        RecordingAPIConnectionFactory {
+            conn_factory: conn_factory,
+            recorder: Rc::new(RefCell::new(Recorder {
+                cassette: Cassette::new(settings.api_url()),
+                path: cassette_path.into(),
+                api_key: settings.api_key().map(|k| k.to_string()),
+            })),
+        }
+    }
+
+    /// Returns a copy of the so-far recorded cassette.
+    pub fn cassette(&self) -> Cassette {
+        self.recorder.borrow().cassette.clone()
+    }
+}
+
+impl APIConnectionFactory for RecordingAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(RecordingAPIConnection {
+            conn: self.conn_factory.new_connection(),
+            recorder: self.recorder.clone(),
+        })
+    }
+}
+
+/// Connection that serves responses from a cassette.
+struct ReplayingAPIConnection {
+    api_url: String,
+    cassette: Rc<RefCell<Cassette>>,
+}
+
+impl ReplayingAPIConnection {
+    fn replay(&self, method: &str, url: &str, args: &APIArguments) -> Result<APIResponse> {
+        let request = RecordedRequest::new(method, url, args);
+        let mut cassette = self.cassette.borrow_mut();
+        match cassette.replay(&request) {
+            Some(response) => Ok(response.to_api_response(url)),
+            None => bail!(
+                "unexpected {} request to {} (no matching request in the cassette)",
+                method,
+                url
+            ),
+        }
+    }
+}
+
+impl APIConnection for ReplayingAPIConnection {
+    fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    fn send_get_request(&mut self,
+                        url: &str,
+                        args: APIArguments) -> Result<APIResponse> {
+        self.replay("GET", url, &args)
+    }
+
+    fn send_post_request(&mut self,
+                         url: &str,
+                         args: APIArguments) -> Result<APIResponse> {
+        self.replay("POST", url, &args)
+    }
+}
+
+/// Factory for connections that serve responses from a cassette.
+///
+/// No network access is performed. A request that is not in the cassette (or
+/// whose recorded responses have all been used) results in an error.
+pub struct ReplayingAPIConnectionFactory {
+    cassette: Rc<RefCell<Cassette>>,
+}
+
+impl ReplayingAPIConnectionFactory {
+    /// Creates a new factory replaying the given cassette.
+    pub fn new(cassette: Cassette) -> Self {
+        ReplayingAPIConnectionFactory {
+            cassette: Rc::new(RefCell::new(cassette)),
+        }
+    }
+
+    /// Creates a new factory replaying a cassette from the given file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    /// Returns the number of interactions that have not been replayed yet.
+    ///
+    /// This is useful for checking that the replayed session is complete.
+    pub fn unused_interactions_count(&self) -> usize {
+        self.cassette.borrow().unused_interactions_count()
+    }
+}
+
+impl APIConnectionFactory for ReplayingAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(ReplayingAPIConnection {
+            api_url: self.cassette.borrow().api_url().to_string(),
+            cassette: self.cassette.clone(),
+        })
+    }
+}
+
+fn redact(s: &str, api_key: Option<&str>) -> String {
+    match api_key {
+        Some(api_key) if !api_key.is_empty() => s.replace(api_key, REDACTED),
+        _ => s.to_string(),
+    }
+}
+
+fn json_str<'a>(json: &'a JsonValue, name: &str) -> Result<&'a str> {
+    json.as_str()
+        .ok_or_else(|| format!("invalid cassette: invalid or missing {}", name).into())
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        bail!("invalid cassette: odd number of hexadecimal digits");
+    }
+    let mut data = Vec::with_capacity(hex.len() / 2);
+    for i in 0..hex.len() / 2 {
+        let byte = hex.get(2 * i..2 * i + 2)
+            .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+            .ok_or("invalid cassette: invalid hexadecimal digits")?;
+        data.push(byte);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    use tempdir::TempDir;
+
+    use file::File;
+    use file::StreamedFile;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
+
+    fn create_settings() -> Settings {
+        Settings::new()
+            .with_api_key("SECRET-KEY")
+            .with_api_url("https://retdec.com/service/api")
+    }
+
+    fn record_session(cassette_path: &Path) -> Cassette {
+        let settings = create_settings();
+        let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings.clone())));
+        conn.borrow_mut().add_response(
+            "POST",
+            "https://retdec.com/service/api/fileinfo/analyses",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{"id": "ID", "key": "SECRET-KEY"}"#)
+                    .build()
+            )
+        );
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_file(File::from_content_with_name(b"\xff\x00", "output.bin"))
+                    .build()
+            )
+        );
+        let factory = RecordingAPIConnectionFactory::new(
+            Box::new(APIConnectionFactoryMock::new(conn)),
+            cassette_path,
+            &settings
+        );
+
+        let mut rec_conn = factory.new_connection();
+        rec_conn.send_post_request(
+            "https://retdec.com/service/api/fileinfo/analyses",
+            APIArgumentsBuilder::new()
+                .with_string_arg("output_format", "json")
+                .with_file("input", File::from_content_with_name(b"content", "file.exe"))
+                .build()
+        ).expect("expected the POST request to succeed");
+        rec_conn.send_get_request_without_args(
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output"
+        ).expect("expected the GET request to succeed");
+        factory.cassette()
+    }
+
+    #[test]
+    fn recording_connection_records_all_interactions_into_file() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+
+        let cassette = record_session(&path);
+
+        assert_eq!(cassette.len(), 2);
+        let loaded = Cassette::load(&path).expect("expected the cassette to be loadable");
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.api_url(), "https://retdec.com/service/api");
+    }
+
+    #[test]
+    fn recording_connection_redacts_api_key_and_stores_file_digests() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+
+        record_session(&path);
+
+        let mut content = String::new();
+        fs::File::open(&path).unwrap().read_to_string(&mut content).unwrap();
+        assert!(!content.contains("SECRET-KEY"));
+        assert!(content.contains(REDACTED));
+        assert!(content.contains(&sha256_hex(b"content")));
+        assert!(content.contains(r#""body_hex": "ff00""#));
+    }
+
+    #[test]
+    fn replaying_connection_serves_recorded_responses() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+        record_session(&path);
+        let factory = ReplayingAPIConnectionFactory::from_file(&path).unwrap();
+        let mut conn = factory.new_connection();
+
+        let response = conn.send_get_request_without_args(
+            "https://retdec.com/service/api/fileinfo/analyses/ID/output"
+        ).expect("expected the request to be replayed");
+
+        assert_eq!(conn.api_url(), "https://retdec.com/service/api");
+        assert_eq!(response.status_code(), 200);
+        assert_eq!(response.body(), b"\xff\x00");
+        assert_eq!(response.body_as_file().unwrap().name(), "output.bin");
+        assert_eq!(factory.unused_interactions_count(), 1);
+    }
+
+    #[test]
+    fn replaying_connection_matches_files_by_digest() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+        record_session(&path);
+        let factory = ReplayingAPIConnectionFactory::from_file(&path).unwrap();
+        let mut conn = factory.new_connection();
+
+        let result = conn.send_post_request(
+            "https://retdec.com/service/api/fileinfo/analyses",
+            APIArgumentsBuilder::new()
+                .with_string_arg("output_format", "json")
+                .with_file("input", File::from_content_with_name(b"other", "file.exe"))
+                .build()
+        );
+
+        let err = result.err().expect("expected the request to fail");
+        assert_eq!(
+            err.to_string(),
+            "unexpected POST request to https://retdec.com/service/api/fileinfo/analyses \
+             (no matching request in the cassette)"
+        );
+    }
+
+    #[test]
+    fn replaying_connection_matches_streamed_file_with_recorded_file() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+        record_session(&path);
+        let factory = ReplayingAPIConnectionFactory::from_file(&path).unwrap();
+        let mut conn = factory.new_connection();
+
+        let response = conn.send_post_request(
+            "https://retdec.com/service/api/fileinfo/analyses",
+            APIArgumentsBuilder::new()
+                .with_string_arg("output_format", "json")
+                .with_streamed_file(
+                    "input",
+                    StreamedFile::from_reader(Cursor::new(b"content"), 7, "file.exe")
+                )
+                .build()
+        ).expect("expected the request to be replayed");
+
+        assert_eq!(response.status_code(), 200);
+    }
+
+    #[test]
+    fn replaying_connection_fails_when_recorded_responses_are_exhausted() {
+        let tmp_dir = TempDir::new("retdec-cassette-test").unwrap();
+        let path = tmp_dir.path().join("session.json");
+        record_session(&path);
+        let factory = ReplayingAPIConnectionFactory::from_file(&path).unwrap();
+        let mut conn = factory.new_connection();
+        let url = "https://retdec.com/service/api/fileinfo/analyses/ID/output";
+
+        conn.send_get_request_without_args(url)
+            .expect("expected the first request to be replayed");
+        let result = conn.send_get_request_without_args(url);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn cassette_load_returns_error_for_invalid_file() {
+        let result = Cassette::from_json_str("{}");
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! Configuration files with named profiles.
+//!
+//! A configuration file consists of profiles. Each profile starts with its
+//! name in brackets and contains `key = value` pairs:
+//!
+//! ```text
+//! # Used when no profile is explicitly selected.
+//! [default]
+//! api_key = MY-API-KEY
+//!
+//! [work]
+//! api_key = MY-WORK-API-KEY
+//! api_url = https://retdec.example.com/service/api
+//! proxy = http://proxy.example.com:3128
+//! timeout = 60
+//! decompiler.target_language = py
+//! decompiler.decomp_var_names = simple
+//! ```
+//!
+//! The following keys are supported:
+//!
+//! * `api_key`, `api_url`, `proxy`: See the corresponding methods of
+//!   [`Settings`](../settings/struct.Settings.html).
+//! * `timeout`: Timeout for reading and writing data (in seconds).
+//! * `decompiler.NAME`: Default value of the decompilation parameter `NAME`
+//!   (e.g. `decompiler.mode`). All parameters of
+//!   [`DecompilationArguments`](../decompilation/struct.DecompilationArguments.html)
+//!   except the input file are supported; `decompiler.pdb_file` is a path to
+//!   the PDB file. Boolean parameters take `yes` or `no`.
+//!
+//! Empty lines and lines starting with `#` or `;` are ignored. Values may be
+//! enclosed in double quotes.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use api_key::ApiKey;
+use decompilation::DecompilationArguments;
+use error::Result;
+use error::ResultExt;
+use file::File;
+use secret::Secret;
+use utils::non_empty_env_var;
+
+/// Name of the profile that is used when no profile is explicitly selected.
+pub const DEFAULT_PROFILE: &'static str = "default";
+
+/// Names of decompilation parameters that can be set in profiles.
+const DECOMPILATION_PARAMS: &'static [&'static str] = &[
+    "mode",
+    "target_language",
+    "architecture",
+    "endian",
+    "raw_entry_point",
+    "raw_section_vma",
+    "pdb_file",
+    "ar_index",
+    "ar_name",
+    "sel_decomp_funcs",
+    "sel_decomp_ranges",
+    "sel_decomp_decoding",
+    "decomp_var_names",
+    "decomp_optimizations",
+    "decomp_unreach_funcs",
+    "decomp_emit_addresses",
+    "generate_cg",
+    "generate_cfgs",
+    "generate_archive",
+];
+
+/// A named set of settings and default decompilation parameters.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Profile {
+    name: String,
+    api_key: Option<ApiKey>,
+    api_url: Option<String>,
+    proxy: Option<Secret>,
+    timeout: Option<Duration>,
+    decompilation_params: BTreeMap<String, String>,
+}
+
+impl Profile {
+    /// Returns the name of the profile.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the API key (if set).
+    pub fn api_key(&self) -> Option<&str> {
+        self.api_key.as_ref().map(ApiKey::expose)
+    }
+
+    /// Returns the API URL (if set).
+    pub fn api_url(&self) -> Option<&str> {
+        self.api_url.as_ref().map(String::as_str)
+    }
+
+    /// Returns the proxy (if set).
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_ref().map(Secret::expose)
+    }
+
+    /// Returns the timeout for reading and writing data (if set).
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the default value of the given decompilation parameter (if
+    /// set).
+    pub fn decompilation_param(&self, name: &str) -> Option<&str> {
+        self.decompilation_params.get(name).map(String::as_str)
+    }
+
+    /// Sets the default decompilation parameters from the profile in the
+    /// given arguments.
+    ///
+    /// Parameters that are not set in the profile are left untouched. Fails
+    /// when a parameter has an invalid value or the PDB file cannot be read.
+    pub fn apply_to_decompilation_args(&self, args: &mut DecompilationArguments) -> Result<()> {
+        for (name, value) in &self.decompilation_params {
+            let value = value.as_str();
+            match name.as_str() {
+                "mode" => args.set_mode(value),
+                "target_language" => args.set_target_language(value),
+                "architecture" => args.set_architecture(value),
+                "endian" => args.set_endian(value),
+                "raw_entry_point" => args.set_raw_entry_point(value),
+                "raw_section_vma" => args.set_raw_section_vma(value),
+                "pdb_file" => args.set_pdb_file(File::from_path(value)?),
+                "ar_index" => {
+                    let index = value.parse()
+                        .chain_err(|| format!("invalid value of decompiler.ar_index: {}", value))?;
+                    args.set_ar_index(index);
+                }
+                "ar_name" => args.set_ar_name(value),
+                "sel_decomp_funcs" => args.set_sel_decomp_funcs(value),
+                "sel_decomp_ranges" => args.set_sel_decomp_ranges(value),
+                "sel_decomp_decoding" => args.set_sel_decomp_decoding(value),
+                "decomp_var_names" => args.set_decomp_var_names(value),
+                "decomp_optimizations" => args.set_decomp_optimizations(value),
+                "decomp_unreach_funcs" => {
+                    args.set_decomp_unreachable_funcs(parse_bool(name, value)?);
+                }
+                "decomp_emit_addresses" => {
+                    args.set_decomp_emit_addresses(parse_bool(name, value)?);
+                }
+                "generate_cg" => args.set_generate_cg(parse_bool(name, value)?),
+                "generate_cfgs" => args.set_generate_cfgs(parse_bool(name, value)?),
+                "generate_archive" => args.set_generate_archive(parse_bool(name, value)?),
+                _ => bail!("unsupported decompilation parameter: {}", name),
+            }
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "api_key" => self.api_key = Some(ApiKey::new(value)),
+            "api_url" => self.api_url = Some(value.to_string()),
+            "proxy" => self.proxy = Some(Secret::new(value)),
+            "timeout" => {
+                match value.parse() {
+                    Ok(secs) if secs > 0 => self.timeout = Some(Duration::from_secs(secs)),
+                    _ => bail!("invalid timeout: {} (expected a number of seconds)", value),
+                }
+            }
+            _ if key.starts_with("decompiler.") => {
+                let name = &key["decompiler.".len()..];
+                if !DECOMPILATION_PARAMS.iter().any(|param| *param == name) {
+                    bail!("unknown decompilation parameter: {}", name);
+                }
+                self.decompilation_params.insert(name.to_string(), value.to_string());
+            }
+            _ => bail!("unknown key: {}", key),
+        }
+        Ok(())
+    }
+}
+
+/// A configuration file with named profiles.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Config {
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Returns the default path to the configuration file.
+    ///
+    /// It is `$XDG_CONFIG_HOME/retdec/config.ini` when `XDG_CONFIG_HOME` is
+    /// set, `%APPDATA%\retdec\config.ini` on Windows, and
+    /// `~/.config/retdec/config.ini` otherwise. Returns `None` when the path
+    /// cannot be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(dir) = non_empty_env_var("XDG_CONFIG_HOME") {
+            return Some(Path::new(&dir).join("retdec").join("config.ini"));
+        }
+        if cfg!(windows) {
+            if let Some(dir) = non_empty_env_var("APPDATA") {
+                return Some(Path::new(&dir).join("retdec").join("config.ini"));
+            }
+        }
+        non_empty_env_var("HOME")
+            .map(|dir| Path::new(&dir).join(".config").join("retdec").join("config.ini"))
+    }
+
+    /// Reads a configuration from the given file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .chain_err(|| format!("failed to read the configuration file {:?}", path))?;
+        Self::parse(&content)
+            .chain_err(|| format!("failed to parse the configuration file {:?}", path))
+    }
+
+    /// Parses a configuration from the given string.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config = Config::default();
+        let mut current: Option<String> = None;
+        for (i, line) in content.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                if !line.ends_with(']') {
+                    bail!("line {}: missing ']'", line_number);
+                }
+                let name = line[1..line.len() - 1].trim();
+                if name.is_empty() {
+                    bail!("line {}: empty profile name", line_number);
+                }
+                if config.profiles.contains_key(name) {
+                    bail!("line {}: duplicate profile '{}'", line_number, name);
+                }
+                config.profiles.insert(name.to_string(), Profile {
+                    name: name.to_string(),
+                    ..Profile::default()
+                });
+                current = Some(name.to_string());
+                continue;
+            }
+
+            let (key, value) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), unquote(line[pos + 1..].trim())),
+                None => bail!("line {}: expected 'key = value'", line_number),
+            };
+            let profile = match current {
+                Some(ref name) => config.profiles.get_mut(name)
+                    .expect("missing profile - this should never happen"),
+                None => bail!("line {}: '{}' is not inside a profile", line_number, key),
+            };
+            profile.set(key, value)
+                .chain_err(|| format!("line {}", line_number))?;
+        }
+        Ok(config)
+    }
+
+    /// Reads the configuration file at the given path, or at the default path
+    /// when no path is given, and returns the profile with the given name.
+    ///
+    /// When no name is given, the [`DEFAULT_PROFILE`](constant.DEFAULT_PROFILE.html)
+    /// is returned (if present). A missing file at the default path is not an
+    /// error unless a profile was explicitly requested.
+    pub fn load_profile(path: Option<&Path>, name: Option<&str>) -> Result<Option<Profile>> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(ref path) if path.is_file() => path.clone(),
+                _ => match name {
+                    Some(name) => bail!(
+                        "cannot use profile '{}' because there is no configuration file", name
+                    ),
+                    None => return Ok(None),
+                },
+            },
+        };
+        let config = Self::from_path(&path)?;
+        match name {
+            Some(name) => match config.profile(name) {
+                Some(profile) => Ok(Some(profile.clone())),
+                None => bail!("there is no profile '{}' in {:?}", name, path),
+            },
+            None => Ok(config.profile(DEFAULT_PROFILE).cloned()),
+        }
+    }
+
+    /// Returns the profile with the given name (if any).
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.get(name)
+    }
+
+    /// Returns names of all profiles (in alphabetical order).
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool> {
+    match value {
+        "yes" | "true" | "1" => Ok(true),
+        "no" | "false" | "0" => Ok(false),
+        _ => bail!("invalid value of decompiler.{}: {} (expected yes or no)", name, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use tempdir::TempDir;
+
+    fn write_file(path: &Path, content: &str) {
+        fs::File::create(path).unwrap().write_all(content.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn parse_returns_profiles_with_given_values() {
+        let config = Config::parse(r#"
+            # Comment.
+            [default]
+            api_key = KEY
+
+            ; Another comment.
+            [work]
+            api_key = "WORK KEY"
+            api_url = https://retdec.example.com/service/api
+            proxy = http://proxy:3128
+            timeout = 60
+            decompiler.mode = raw
+        "#).unwrap();
+
+        assert_eq!(config.profile_names(), vec!["default", "work"]);
+        let default = config.profile("default").unwrap();
+        assert_eq!(default.name(), "default");
+        assert_eq!(default.api_key(), Some("KEY"));
+        assert_eq!(default.api_url(), None);
+        let work = config.profile("work").unwrap();
+        assert_eq!(work.api_key(), Some("WORK KEY"));
+        assert_eq!(work.api_url(), Some("https://retdec.example.com/service/api"));
+        assert_eq!(work.proxy(), Some("http://proxy:3128"));
+        assert_eq!(work.timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(work.decompilation_param("mode"), Some("raw"));
+    }
+
+    #[test]
+    fn parse_returns_error_for_key_outside_profile() {
+        assert!(Config::parse("api_key = KEY\n").is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_for_unknown_key() {
+        assert!(Config::parse("[p]\nunknown = 1\n").is_err());
+        assert!(Config::parse("[p]\ndecompiler.unknown = 1\n").is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_for_invalid_timeout() {
+        assert!(Config::parse("[p]\ntimeout = soon\n").is_err());
+        assert!(Config::parse("[p]\ntimeout = 0\n").is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_for_malformed_lines() {
+        assert!(Config::parse("[p\n").is_err());
+        assert!(Config::parse("[]\n").is_err());
+        assert!(Config::parse("[p]\napi_key\n").is_err());
+        assert!(Config::parse("[p]\n[p]\n").is_err());
+    }
+
+    #[test]
+    fn apply_to_decompilation_args_sets_params_from_profile() {
+        let config = Config::parse(
+            "[p]\n\
+             decompiler.target_language = py\n\
+             decompiler.ar_index = 2\n\
+             decompiler.decomp_emit_addresses = no\n\
+             decompiler.generate_cg = yes\n"
+        ).unwrap();
+        let mut args = DecompilationArguments::new()
+            .with_decomp_var_names("simple");
+
+        config.profile("p").unwrap().apply_to_decompilation_args(&mut args).unwrap();
+
+        assert_eq!(args.target_language().map(String::as_str), Some("py"));
+        assert_eq!(args.ar_index(), Some(2));
+        assert_eq!(args.decomp_emit_addresses(), Some(false));
+        assert_eq!(args.generate_cg(), Some(true));
+        assert_eq!(args.decomp_var_names().map(String::as_str), Some("simple"));
+    }
+
+    #[test]
+    fn apply_to_decompilation_args_returns_error_for_invalid_bool() {
+        let config = Config::parse("[p]\ndecompiler.generate_cg = maybe\n").unwrap();
+        let mut args = DecompilationArguments::new();
+
+        let result = config.profile("p").unwrap().apply_to_decompilation_args(&mut args);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_profile_returns_requested_profile_from_given_file() {
+        let tmp_dir = TempDir::new("retdec-config-test").unwrap();
+        let path = tmp_dir.path().join("config.ini");
+        write_file(&path, "[default]\napi_key = A\n[work]\napi_key = B\n");
+
+        let default = Config::load_profile(Some(&path), None).unwrap().unwrap();
+        let work = Config::load_profile(Some(&path), Some("work")).unwrap().unwrap();
+
+        assert_eq!(default.api_key(), Some("A"));
+        assert_eq!(work.api_key(), Some("B"));
+    }
+
+    #[test]
+    fn load_profile_returns_none_when_there_is_no_default_profile() {
+        let tmp_dir = TempDir::new("retdec-config-test").unwrap();
+        let path = tmp_dir.path().join("config.ini");
+        write_file(&path, "[work]\napi_key = B\n");
+
+        assert!(Config::load_profile(Some(&path), None).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_profile_returns_error_for_missing_profile_or_file() {
+        let tmp_dir = TempDir::new("retdec-config-test").unwrap();
+        let path = tmp_dir.path().join("config.ini");
+        write_file(&path, "[work]\napi_key = B\n");
+
+        assert!(Config::load_profile(Some(&path), Some("home")).is_err());
+        assert!(Config::load_profile(Some(&tmp_dir.path().join("none.ini")), None).is_err());
+    }
+}
@@ -1,43 +1,150 @@
 //! Connectivity to `retdec.com`'s API.
+//!
+//! The services (decompiler, fileinfo, test) talk to the API through a
+//! transport, which is represented by the `APIConnection` trait. New
+//! connections are created by an `APIConnectionFactory`. By default,
+//! `HyperAPIConnectionFactory` is used, which sends requests via
+//! [hyper](https://hyper.rs/). You can plug in your own transport by
+//! implementing these two traits and passing your factory to
+//! `Decompiler::with_transport()`, `Fileinfo::with_transport()`, or
+//! `Test::with_transport()`.
+//!
+//! # Examples
+//!
+//! A transport that logs all requests and delegates them to the default one:
+//!
+//! ```
+//! use retdec::connection::APIArguments;
+//! use retdec::connection::APIConnection;
+//! use retdec::connection::APIConnectionFactory;
+//! use retdec::connection::APIResponse;
+//! use retdec::connection::HyperAPIConnectionFactory;
+//! use retdec::decompiler::Decompiler;
+//! use retdec::error::Result;
+//! use retdec::settings::Settings;
+//!
+//! struct LoggingConnection {
+//!     conn: Box<APIConnection>,
+//! }
+//!
+//! impl APIConnection for LoggingConnection {
+//!     fn api_url(&self) -> &str {
+//!         self.conn.api_url()
+//!     }
+//!
+//!     fn send_get_request(&mut self,
+//!                         url: &str,
+//!                         args: APIArguments) -> Result<APIResponse> {
+//!         println!("GET {}", url);
+//!         self.conn.send_get_request(url, args)
+//!     }
+//!
+//!     fn send_post_request(&mut self,
+//!                          url: &str,
+//!                          args: APIArguments) -> Result<APIResponse> {
+//!         println!("POST {}", url);
+//!         self.conn.send_post_request(url, args)
+//!     }
+//! }
+//!
+//! struct LoggingConnectionFactory {
+//!     conn_factory: HyperAPIConnectionFactory,
+//! }
+//!
+//! impl APIConnectionFactory for LoggingConnectionFactory {
+//!     fn new_connection(&self) -> Box<APIConnection> {
+//!         Box::new(LoggingConnection {
+//!             conn: self.conn_factory.new_connection()
+//!         })
+//!     }
+//! }
+//!
+//! let decompiler = Decompiler::with_transport(
+//!     Box::new(LoggingConnectionFactory {
+//!         conn_factory: HyperAPIConnectionFactory::new(Settings::new())
+//!     })
+//! );
+//! ```
 
 use std::collections::HashMap;
 use std::collections::hash_map::Iter as ArgIter;
 use std::io::Read;
+use std::io::Write;
+use std::io;
+use std::slice;
 use std::str;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::SystemTime;
 
 use hyper::Url as HyperUrl;
 use hyper::client::request::Request as HyperRequest;
 use hyper::client::response::Response as HyperResponse;
+use hyper::header::ContentLength;
+use hyper::http::HttpMessage;
+use hyper::http::h1::Http11Message;
 use hyper::method::Method as HyperMethod;
 use hyper::net::Fresh;
 use hyper::net::HttpsConnector;
+use hyper::net::NetworkConnector;
 use hyper;
 use hyper_native_tls::NativeTlsClient;
 use json::JsonValue;
 use json;
-use multipart::client::Multipart;
-use regex::Regex;
 
+use content_disposition::file_name_from_content_disposition;
+use error::ErrorKind;
 use error::Result;
 use error::ResultExt;
 use file::File;
+use file::StreamedFile;
+use http_log::RequestLogger;
+use proxy::Proxy;
+use proxy::ProxyConnector;
 use settings::Settings;
+use tls::create_ssl_client;
 use utils::current_platform_name;
+use utils::sha256_hex;
 
 /// A single HTTP header field.
 #[derive(Clone, Debug, Default)]
-struct Header {
+pub struct Header {
+    /// Name of the header (e.g. `Content-Type`).
     pub name: String,
+    /// Value of the header (e.g. `text/plain`).
     pub value: String,
 }
 
+impl Header {
+    /// Creates a new header with the given name and value.
+    pub fn new<N, V>(name: N, value: V) -> Self
+        where N: Into<String>,
+              V: Into<String>
+    {
+        Header {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
 /// HTTP headers.
 #[derive(Clone, Debug, Default)]
-struct Headers {
+pub struct Headers {
     headers: Vec<Header>,
 }
 
 impl Headers {
+    /// Creates empty headers.
+    pub fn new() -> Self {
+        Headers::default()
+    }
+
+    /// Returns an iterator over all headers.
+    pub fn iter(&self) -> slice::Iter<Header> {
+        self.headers.iter()
+    }
+
     /// Adds the given header into the headers.
     pub fn add(&mut self, header: Header) {
         self.headers.push(header);
@@ -65,6 +172,26 @@ pub struct APIResponse {
 }
 
 impl APIResponse {
+    /// Creates a new response.
+    ///
+    /// This is useful when implementing a custom transport.
+    pub fn new<U, M>(request_url: U,
+                     status_code: u16,
+                     status_message: M,
+                     headers: Headers,
+                     body: Vec<u8>) -> Self
+        where U: Into<String>,
+              M: Into<String>
+    {
+        APIResponse {
+            request_url: request_url.into(),
+            status_code: status_code,
+            status_message: status_message.into(),
+            headers: headers,
+            body: body,
+        }
+    }
+
     /// Returns the request URL to which this response corresponds.
     pub fn request_url(&self) -> &str {
         &self.request_url
@@ -80,6 +207,11 @@ impl APIResponse {
         &self.status_message
     }
 
+    /// Returns the headers of the response.
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
     /// Has the request succeeded?
     pub fn succeeded(&self) -> bool {
         self.status_code >= 200 && self.status_code <= 299
@@ -134,7 +266,9 @@ impl APIResponse {
     /// Returns the body of the response as a non-owned UTF-8 string.
     pub fn body_as_str(&self) -> Result<&str> {
         str::from_utf8(&self.body)
-            .chain_err(|| "failed to decode API response body as UTF-8")
+            .chain_err(|| ErrorKind::InvalidResponse(
+                "failed to decode API response body as UTF-8".to_string()
+            ))
     }
 
     /// Returns the body of the response as an owned UTF-8 string.
@@ -145,7 +279,9 @@ impl APIResponse {
     /// Returns the body as a parsed JSON.
     pub fn body_as_json(&self) -> Result<JsonValue> {
         json::parse(self.body_as_str()?)
-            .chain_err(|| "failed to parse API response body as JSON")
+            .chain_err(|| ErrorKind::InvalidResponse(
+                "failed to parse API response body as JSON".to_string()
+            ))
     }
 
     /// Returns the value of the given key in the parsed JSON body as a string.
@@ -172,24 +308,32 @@ impl APIResponse {
     ///
     /// Only works when the response is a file.
     pub fn body_as_file(&self) -> Result<File> {
+        match self.file_name() {
+            Some(file_name) => Ok(File::from_content_with_name(self.body(), file_name)),
+            None => bail!("response from {} does not contain a file", self.request_url),
+        }
+    }
+
+    /// Returns the body of the response as a file, using the given name when
+    /// the response does not specify a usable name of the file.
+    pub fn body_as_file_or_named<N>(&self, fallback_name: N) -> File
+        where N: Into<String>
+    {
+        let file_name = self.file_name().unwrap_or_else(|| fallback_name.into());
+        File::from_content_with_name(self.body(), file_name)
+    }
+
+    /// Returns the name of the file from the `Content-Disposition` header (if
+    /// any).
+    pub fn file_name(&self) -> Option<String> {
         // File responses contain the Content-Disposition header, which
         // includes the name of the file. Example:
         //
         //     Content-Disposition: attachment; filename=file.txt
         //
         // https://retdec.com/api/docs/essential_information.html#id3
-        let content_disposition = self.headers.first_value_for("Content-Disposition");
-        if let Some(content_disposition) = content_disposition {
-            let filename_regex = Regex::new(r"^attachment; filename=(.+)$")
-                .expect("invalid regexp - this should never happen");
-            if let Some(captures) = filename_regex.captures(content_disposition) {
-                if let Some(filename) = captures.get(1) {
-                    return Ok(File::from_content_with_name(self.body(), filename.as_str()));
-                }
-            }
-        }
-
-        bail!("response from {} does not contain a file", self.request_url);
+        self.headers.first_value_for("Content-Disposition")
+            .and_then(file_name_from_content_disposition)
     }
 }
 
@@ -198,6 +342,7 @@ impl APIResponse {
 pub struct APIArguments {
     args: HashMap<String, String>,
     files: HashMap<String, File>,
+    streamed_files: HashMap<String, StreamedFile>,
 }
 
 impl APIArguments {
@@ -271,6 +416,23 @@ impl APIArguments {
     pub fn files(&self) -> ArgIter<String, File> {
         self.files.iter()
     }
+
+    /// Adds the given streamed file under the given name.
+    pub fn add_streamed_file<N>(&mut self, name: N, file: StreamedFile)
+        where N: Into<String>
+    {
+        self.streamed_files.insert(name.into(), file);
+    }
+
+    /// Returns a streamed file with the given name.
+    pub fn get_streamed_file(&self, name: &str) -> Option<&StreamedFile> {
+        self.streamed_files.get(name)
+    }
+
+    /// Returns an iterator over streamed files (`name` => `file`).
+    pub fn streamed_files(&self) -> ArgIter<String, StreamedFile> {
+        self.streamed_files.iter()
+    }
 }
 
 // Use a custom implementation of PartialEq instead of an automatically derived
@@ -282,21 +444,49 @@ impl PartialEq for APIArguments {
             return false;
         }
 
-        for ((k1, f1), (k2, f2)) in self.files.iter().zip(other.files.iter()) {
-            if k1 != k2 || f1.name() != f2.name() || f1.content() != f2.content() {
-                return false;
-            }
+        if self.files.len() != other.files.len() ||
+                self.streamed_files.len() != other.streamed_files.len() {
+            return false;
         }
 
-        self.files.len() == other.files.len()
+        // Files are looked up by their names because two equal maps may
+        // iterate over their entries in a different order.
+        let files_are_equal = self.files.iter().all(|(name, f1)| {
+            match other.files.get(name) {
+                Some(f2) => f1.name() == f2.name() && f1.content() == f2.content(),
+                None => false,
+            }
+        });
+
+        // The content of streamed files is not compared as that would require
+        // reading them.
+        let streamed_files_are_equal = self.streamed_files.iter().all(|(name, f1)| {
+            match other.streamed_files.get(name) {
+                Some(f2) => f1.name() == f2.name() && f1.len() == f2.len(),
+                None => false,
+            }
+        });
+
+        files_are_equal && streamed_files_are_equal
     }
 }
 
-/// API connection.
+/// Connection to the API (a transport).
+///
+/// Implement this trait when you want the services to send requests in a
+/// custom way. See the module-level documentation for an example.
 pub trait APIConnection {
     /// Returns the URL to the API.
     fn api_url(&self) -> &str;
 
+    /// Returns the API key with which requests are authenticated (if any).
+    ///
+    /// It is used e.g. to keep cached results of different accounts apart.
+    /// The default implementation returns `None`.
+    fn api_key(&self) -> Option<&str> {
+        None
+    }
+
     /// Sends an HTTP GET request to the given url with the given arguments.
     fn send_get_request(&mut self,
                         url: &str,
@@ -312,6 +502,32 @@ pub trait APIConnection {
                                      url: &str) -> Result<APIResponse> {
         self.send_get_request(url, APIArguments::new())
     }
+
+    /// Sends an HTTP GET request to the given url with the given arguments
+    /// and writes the body of the response into the given writer.
+    ///
+    /// The body is written only when the request succeeded. In such a case,
+    /// the body of the returned response is empty. Otherwise, nothing is
+    /// written and the body is kept in the response so the reason of the
+    /// failure can be found out. Also returns the number of written bytes.
+    ///
+    /// The default implementation receives the whole body into memory before
+    /// writing it. Override it if your transport can do better.
+    fn send_get_request_into(&mut self,
+                             url: &str,
+                             args: APIArguments,
+                             writer: &mut Write) -> Result<(APIResponse, u64)> {
+        let mut response = self.send_get_request(url, args)?;
+        if response.failed() {
+            return Ok((response, 0));
+        }
+
+        writer.write_all(&response.body)
+            .chain_err(|| format!("failed to write the body of a response from {}", url))?;
+        let written = response.body.len() as u64;
+        response.body = Vec::new();
+        Ok((response, written))
+    }
 }
 
 /// Wrapper of API connections that automatically verifies that requests
@@ -320,7 +536,7 @@ pub trait APIConnection {
 /// It wraps an existing API connection. Then, when a response from a GET/POST
 /// request is received, it verifies that the request succeeded. If the request
 /// failed, it automatically returns an error.
-struct ResponseVerifyingAPIConnection {
+pub struct ResponseVerifyingAPIConnection {
     conn: Box<APIConnection>,
 }
 
@@ -335,7 +551,10 @@ impl ResponseVerifyingAPIConnection {
             return Ok(());
         }
 
-        bail!("request to {} failed: {}", response.request_url(), response.error_reason());
+        bail!(ErrorKind::RequestFailed(
+            response.status_code(),
+            format!("request to {} failed: {}", response.request_url(), response.error_reason())
+        ));
     }
 }
 
@@ -344,6 +563,10 @@ impl APIConnection for ResponseVerifyingAPIConnection {
         self.conn.api_url()
     }
 
+    fn api_key(&self) -> Option<&str> {
+        self.conn.api_key()
+    }
+
     fn send_get_request(&mut self,
                         url: &str,
                         args: APIArguments) -> Result<APIResponse> {
@@ -359,9 +582,21 @@ impl APIConnection for ResponseVerifyingAPIConnection {
         self.ensure_request_succeeded(&response)?;
         Ok(response)
     }
+
+    fn send_get_request_into(&mut self,
+                             url: &str,
+                             args: APIArguments,
+                             writer: &mut Write) -> Result<(APIResponse, u64)> {
+        let (response, written) = self.conn.send_get_request_into(url, args, writer)?;
+        self.ensure_request_succeeded(&response)?;
+        Ok((response, written))
+    }
 }
 
 /// Factory for creating new API connections.
+///
+/// Every service creates a new connection for each started decompilation or
+/// analysis.
 pub trait APIConnectionFactory {
     /// Creates a new connection to the API.
     fn new_connection(&self) -> Box<APIConnection>;
@@ -393,6 +628,9 @@ impl APIConnectionFactory for ResponseVerifyingAPIConnectionFactory {
 }
 
 /// Connection to `retdec.com`'s API via [hyper](https://hyper.rs/).
+///
+/// This is the default transport. Use `HyperAPIConnectionFactory` to create
+/// it.
 pub struct HyperAPIConnection {
     settings: Settings,
 }
@@ -408,14 +646,75 @@ impl HyperAPIConnection {
             parsed_url.query_pairs_mut().append_pair(key, value);
         }
 
-        let ssl = NativeTlsClient::new()
-            .chain_err(|| format!("failed to create a SSL client for a HTTP request to {}", url))?;
-        let connector = HttpsConnector::new(ssl);
+        self.ensure_scheme_is_allowed(&parsed_url)?;
 
-        let mut request = HyperRequest::<Fresh>::with_connector(method, parsed_url, &connector)
-            .chain_err(|| format!("failed to create a new HTTP request to {}", url))?;
+        let ssl = create_ssl_client(&self.settings)
+            .chain_err(|| format!("failed to create a SSL client for a HTTP request to {}", url))?;
+        let proxy = Proxy::for_url(&self.settings, &parsed_url)
+            .chain_err(|| format!("failed to determine a proxy for a HTTP request to {}", url))?;
+
+        let mut request = match proxy {
+            Some(proxy) => self.create_proxied_request(method, parsed_url, proxy, ssl)?,
+            None => {
+                let connector = HttpsConnector::new(ssl);
+                HyperRequest::<Fresh>::with_connector(method, parsed_url, &connector)
+                    .chain_err(|| ErrorKind::ConnectionFailed(
+                        format!("failed to create a new HTTP request to {}", url)
+                    ))?
+            }
+        };
         self.add_auth_to_request(&mut request)?;
         self.add_user_agent_to_request(&mut request);
+        if let Some(timeout) = self.settings.timeout() {
+            request.set_read_timeout(Some(timeout))
+                .chain_err(|| format!("failed to set a read timeout for a HTTP request to {}", url))?;
+            request.set_write_timeout(Some(timeout))
+                .chain_err(|| format!("failed to set a write timeout for a HTTP request to {}", url))?;
+        }
+        Ok(request)
+    }
+
+    fn ensure_scheme_is_allowed(&self, url: &HyperUrl) -> Result<()> {
+        if url.scheme() == "https" || self.settings.plain_http_allowed() {
+            return Ok(());
+        }
+
+        bail!(
+            "refusing to send a request to {} over an unencrypted connection \
+             (use an https:// URL)",
+            url
+        );
+    }
+
+    fn create_proxied_request(&self,
+                              method: HyperMethod,
+                              url: HyperUrl,
+                              proxy: Proxy,
+                              ssl: NativeTlsClient) -> Result<HyperRequest<Fresh>> {
+        let url_str = url.to_string();
+        let host = url.host_str()
+            .ok_or_else(|| format!("no host in URL: {}", url))?
+            .to_string();
+        let port = url.port_or_known_default()
+            .ok_or_else(|| format!("no port in URL: {}", url))?;
+        let is_plain_http = url.scheme() == "http";
+        let authorization = proxy.authorization();
+
+        let connector = ProxyConnector::new(proxy, ssl);
+        let stream = connector.connect(&host, port, url.scheme())
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to connect to {}:{} through a proxy", host, port)
+            ))?;
+        let mut message = Http11Message::with_stream(Box::new(stream));
+        // Plain HTTP requests are forwarded by the proxy itself, so they have
+        // to use absolute URLs and carry the proxy credentials. HTTPS requests
+        // go through a tunnel, which has already been authorized.
+        message.set_proxied(is_plain_http);
+        let mut request = HyperRequest::<Fresh>::with_message(method, url, Box::new(message))
+            .chain_err(|| format!("failed to create a new HTTP request to {}", url_str))?;
+        if let (true, Some(authorization)) = (is_plain_http, authorization) {
+            request.headers_mut().set_raw("Proxy-Authorization", vec![authorization.into_bytes()]);
+        }
         Ok(request)
     }
 
@@ -441,12 +740,31 @@ impl HyperAPIConnection {
         request.headers_mut().set(hyper::header::UserAgent(user_agent));
     }
 
+    fn start_get_request(&self,
+                         url: &str,
+                         args: &APIArguments,
+                         logger: &RequestLogger) -> Result<HyperResponse> {
+        let request = self.prepare_request(HyperMethod::Get, url, args)
+            .chain_err(|| format!("failed to prepare a GET request to {}", url))?;
+        logger.dump_request_headers(&self.parse_headers(request.headers()));
+        request.start()
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to start a GET request to {}", url)
+            ))?
+            .send()
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to send a GET request to {}", url)
+            ))
+    }
+
     fn parse_response(&self, mut response: HyperResponse, request_url: &str)
         -> Result<APIResponse>
     {
         let mut body: Vec<u8> = Vec::new();
         response.read_to_end(&mut body)
-            .chain_err(|| format!("failed to read the body of a response from {}", response.url))?;
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to read the body of a response from {}", response.url)
+            ))?;
         let raw_status = response.status_raw();
         Ok(APIResponse {
             request_url: request_url.to_string(),
@@ -467,6 +785,62 @@ impl HyperAPIConnection {
         }
         parsed_headers
     }
+
+    fn get_into(&self,
+                url: &str,
+                args: &APIArguments,
+                writer: &mut Write,
+                logger: &RequestLogger) -> Result<(APIResponse, u64)> {
+        let mut response = self.start_get_request(url, args, logger)?;
+        let raw_status = response.status_raw().clone();
+        let mut api_response = APIResponse {
+            request_url: url.to_string(),
+            status_code: raw_status.0,
+            status_message: raw_status.1.into_owned(),
+            headers: self.parse_headers(&response.headers),
+            body: Vec::new(),
+        };
+        if api_response.failed() {
+            response.read_to_end(&mut api_response.body)
+                .chain_err(|| format!("failed to read the body of a response from {}", url))?;
+            return Ok((api_response, 0));
+        }
+
+        let written = io::copy(&mut response, writer)
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to receive the body of a response from {}", url)
+            ))?;
+        Ok((api_response, written))
+    }
+
+    fn post(&self,
+            url: &str,
+            args: &APIArguments,
+            logger: &RequestLogger) -> Result<APIResponse> {
+        let mut request = self.prepare_request(HyperMethod::Post, url, args)
+            .chain_err(|| format!("failed to prepare a POST request to {}", url))?;
+        // The retdec.com API does not support chunked requests, so ensure that
+        // we send a request with the Content-Length header.
+        // https://retdec.com/api/docs/essential_information.html#transfer-encoding
+        let body = MultipartBody::new(args);
+        request.headers_mut().set(ContentLength(body.len()));
+        request.headers_mut().set_raw("Content-Type", vec![body.content_type().into_bytes()]);
+        logger.dump_request_headers(&self.parse_headers(request.headers()));
+        logger.dump_request_body(&body.summary());
+        let mut request = request.start()
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to start a POST request to {}", url)
+            ))?;
+        body.write_to(&mut request)
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to send files in a POST request to {}", url)
+            ))?;
+        let response = request.send()
+            .chain_err(|| ErrorKind::ConnectionFailed(
+                format!("failed to send a POST request to {}", url)
+            ))?;
+        self.parse_response(response, url)
+    }
 }
 
 impl APIConnection for HyperAPIConnection {
@@ -474,349 +848,216 @@ impl APIConnection for HyperAPIConnection {
         self.settings.api_url()
     }
 
+    fn api_key(&self) -> Option<&str> {
+        self.settings.api_key()
+    }
+
     fn send_get_request(&mut self,
                         url: &str,
                         args: APIArguments) -> Result<APIResponse> {
-        let request = self.prepare_request(HyperMethod::Get, url, &args)
-            .chain_err(|| format!("failed to prepare a GET request to {}", url))?;
-        let response = request.start()
-            .chain_err(|| format!("failed to start a GET request to {}", url))?
-            .send()
-            .chain_err(|| format!("failed to send a GET request to {}", url))?;
-        self.parse_response(response, url)
+        let logger = RequestLogger::start("GET", url, &args, self.settings.verbose_http());
+        let result = self.start_get_request(url, &args, &logger)
+            .and_then(|response| self.parse_response(response, url));
+        match result {
+            Ok(ref response) => logger.finish(response, 0),
+            Err(ref err) => logger.fail(err),
+        }
+        result
+    }
+
+    fn send_get_request_into(&mut self,
+                             url: &str,
+                             args: APIArguments,
+                             writer: &mut Write) -> Result<(APIResponse, u64)> {
+        let logger = RequestLogger::start("GET", url, &args, self.settings.verbose_http());
+        let result = self.get_into(url, &args, writer, &logger);
+        match result {
+            Ok((ref response, written)) => logger.finish(response, written),
+            Err(ref err) => logger.fail(err),
+        }
+        result
     }
 
     fn send_post_request(&mut self,
                          url: &str,
                          args: APIArguments) -> Result<APIResponse> {
-        let request = self.prepare_request(HyperMethod::Post, url, &args)
-            .chain_err(|| format!("failed to prepare a POST request to {}", url))?;
-        // The retdec.com API does not support chunked requests, so ensure that
-        // we send a request with the Content-Length header by using
-        // from_request_sized() instead of from_request().
-        // https://retdec.com/api/docs/essential_information.html#transfer-encoding
-        let mut mp = Multipart::from_request_sized(request)
-            .chain_err(|| format!("failed to prepare a multipart POST request to {}", url))?;
-        for (name, file) in args.files() {
-            mp.write_stream(name, &mut file.content(), Some(&file.safe_name()), None)
-                .chain_err(|| format!("failed to add a file into a POST request to {}", url))?;
+        let logger = RequestLogger::start("POST", url, &args, self.settings.verbose_http());
+        let result = self.post(url, &args, &logger);
+        match result {
+            Ok(ref response) => logger.finish(response, 0),
+            Err(ref err) => logger.fail(err),
         }
-        let response = mp.send()
-            .chain_err(|| format!("failed to send a POST request to {}", url))?;
-        self.parse_response(response, url)
+        result
     }
 }
 
-/// Factory for creating new API connections via [hyper](https://hyper.rs/).
-pub struct HyperAPIConnectionFactory {
-    settings: Settings,
+/// Body of a `multipart/form-data` request with files.
+///
+/// The body is built manually because we need to know its length before
+/// sending it (see `send_post_request()`) and we do not want to buffer
+/// streamed files in memory to compute it.
+struct MultipartBody<'a> {
+    boundary: String,
+    parts: Vec<MultipartPart<'a>>,
 }
 
-impl HyperAPIConnectionFactory {
-    /// Creates a new factory with the given settings.
-    pub fn new(settings: Settings) -> Self {
-        HyperAPIConnectionFactory {
-            settings: settings,
-        }
-    }
+struct MultipartPart<'a> {
+    header: String,
+    content: MultipartContent<'a>,
 }
 
-impl APIConnectionFactory for HyperAPIConnectionFactory {
-    fn new_connection(&self) -> Box<APIConnection> {
-        Box::new(
-            HyperAPIConnection {
-                settings: self.settings.clone()
-            }
-        )
-    }
+enum MultipartContent<'a> {
+    Bytes(&'a [u8]),
+    Stream(&'a StreamedFile),
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-
-    use std::cell::RefCell;
-    use std::rc::Rc;
-
-    /// A builder of API arguments.
-    #[derive(Debug, Default)]
-    pub struct APIArgumentsBuilder {
-        args: APIArguments,
+impl<'a> MultipartBody<'a> {
+    fn new(args: &'a APIArguments) -> Self {
+        Self::with_boundary(args, generate_boundary())
     }
 
-    impl APIArgumentsBuilder {
-        /// Creates a new builder.
-        pub fn new() -> Self {
-            APIArgumentsBuilder::default()
-        }
-
-        /// Adds a new string argument (`"name=value"`).
-        pub fn with_string_arg<N, V>(mut self, name: N, value: V) -> Self
-            where N: Into<String>,
-                  V: Into<String>
-        {
-            self.args.add_string_arg(name, value);
-            self
+    fn with_boundary(args: &'a APIArguments, boundary: String) -> Self {
+        let mut parts = Vec::new();
+        for (name, file) in args.files() {
+            parts.push(MultipartPart {
+                header: Self::part_header(&boundary, name, &file.safe_name()),
+                content: MultipartContent::Bytes(file.content()),
+            });
         }
-
-        /// Adds a new bool argument (`"name=value"`).
-        pub fn with_bool_arg<N>(mut self, name: N, value: bool) -> Self
-            where N: Into<String>
-        {
-            self.args.add_bool_arg(name, value);
-            self
+        for (name, file) in args.streamed_files() {
+            parts.push(MultipartPart {
+                header: Self::part_header(&boundary, name, &file.safe_name()),
+                content: MultipartContent::Stream(file),
+            });
         }
-
-        /// Adds a new file with the given name.
-        pub fn with_file<N>(mut self, name: N, file: File) -> Self
-            where N: Into<String>
-        {
-            self.args.add_file(name, file);
-            self
+        MultipartBody {
+            boundary: boundary,
+            parts: parts,
         }
-
-        /// Builds the arguments.
-        pub fn build(self) -> APIArguments {
-            self.args
-        }
-    }
-
-    /// Information about an API request.
-    #[derive(Debug, PartialEq)]
-    struct APIRequestInfo {
-        method: &'static str,
-        url: String,
-        args: APIArguments,
     }
 
-    /// Information about an API response.
-    #[derive(Debug)]
-    struct APIResponseInfo {
-        method: &'static str,
-        url: String,
-        response: Result<APIResponse>,
+    fn part_header(boundary: &str, name: &str, file_name: &str) -> String {
+        format!(
+            "--{}\r\n\
+             Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+             Content-Type: application/octet-stream\r\n\
+             \r\n",
+            boundary,
+            name,
+            file_name.replace('"', "\\\"")
+        )
     }
 
-    /// A connection mock to be used in tests.
-    pub struct APIConnectionMock {
-        settings: Settings,
-        requests: Vec<APIRequestInfo>,
-        responses: Vec<APIResponseInfo>,
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
     }
 
-    impl APIConnectionMock {
-        /// Creates a new mock with the given settings.
-        pub fn new(settings: Settings) -> Self {
-            APIConnectionMock {
-                settings: settings,
-                requests: Vec::new(),
-                responses: Vec::new(),
-            }
-        }
-
-        /// Adds a response to be returned for the given request.
-        pub fn add_response<U>(&mut self,
-                            method: &'static str,
-                            url: U,
-                            response: Result<APIResponse>)
-            where U: Into<String>
-        {
-            self.responses.push(
-                APIResponseInfo {
-                    method: method,
-                    url: url.into(),
-                    response: response,
-                }
-            );
-        }
-
-        /// Has a request to the given URL and with the given arguments been sent?
-        pub fn request_sent<U>(&self,
-                            method: &'static str,
-                            url: U,
-                            args: APIArguments) -> bool
-            where U: Into<String>
-        {
-            self.requests.contains(
-                &APIRequestInfo {
-                    method: method,
-                    url: url.into(),
-                    args: args,
-                }
-            )
-        }
-
-        /// Removes all sent requests and set responses.
-        pub fn reset(&mut self) {
-            self.requests.clear();
-            self.responses.clear();
+    /// Returns a summary of the body for verbose logging (files are replaced
+    /// by their sizes).
+    fn summary(&self) -> String {
+        let mut summary = String::new();
+        for part in &self.parts {
+            let content_len = match part.content {
+                MultipartContent::Bytes(bytes) => bytes.len() as u64,
+                MultipartContent::Stream(file) => file.len(),
+            };
+            summary.push_str(&part.header);
+            summary.push_str(&format!("<{} bytes>\r\n", content_len));
         }
-
-        /// Check if no requests were sent.
-        pub fn no_requests_sent(&self) -> bool {
-            self.requests.is_empty()
-        }
-
-        fn add_request<U>(&mut self,
-                    method: &'static str,
-                    url: U,
-                    args: APIArguments)
-            where U: Into<String>
-        {
-            self.requests.push(
-                APIRequestInfo {
-                    method: method,
-                    url: url.into(),
-                    args: args,
+        summary.push_str(&self.closing_delimiter());
+        summary
+    }
+
+    fn closing_delimiter(&self) -> String {
+        format!("--{}--\r\n", self.boundary)
+    }
+
+    fn len(&self) -> u64 {
+        let parts_len: u64 = self.parts.iter()
+            .map(|part| {
+                let content_len = match part.content {
+                    MultipartContent::Bytes(bytes) => bytes.len() as u64,
+                    MultipartContent::Stream(file) => file.len(),
+                };
+                // The content is followed by CRLF.
+                part.header.len() as u64 + content_len + 2
+            })
+            .sum();
+        parts_len + self.closing_delimiter().len() as u64
+    }
+
+    fn write_to(&self, writer: &mut Write) -> Result<()> {
+        for part in &self.parts {
+            writer.write_all(part.header.as_bytes())
+                .chain_err(|| "failed to write a multipart header")?;
+            match part.content {
+                MultipartContent::Bytes(bytes) => {
+                    writer.write_all(bytes)
+                        .chain_err(|| "failed to write a file")?;
                 }
-            );
-        }
-
-        fn find_response(&mut self,
-                        method: &'static str,
-                        url: &str) -> Result<APIResponse> {
-            let mut found_index = None;
-            for (i, ref r) in self.responses.iter().enumerate() {
-                if r.method == method && r.url == url {
-                    found_index = Some(i);
-                    break;
+                MultipartContent::Stream(file) => {
+                    file.copy_to(writer)?;
                 }
             }
-            if let Some(i) = found_index {
-                return self.responses.remove(i).response;
-            }
-            panic!("no response set for {} request to {}", method, url);
-        }
-    }
-
-    impl APIConnection for APIConnectionMock {
-        fn api_url(&self) -> &str {
-            self.settings.api_url()
-        }
-
-        fn send_get_request(&mut self,
-                            url: &str,
-                            args: APIArguments) -> Result<APIResponse> {
-            self.add_request("GET", url, args);
-            self.find_response("GET", url)
-        }
-
-        fn send_post_request(&mut self,
-                            url: &str,
-                            args: APIArguments) -> Result<APIResponse> {
-            self.add_request("POST", url, args);
-            self.find_response("POST", url)
-        }
-    }
-
-    /// A wrapper over `APIConnectionMock` that can be passed to functions that
-    /// expect `Box<APIConnection>`.
-    pub struct APIConnectionMockWrapper {
-        settings: Settings,
-        conn: Rc<RefCell<APIConnectionMock>>,
-    }
-
-    impl APIConnectionMockWrapper {
-        pub fn new(conn: Rc<RefCell<APIConnectionMock>>) -> Self {
-            let settings = conn.borrow().settings.clone();
-            APIConnectionMockWrapper {
-                settings: settings,
-                conn: conn,
-            }
+            writer.write_all(b"\r\n")
+                .chain_err(|| "failed to write a multipart delimiter")?;
         }
+        writer.write_all(self.closing_delimiter().as_bytes())
+            .chain_err(|| "failed to write a multipart delimiter")?;
+        Ok(())
     }
+}
 
-    impl APIConnection for APIConnectionMockWrapper {
-        fn api_url(&self) -> &str {
-            self.settings.api_url()
-        }
-
-        fn send_get_request(&mut self,
-                            url: &str,
-                            args: APIArguments) -> Result<APIResponse> {
-            self.conn.borrow_mut().send_get_request(url, args)
-        }
-
-        fn send_post_request(&mut self,
-                            url: &str,
-                            args: APIArguments) -> Result<APIResponse> {
-            self.conn.borrow_mut().send_post_request(url, args)
-        }
-    }
+fn generate_boundary() -> String {
+    // The boundary only needs to be unlikely to appear in uploaded files.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let seed = format!(
+        "{:?}-{}",
+        SystemTime::now(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+    format!("retdec-rust-{}", &sha256_hex(seed.as_bytes())[..32])
+}
 
-    /// A connection-factory mock to be used in tests.
-    pub struct APIConnectionFactoryMock {
-        conn: Rc<RefCell<APIConnectionMock>>,
-    }
+/// Factory for creating new API connections via [hyper](https://hyper.rs/).
+pub struct HyperAPIConnectionFactory {
+    settings: Settings,
+}
 
-    impl APIConnectionFactoryMock {
-        /// Creates a new factory.
-        pub fn new(conn: Rc<RefCell<APIConnectionMock>>) -> Self {
-            APIConnectionFactoryMock { conn: conn }
+impl HyperAPIConnectionFactory {
+    /// Creates a new factory with the given settings.
+    pub fn new(settings: Settings) -> Self {
+        if settings.plain_http_allowed() {
+            warn!(
+                "plain HTTP is allowed, so the API key and files may be sent \
+                 unencrypted; never do this outside of local testing"
+            );
         }
-    }
-
-    impl APIConnectionFactory for APIConnectionFactoryMock {
-        fn new_connection(&self) -> Box<APIConnection> {
-            Box::new(APIConnectionMockWrapper::new(self.conn.clone()))
+        HyperAPIConnectionFactory {
+            settings: settings,
         }
     }
+}
 
-    /// A builder of API responses.
-    #[derive(Debug, Default)]
-    pub struct APIResponseBuilder {
-        response: APIResponse,
+impl APIConnectionFactory for HyperAPIConnectionFactory {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(
+            HyperAPIConnection {
+                settings: self.settings.clone()
+            }
+        )
     }
+}
 
-    impl APIResponseBuilder {
-        /// Creates a new builder.
-        pub fn new() -> Self {
-            APIResponseBuilder::default()
-        }
-
-        /// Sets the request URL of the response.
-        pub fn with_request_url<U>(mut self, new_request_url: U) -> Self
-            where U: Into<String>
-        {
-            self.response.request_url = new_request_url.into();
-            self
-        }
-
-        /// Sets the status code of the response.
-        pub fn with_status_code(mut self, new_status_code: u16) -> Self {
-            self.response.status_code = new_status_code;
-            self
-        }
-
-        /// Sets the status message of the response.
-        pub fn with_status_message<M>(mut self, new_status_message: M) -> Self
-            where M: Into<String>
-        {
-            self.response.status_message = new_status_message.into();
-            self
-        }
-
-        /// Sets the body of the response.
-        pub fn with_body(mut self, new_body: &[u8]) -> Self {
-            self.response.body = new_body.to_vec();
-            self
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        /// Sets the response to return the given file.
-        pub fn with_file(mut self, file: File) -> Self {
-            self.response.headers.add(Header {
-                name: "Content-Disposition".to_string(),
-                value: format!("attachment; filename={}", file.name()),
-            });
-            self.response.body = file.content().to_vec();
-            self
-        }
+    use std::io::Cursor;
 
-        /// Builds the response.
-        pub fn build(self) -> APIResponse {
-            self.response
-        }
-    }
+    use proxy::tests::ProxyStandIn;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
 
     #[test]
     fn api_response_getters_return_correct_values() {
@@ -1040,6 +1281,41 @@ pub mod tests {
         assert_eq!(file.content(), b"content");
     }
 
+    #[test]
+    fn api_response_body_as_file_handles_quoted_and_encoded_file_names() {
+        let r = APIResponseBuilder::new()
+            .with_header(
+                "Content-Disposition",
+                "attachment; filename*=UTF-8''na%C3%AFve.c; filename=\"naive.c\""
+            )
+            .with_body(b"content")
+            .build();
+
+        let file = r.body_as_file().expect("expected a file to be returned");
+        assert_eq!(file.name(), "na\u{ef}ve.c");
+    }
+
+    #[test]
+    fn api_response_body_as_file_returns_error_when_response_is_not_file() {
+        let r = APIResponseBuilder::new()
+            .with_body(b"content")
+            .build();
+
+        assert!(r.body_as_file().is_err());
+    }
+
+    #[test]
+    fn api_response_body_as_file_or_named_uses_fallback_name_when_response_has_no_name() {
+        let r = APIResponseBuilder::new()
+            .with_header("Content-Disposition", "attachment")
+            .with_body(b"content")
+            .build();
+
+        let file = r.body_as_file_or_named("ID.hll");
+        assert_eq!(file.name(), "ID.hll");
+        assert_eq!(file.content(), b"content");
+    }
+
     #[test]
     fn api_arguments_add_string_arg_adds_string_argument() {
         let mut args = APIArguments::new();
@@ -1136,6 +1412,202 @@ pub mod tests {
         assert_eq!(files[0].1.name(), "file.exe");
     }
 
+    #[test]
+    fn api_arguments_add_streamed_file_adds_file() {
+        let mut args = APIArguments::new();
+
+        args.add_streamed_file(
+            "input",
+            StreamedFile::from_reader(Cursor::new(b"content"), 7, "file.exe")
+        );
+
+        assert_eq!(args.get_streamed_file("input").unwrap().name(), "file.exe");
+        assert_eq!(args.streamed_files().count(), 1);
+    }
+
+    #[test]
+    fn api_arguments_with_different_streamed_files_are_not_equal() {
+        let mut args1 = APIArguments::new();
+        args1.add_streamed_file(
+            "input",
+            StreamedFile::from_reader(Cursor::new(b"content"), 7, "file.exe")
+        );
+        let mut args2 = APIArguments::new();
+        args2.add_streamed_file(
+            "input",
+            StreamedFile::from_reader(Cursor::new(b"content"), 7, "other.exe")
+        );
+
+        assert!(args1 != args2);
+        assert!(args1 != APIArguments::new());
+    }
+
+    #[test]
+    fn api_arguments_with_same_files_added_in_different_order_are_equal() {
+        let names: Vec<String> = (0..16).map(|i| format!("file{}", i)).collect();
+        let mut args1 = APIArguments::new();
+        let mut args2 = APIArguments::new();
+        for name in &names {
+            args1.add_file(name.as_str(), File::from_content_with_name(b"content", name.as_str()));
+            args1.add_streamed_file(
+                format!("streamed_{}", name),
+                StreamedFile::from_reader(Cursor::new(b"content"), 7, name.as_str())
+            );
+        }
+        for name in names.iter().rev() {
+            args2.add_file(name.as_str(), File::from_content_with_name(b"content", name.as_str()));
+            args2.add_streamed_file(
+                format!("streamed_{}", name),
+                StreamedFile::from_reader(Cursor::new(b"content"), 7, name.as_str())
+            );
+        }
+
+        assert!(args1 == args2);
+    }
+
+    #[test]
+    fn api_arguments_with_files_under_different_names_are_not_equal() {
+        let mut args1 = APIArguments::new();
+        args1.add_file("input", File::from_content_with_name(b"content", "file.exe"));
+        args1.add_file("pdb", File::from_content_with_name(b"debug info", "file.pdb"));
+        let mut args2 = APIArguments::new();
+        args2.add_file("input", File::from_content_with_name(b"debug info", "file.pdb"));
+        args2.add_file("pdb", File::from_content_with_name(b"content", "file.exe"));
+
+        assert!(args1 != args2);
+    }
+
+    #[test]
+    fn multipart_body_contains_all_files_and_has_correct_length() {
+        let mut args = APIArguments::new();
+        args.add_file("input", File::from_content_with_name(b"content", "file.exe"));
+        args.add_streamed_file(
+            "pdb",
+            StreamedFile::from_reader(Cursor::new(b"debug info"), 10, "file.pdb")
+        );
+        let body = MultipartBody::with_boundary(&args, "XyZ".to_string());
+
+        let mut written = Vec::new();
+        body.write_to(&mut written).unwrap();
+
+        assert_eq!(body.content_type(), "multipart/form-data; boundary=XyZ");
+        assert_eq!(body.len(), written.len() as u64);
+        assert_eq!(
+            String::from_utf8(written).unwrap(),
+            "--XyZ\r\n\
+             Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+             Content-Type: application/octet-stream\r\n\
+             \r\n\
+             content\r\n\
+             --XyZ\r\n\
+             Content-Disposition: form-data; name=\"pdb\"; filename=\"file.pdb\"\r\n\
+             Content-Type: application/octet-stream\r\n\
+             \r\n\
+             debug info\r\n\
+             --XyZ--\r\n"
+        );
+    }
+
+    #[test]
+    fn multipart_body_escapes_quotes_in_file_names() {
+        let mut args = APIArguments::new();
+        args.add_file("input", File::from_content_with_name(b"", "a\"b.exe"));
+        let body = MultipartBody::with_boundary(&args, "XyZ".to_string());
+
+        let mut written = Vec::new();
+        body.write_to(&mut written).unwrap();
+
+        assert!(String::from_utf8(written).unwrap().contains("filename=\"a\\\"b.exe\""));
+    }
+
+    #[test]
+    fn generate_boundary_returns_different_boundaries() {
+        assert!(generate_boundary() != generate_boundary());
+    }
+
+    #[test]
+    fn hyper_api_connection_sends_streamed_file_with_content_length() {
+        let stand_in = ProxyStandIn::start(
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}"
+        );
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("http://retdec.example/service/api")
+            .with_plain_http_allowed(true)
+            .with_proxy(stand_in.url());
+        let mut conn = HyperAPIConnectionFactory::new(settings).new_connection();
+        let mut args = APIArguments::new();
+        args.add_streamed_file(
+            "input",
+            StreamedFile::from_reader(Cursor::new(vec![0; 1000]), 1000, "file.exe")
+        );
+
+        let response = conn.send_post_request(
+            "http://retdec.example/service/api/decompiler/decompilations",
+            args
+        ).expect("expected the request to succeed");
+
+        assert_eq!(response.status_code(), 200);
+        let request = stand_in.received_request();
+        assert!(request.contains("Content-Length: "));
+        assert!(request.contains("Content-Type: multipart/form-data; boundary=retdec-rust-"));
+        assert!(!request.contains("Transfer-Encoding"));
+    }
+
+    #[test]
+    fn send_get_request_into_writes_body_into_writer_when_request_succeeds() {
+        let mut conn = APIConnectionMock::new(Settings::new());
+        conn.add_response(
+            "GET",
+            "https://retdec.com/service/api/test/echo",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(b"content")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let (response, written) = conn.send_get_request_into(
+            "https://retdec.com/service/api/test/echo",
+            APIArguments::new(),
+            &mut output
+        ).unwrap();
+
+        assert!(response.succeeded());
+        assert_eq!(written, 7);
+        assert_eq!(output, b"content");
+        assert!(response.body().is_empty());
+    }
+
+    #[test]
+    fn send_get_request_into_does_not_write_body_when_request_fails() {
+        let mut conn = APIConnectionMock::new(Settings::new());
+        conn.add_response(
+            "GET",
+            "https://retdec.com/service/api/XYZ",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(404)
+                    .with_body(b"{\"message\": \"Not Found\"}")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let (response, written) = conn.send_get_request_into(
+            "https://retdec.com/service/api/XYZ",
+            APIArguments::new(),
+            &mut output
+        ).unwrap();
+
+        assert!(response.failed());
+        assert_eq!(written, 0);
+        assert!(output.is_empty());
+        assert_eq!(response.error_message(), Some("Not Found".to_string()));
+    }
+
     #[test]
     fn response_verifying_api_connection_returns_get_request_when_succeeded() {
         let mut conn = Box::new(APIConnectionMock::new(Settings::new()));
@@ -1235,4 +1707,59 @@ pub mod tests {
             "request to https://retdec.com/service/api/XYZ failed: Not Found (HTTP 404)"
         );
     }
+
+    #[test]
+    fn hyper_api_connection_sends_plain_http_request_through_proxy() {
+        let stand_in = ProxyStandIn::start(
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}"
+        );
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("http://retdec.example/service/api")
+            .with_plain_http_allowed(true)
+            .with_proxy(stand_in.url_with_credentials("user", "pass"));
+        let mut conn = HyperAPIConnectionFactory::new(settings).new_connection();
+
+        let response = conn.send_get_request_without_args(
+            "http://retdec.example/service/api/test"
+        ).expect("expected the request to succeed");
+
+        assert_eq!(response.status_code(), 200);
+        let request = stand_in.received_request();
+        assert!(request.starts_with(
+            "GET http://retdec.example/service/api/test HTTP/1.1\r\n"
+        ));
+        assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+    }
+
+    #[test]
+    fn hyper_api_connection_refuses_plain_http_by_default() {
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("http://retdec.example/service/api");
+        let mut conn = HyperAPIConnectionFactory::new(settings).new_connection();
+
+        let result = conn.send_get_request_without_args(
+            "http://retdec.example/service/api/test"
+        );
+
+        let err = result.err().expect("expected the request to fail");
+        assert!(err.iter().any(|e| e.to_string().contains("unencrypted connection")));
+    }
+
+    #[test]
+    fn hyper_api_connection_returns_error_when_root_certificate_cannot_be_read() {
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("https://retdec.example/service/api")
+            .with_root_certificate("/nonexisting/ca.pem");
+        let mut conn = HyperAPIConnectionFactory::new(settings).new_connection();
+
+        let result = conn.send_get_request_without_args(
+            "https://retdec.example/service/api/test"
+        );
+
+        let err = result.err().expect("expected the request to fail");
+        assert!(err.iter().any(|e| e.to_string().contains("/nonexisting/ca.pem")));
+    }
 }
@@ -0,0 +1,245 @@
+//! Parsing of the `Content-Disposition` header (RFC 6266).
+
+use utils::percent_decode;
+
+/// Returns the name of the file from the given value of the
+/// `Content-Disposition` header.
+///
+/// Handles quoted names (including escaped characters), arbitrary order of
+/// parameters, and names encoded according to RFC 5987
+/// (`filename*=UTF-8''na%C3%AFve.c`). When both `filename*` and `filename`
+/// are present, the former is preferred. Returns `None` when the header does
+/// not contain a usable file name.
+pub fn file_name_from_content_disposition(value: &str) -> Option<String> {
+    let mut params = Params::new(value);
+    // The first item is the disposition type (e.g. `attachment`), which
+    // does not affect the file name.
+    params.skip_token();
+
+    let mut name = None;
+    let mut encoded_name = None;
+    while let Some((key, value)) = params.next_param() {
+        if key.eq_ignore_ascii_case("filename*") {
+            if encoded_name.is_none() {
+                encoded_name = decode_ext_value(&value);
+            }
+        } else if key.eq_ignore_ascii_case("filename") && name.is_none() {
+            name = Some(value);
+        }
+    }
+    match encoded_name.or(name) {
+        Some(ref name) if !name.trim().is_empty() => Some(name.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Returns a value of the `Content-Disposition` header for an attachment with
+/// the given file name.
+///
+/// The name is quoted, so it may contain any characters but control ones.
+pub fn content_disposition_with_file_name(file_name: &str) -> String {
+    let escaped = file_name.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("attachment; filename=\"{}\"", escaped)
+}
+
+/// A simple tokenizer of `;`-separated `key=value` parameters.
+struct Params<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Params<'a> {
+    fn new(input: &'a str) -> Self {
+        Params { input: input, pos: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_left().len();
+    }
+
+    fn skip_token(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.find(';').unwrap_or_else(|| rest.len());
+    }
+
+    fn next_param(&mut self) -> Option<(String, String)> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with(';') {
+                self.pos += 1;
+                continue;
+            }
+            if self.rest().is_empty() {
+                return None;
+            }
+
+            let rest = self.rest();
+            let key_end = rest.find(|c| c == '=' || c == ';').unwrap_or_else(|| rest.len());
+            let key = rest[..key_end].trim().to_string();
+            self.pos += key_end;
+            if !self.rest().starts_with('=') {
+                // A parameter without a value; ignore it.
+                continue;
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let value = if self.rest().starts_with('"') {
+                self.quoted_string()
+            } else {
+                let rest = self.rest();
+                let value_end = rest.find(';').unwrap_or_else(|| rest.len());
+                self.pos += value_end;
+                rest[..value_end].trim().to_string()
+            };
+            return Some((key, value));
+        }
+    }
+
+    fn quoted_string(&mut self) -> String {
+        // Skip the opening quote.
+        self.pos += 1;
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        let mut consumed = self.rest().len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    consumed = i + 1;
+                    break;
+                }
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                _ => value.push(c),
+            }
+        }
+        self.pos += consumed;
+        value
+    }
+}
+
+/// Decodes a value in the `charset'language'percent-encoded` format from
+/// RFC 5987.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+    let bytes = percent_decode(encoded)?;
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        // ISO-8859-1 maps bytes directly to the first 256 code points.
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(value: &str) -> Option<String> {
+        file_name_from_content_disposition(value)
+    }
+
+    #[test]
+    fn returns_unquoted_file_name() {
+        assert_eq!(name("attachment; filename=file.txt"), Some("file.txt".to_string()));
+    }
+
+    #[test]
+    fn returns_quoted_file_name() {
+        assert_eq!(
+            name("attachment; filename=\"my file; v2.c\""),
+            Some("my file; v2.c".to_string())
+        );
+    }
+
+    #[test]
+    fn unescapes_quoted_file_name() {
+        assert_eq!(
+            name(r#"attachment; filename="a \"quoted\" \\name.c""#),
+            Some(r#"a "quoted" \name.c"#.to_string())
+        );
+    }
+
+    #[test]
+    fn handles_extra_parameters_in_any_order() {
+        assert_eq!(
+            name("attachment; size=123; FileName=file.c; creation-date=\"Wed, 12 Feb 1997\""),
+            Some("file.c".to_string())
+        );
+    }
+
+    #[test]
+    fn handles_missing_whitespace_and_inline_disposition() {
+        assert_eq!(name("inline;filename=file.c"), Some("file.c".to_string()));
+    }
+
+    #[test]
+    fn decodes_utf8_encoded_file_name() {
+        assert_eq!(
+            name("attachment; filename*=UTF-8''na%C3%AFve%20file.c"),
+            Some("na\u{ef}ve file.c".to_string())
+        );
+    }
+
+    #[test]
+    fn decodes_iso_8859_1_encoded_file_name() {
+        assert_eq!(
+            name("attachment; filename*=iso-8859-1'en'%A3rates.c"),
+            Some("\u{a3}rates.c".to_string())
+        );
+    }
+
+    #[test]
+    fn prefers_encoded_file_name_regardless_of_order() {
+        assert_eq!(
+            name("attachment; filename*=UTF-8''%E2%82%AC.c; filename=\"EUR.c\""),
+            Some("\u{20ac}.c".to_string())
+        );
+        assert_eq!(
+            name("attachment; filename=\"EUR.c\"; filename*=UTF-8''%E2%82%AC.c"),
+            Some("\u{20ac}.c".to_string())
+        );
+    }
+
+    #[test]
+    fn falls_back_to_plain_file_name_when_encoded_one_is_invalid() {
+        assert_eq!(
+            name("attachment; filename*=KOI8-R''%C1.c; filename=a.c"),
+            Some("a.c".to_string())
+        );
+        assert_eq!(
+            name("attachment; filename*=UTF-8''%ZZ.c; filename=a.c"),
+            Some("a.c".to_string())
+        );
+    }
+
+    #[test]
+    fn returns_none_when_there_is_no_file_name() {
+        assert_eq!(name("attachment"), None);
+        assert_eq!(name("attachment; size=123"), None);
+        assert_eq!(name("attachment; filename=\"\""), None);
+        assert_eq!(name(""), None);
+    }
+
+    #[test]
+    fn content_disposition_with_file_name_round_trips_through_parsing() {
+        for file_name in &["file.c", "my file; v2.c", r#"a "quoted" \name.c"#] {
+            assert_eq!(
+                name(&content_disposition_with_file_name(file_name)),
+                Some(file_name.to_string())
+            );
+        }
+    }
+}
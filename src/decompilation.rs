@@ -1,12 +1,19 @@
 //! Decompilations from the decompiler service.
 
+use std::io::Write;
 use std::time::Duration;
+use std::time::Instant;
 
+use json::JsonValue;
+
+use connection::APIArguments;
 use connection::APIConnection;
 use connection::APIResponse;
+use error::ErrorKind;
 use error::Result;
 use error::ResultExt;
 use file::File;
+use file::StreamedFile;
 use resource::Resource;
 
 /// Arguments for a decompilation.
@@ -26,6 +33,26 @@ use resource::Resource;
 #[derive(Clone, Debug, Default)]
 pub struct DecompilationArguments {
     input_file: Option<File>,
+    streamed_input_file: Option<StreamedFile>,
+    mode: Option<String>,
+    target_language: Option<String>,
+    architecture: Option<String>,
+    endian: Option<String>,
+    raw_entry_point: Option<String>,
+    raw_section_vma: Option<String>,
+    pdb_file: Option<File>,
+    ar_index: Option<u32>,
+    ar_name: Option<String>,
+    sel_decomp_funcs: Option<String>,
+    sel_decomp_ranges: Option<String>,
+    sel_decomp_decoding: Option<String>,
+    decomp_var_names: Option<String>,
+    decomp_optimizations: Option<String>,
+    decomp_unreachable_funcs: Option<bool>,
+    decomp_emit_addresses: Option<bool>,
+    generate_cg: Option<bool>,
+    generate_cfgs: Option<bool>,
+    generate_archive: Option<bool>,
 }
 
 impl DecompilationArguments {
@@ -42,11 +69,352 @@ impl DecompilationArguments {
         self
     }
 
+    /// Sets the file to be decompiled, streaming its content when it is being
+    /// uploaded.
+    ///
+    /// Use this instead of `with_input_file()` for large files that you do not
+    /// want to load into memory. It replaces a previously set input file.
+    pub fn with_streamed_input_file(mut self, input_file: StreamedFile) -> Self {
+        self.set_streamed_input_file(input_file);
+        self
+    }
+
+    /// Sets the decompilation mode.
+    ///
+    /// Available values are: `bin` (default), `c`, `raw`.
+    pub fn with_mode<V>(mut self, mode: V) -> Self
+        where V: Into<String>
+    {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Sets the language of the generated code.
+    ///
+    /// Available values are: `c` (default), `py`.
+    pub fn with_target_language<V>(mut self, target_language: V) -> Self
+        where V: Into<String>
+    {
+        self.set_target_language(target_language);
+        self
+    }
+
+    /// Sets the architecture of the input file.
+    ///
+    /// Available values are: `x86`, `arm`, `thumb`, `mips`, `pic32`, `powerpc`.
+    /// Required in the `raw` mode. In the `bin` mode, the architecture is
+    /// detected automatically.
+    pub fn with_architecture<V>(mut self, architecture: V) -> Self
+        where V: Into<String>
+    {
+        self.set_architecture(architecture);
+        self
+    }
+
+    /// Sets the endianness of the input file.
+    ///
+    /// Available values are: `little`, `big`. Required in the `raw` mode.
+    pub fn with_endian<V>(mut self, endian: V) -> Self
+        where V: Into<String>
+    {
+        self.set_endian(endian);
+        self
+    }
+
+    /// Sets the address of the entry point (e.g. `0x8000`).
+    ///
+    /// Required in the `raw` mode.
+    pub fn with_raw_entry_point<V>(mut self, raw_entry_point: V) -> Self
+        where V: Into<String>
+    {
+        self.set_raw_entry_point(raw_entry_point);
+        self
+    }
+
+    /// Sets the address where the input file is loaded (e.g. `0x8000`).
+    ///
+    /// Required in the `raw` mode.
+    pub fn with_raw_section_vma<V>(mut self, raw_section_vma: V) -> Self
+        where V: Into<String>
+    {
+        self.set_raw_section_vma(raw_section_vma);
+        self
+    }
+
+    /// Sets a PDB file with debugging information for the input file.
+    ///
+    /// Can be used only in the `bin` mode.
+    pub fn with_pdb_file(mut self, pdb_file: File) -> Self {
+        self.set_pdb_file(pdb_file);
+        self
+    }
+
+    /// Selects the member of an archive to be decompiled by its index (starting
+    /// from zero).
+    ///
+    /// Cannot be used together with `ar_name`.
+    pub fn with_ar_index(mut self, ar_index: u32) -> Self {
+        self.set_ar_index(ar_index);
+        self
+    }
+
+    /// Selects the member of an archive to be decompiled by its name.
+    ///
+    /// Cannot be used together with `ar_index`.
+    pub fn with_ar_name<V>(mut self, ar_name: V) -> Self
+        where V: Into<String>
+    {
+        self.set_ar_name(ar_name);
+        self
+    }
+
+    /// Decompiles only the given functions (a comma-separated list of names).
+    pub fn with_sel_decomp_funcs<V>(mut self, sel_decomp_funcs: V) -> Self
+        where V: Into<String>
+    {
+        self.set_sel_decomp_funcs(sel_decomp_funcs);
+        self
+    }
+
+    /// Decompiles only the given address ranges (a comma-separated list, e.g.
+    /// `0x100-0x200,0x300-0x400`).
+    pub fn with_sel_decomp_ranges<V>(mut self, sel_decomp_ranges: V) -> Self
+        where V: Into<String>
+    {
+        self.set_sel_decomp_ranges(sel_decomp_ranges);
+        self
+    }
+
+    /// Sets what should be decoded in a selective decompilation.
+    ///
+    /// Available values are: `everything` (default), `only`.
+    pub fn with_sel_decomp_decoding<V>(mut self, sel_decomp_decoding: V) -> Self
+        where V: Into<String>
+    {
+        self.set_sel_decomp_decoding(sel_decomp_decoding);
+        self
+    }
+
+    /// Sets the naming style of variables.
+    ///
+    /// Available values are: `readable` (default), `address`, `hungarian`,
+    /// `simple`, `unified`.
+    pub fn with_decomp_var_names<V>(mut self, decomp_var_names: V) -> Self
+        where V: Into<String>
+    {
+        self.set_decomp_var_names(decomp_var_names);
+        self
+    }
+
+    /// Sets the level of optimizations performed by the decompiler.
+    ///
+    /// Available values are: `none`, `limited`, `normal` (default), `full`.
+    pub fn with_decomp_optimizations<V>(mut self, decomp_optimizations: V) -> Self
+        where V: Into<String>
+    {
+        self.set_decomp_optimizations(decomp_optimizations);
+        self
+    }
+
+    /// Should functions that are not reachable from the entry point be
+    /// decompiled?
+    pub fn with_decomp_unreachable_funcs(mut self, decomp_unreachable_funcs: bool) -> Self {
+        self.set_decomp_unreachable_funcs(decomp_unreachable_funcs);
+        self
+    }
+
+    /// Should addresses be emitted in comments in the generated code?
+    pub fn with_decomp_emit_addresses(mut self, decomp_emit_addresses: bool) -> Self {
+        self.set_decomp_emit_addresses(decomp_emit_addresses);
+        self
+    }
+
+    /// Should a call graph be generated (the `cg` output)?
+    pub fn with_generate_cg(mut self, generate_cg: bool) -> Self {
+        self.set_generate_cg(generate_cg);
+        self
+    }
+
+    /// Should control-flow graphs be generated (the `cfgs` output)?
+    ///
+    /// The graphs are provided per function (see
+    /// `Decompilation::get_cfg_as_file()`).
+    pub fn with_generate_cfgs(mut self, generate_cfgs: bool) -> Self {
+        self.set_generate_cfgs(generate_cfgs);
+        self
+    }
+
+    /// Should an archive with all outputs be generated (the `archive`
+    /// output)?
+    pub fn with_generate_archive(mut self, generate_archive: bool) -> Self {
+        self.set_generate_archive(generate_archive);
+        self
+    }
+
     /// Sets the file to be analyzed.
     ///
     /// This parameter is required. Without it, there is nothing to analyze.
     pub fn set_input_file(&mut self, input_file: File) {
         self.input_file = Some(input_file);
+        self.streamed_input_file = None;
+    }
+
+    /// Sets the file to be decompiled, streaming its content when it is being
+    /// uploaded.
+    ///
+    /// It replaces a previously set input file.
+    pub fn set_streamed_input_file(&mut self, input_file: StreamedFile) {
+        self.streamed_input_file = Some(input_file);
+        self.input_file = None;
+    }
+
+    /// Sets the decompilation mode.
+    ///
+    /// Available values are: `bin` (default), `c`, `raw`.
+    pub fn set_mode<V>(&mut self, mode: V)
+        where V: Into<String>
+    {
+        self.mode = Some(mode.into());
+    }
+
+    /// Sets the language of the generated code.
+    ///
+    /// Available values are: `c` (default), `py`.
+    pub fn set_target_language<V>(&mut self, target_language: V)
+        where V: Into<String>
+    {
+        self.target_language = Some(target_language.into());
+    }
+
+    /// Sets the architecture of the input file.
+    ///
+    /// Available values are: `x86`, `arm`, `thumb`, `mips`, `pic32`, `powerpc`.
+    /// Required in the `raw` mode. In the `bin` mode, the architecture is
+    /// detected automatically.
+    pub fn set_architecture<V>(&mut self, architecture: V)
+        where V: Into<String>
+    {
+        self.architecture = Some(architecture.into());
+    }
+
+    /// Sets the endianness of the input file.
+    ///
+    /// Available values are: `little`, `big`. Required in the `raw` mode.
+    pub fn set_endian<V>(&mut self, endian: V)
+        where V: Into<String>
+    {
+        self.endian = Some(endian.into());
+    }
+
+    /// Sets the address of the entry point (e.g. `0x8000`).
+    ///
+    /// Required in the `raw` mode.
+    pub fn set_raw_entry_point<V>(&mut self, raw_entry_point: V)
+        where V: Into<String>
+    {
+        self.raw_entry_point = Some(raw_entry_point.into());
+    }
+
+    /// Sets the address where the input file is loaded (e.g. `0x8000`).
+    ///
+    /// Required in the `raw` mode.
+    pub fn set_raw_section_vma<V>(&mut self, raw_section_vma: V)
+        where V: Into<String>
+    {
+        self.raw_section_vma = Some(raw_section_vma.into());
+    }
+
+    /// Sets a PDB file with debugging information for the input file.
+    ///
+    /// Can be used only in the `bin` mode.
+    pub fn set_pdb_file(&mut self, pdb_file: File) {
+        self.pdb_file = Some(pdb_file);
+    }
+
+    /// Selects the member of an archive to be decompiled by its index (starting
+    /// from zero).
+    ///
+    /// Cannot be used together with `ar_name`.
+    pub fn set_ar_index(&mut self, ar_index: u32) {
+        self.ar_index = Some(ar_index);
+    }
+
+    /// Selects the member of an archive to be decompiled by its name.
+    ///
+    /// Cannot be used together with `ar_index`.
+    pub fn set_ar_name<V>(&mut self, ar_name: V)
+        where V: Into<String>
+    {
+        self.ar_name = Some(ar_name.into());
+    }
+
+    /// Decompiles only the given functions (a comma-separated list of names).
+    pub fn set_sel_decomp_funcs<V>(&mut self, sel_decomp_funcs: V)
+        where V: Into<String>
+    {
+        self.sel_decomp_funcs = Some(sel_decomp_funcs.into());
+    }
+
+    /// Decompiles only the given address ranges (a comma-separated list, e.g.
+    /// `0x100-0x200,0x300-0x400`).
+    pub fn set_sel_decomp_ranges<V>(&mut self, sel_decomp_ranges: V)
+        where V: Into<String>
+    {
+        self.sel_decomp_ranges = Some(sel_decomp_ranges.into());
+    }
+
+    /// Sets what should be decoded in a selective decompilation.
+    ///
+    /// Available values are: `everything` (default), `only`.
+    pub fn set_sel_decomp_decoding<V>(&mut self, sel_decomp_decoding: V)
+        where V: Into<String>
+    {
+        self.sel_decomp_decoding = Some(sel_decomp_decoding.into());
+    }
+
+    /// Sets the naming style of variables.
+    ///
+    /// Available values are: `readable` (default), `address`, `hungarian`,
+    /// `simple`, `unified`.
+    pub fn set_decomp_var_names<V>(&mut self, decomp_var_names: V)
+        where V: Into<String>
+    {
+        self.decomp_var_names = Some(decomp_var_names.into());
+    }
+
+    /// Sets the level of optimizations performed by the decompiler.
+    ///
+    /// Available values are: `none`, `limited`, `normal` (default), `full`.
+    pub fn set_decomp_optimizations<V>(&mut self, decomp_optimizations: V)
+        where V: Into<String>
+    {
+        self.decomp_optimizations = Some(decomp_optimizations.into());
+    }
+
+    /// Should functions that are not reachable from the entry point be
+    /// decompiled?
+    pub fn set_decomp_unreachable_funcs(&mut self, decomp_unreachable_funcs: bool) {
+        self.decomp_unreachable_funcs = Some(decomp_unreachable_funcs);
+    }
+
+    /// Should addresses be emitted in comments in the generated code?
+    pub fn set_decomp_emit_addresses(&mut self, decomp_emit_addresses: bool) {
+        self.decomp_emit_addresses = Some(decomp_emit_addresses);
+    }
+
+    /// Sets whether a call graph should be generated.
+    pub fn set_generate_cg(&mut self, generate_cg: bool) {
+        self.generate_cg = Some(generate_cg);
+    }
+
+    /// Sets whether control-flow graphs should be generated.
+    pub fn set_generate_cfgs(&mut self, generate_cfgs: bool) {
+        self.generate_cfgs = Some(generate_cfgs);
+    }
+
+    /// Sets whether an archive with all outputs should be generated.
+    pub fn set_generate_archive(&mut self, generate_archive: bool) {
+        self.generate_archive = Some(generate_archive);
     }
 
     /// Returns the the file to be decompiled.
@@ -58,11 +426,292 @@ impl DecompilationArguments {
     pub fn take_input_file(&mut self) -> Option<File> {
         self.input_file.take()
     }
+
+    /// Returns the file to be decompiled via streaming.
+    pub fn streamed_input_file(&self) -> Option<&StreamedFile> {
+        self.streamed_input_file.as_ref()
+    }
+
+    /// Takes ownership of the streamed input file and sets it to `None`.
+    pub fn take_streamed_input_file(&mut self) -> Option<StreamedFile> {
+        self.streamed_input_file.take()
+    }
+
+    /// Returns the decompilation mode.
+    pub fn mode(&self) -> Option<&String> {
+        self.mode.as_ref()
+    }
+
+    /// Takes ownership of the mode and sets it to `None`.
+    pub fn take_mode(&mut self) -> Option<String> {
+        self.mode.take()
+    }
+
+    /// Returns the language of the generated code.
+    pub fn target_language(&self) -> Option<&String> {
+        self.target_language.as_ref()
+    }
+
+    /// Takes ownership of the target language and sets it to `None`.
+    pub fn take_target_language(&mut self) -> Option<String> {
+        self.target_language.take()
+    }
+
+    /// Returns the architecture of the input file.
+    pub fn architecture(&self) -> Option<&String> {
+        self.architecture.as_ref()
+    }
+
+    /// Takes ownership of the architecture and sets it to `None`.
+    pub fn take_architecture(&mut self) -> Option<String> {
+        self.architecture.take()
+    }
+
+    /// Returns the endianness of the input file.
+    pub fn endian(&self) -> Option<&String> {
+        self.endian.as_ref()
+    }
+
+    /// Takes ownership of the endianness and sets it to `None`.
+    pub fn take_endian(&mut self) -> Option<String> {
+        self.endian.take()
+    }
+
+    /// Returns the address of the entry point.
+    pub fn raw_entry_point(&self) -> Option<&String> {
+        self.raw_entry_point.as_ref()
+    }
+
+    /// Takes ownership of the entry point and sets it to `None`.
+    pub fn take_raw_entry_point(&mut self) -> Option<String> {
+        self.raw_entry_point.take()
+    }
+
+    /// Returns the address where the input file is loaded.
+    pub fn raw_section_vma(&self) -> Option<&String> {
+        self.raw_section_vma.as_ref()
+    }
+
+    /// Takes ownership of the section address and sets it to `None`.
+    pub fn take_raw_section_vma(&mut self) -> Option<String> {
+        self.raw_section_vma.take()
+    }
+
+    /// Returns the PDB file.
+    pub fn pdb_file(&self) -> Option<&File> {
+        self.pdb_file.as_ref()
+    }
+
+    /// Takes ownership of the PDB file and sets it to `None`.
+    pub fn take_pdb_file(&mut self) -> Option<File> {
+        self.pdb_file.take()
+    }
+
+    /// Returns the index of the archive member to be decompiled.
+    pub fn ar_index(&self) -> Option<u32> {
+        self.ar_index
+    }
+
+    /// Returns the name of the archive member to be decompiled.
+    pub fn ar_name(&self) -> Option<&String> {
+        self.ar_name.as_ref()
+    }
+
+    /// Takes ownership of the archive member name and sets it to `None`.
+    pub fn take_ar_name(&mut self) -> Option<String> {
+        self.ar_name.take()
+    }
+
+    /// Returns the functions to be decompiled.
+    pub fn sel_decomp_funcs(&self) -> Option<&String> {
+        self.sel_decomp_funcs.as_ref()
+    }
+
+    /// Takes ownership of the selected functions and sets it to `None`.
+    pub fn take_sel_decomp_funcs(&mut self) -> Option<String> {
+        self.sel_decomp_funcs.take()
+    }
+
+    /// Returns the address ranges to be decompiled.
+    pub fn sel_decomp_ranges(&self) -> Option<&String> {
+        self.sel_decomp_ranges.as_ref()
+    }
+
+    /// Takes ownership of the selected ranges and sets it to `None`.
+    pub fn take_sel_decomp_ranges(&mut self) -> Option<String> {
+        self.sel_decomp_ranges.take()
+    }
+
+    /// Returns what should be decoded in a selective decompilation.
+    pub fn sel_decomp_decoding(&self) -> Option<&String> {
+        self.sel_decomp_decoding.as_ref()
+    }
+
+    /// Takes ownership of the selective decoding and sets it to `None`.
+    pub fn take_sel_decomp_decoding(&mut self) -> Option<String> {
+        self.sel_decomp_decoding.take()
+    }
+
+    /// Returns the naming style of variables.
+    pub fn decomp_var_names(&self) -> Option<&String> {
+        self.decomp_var_names.as_ref()
+    }
+
+    /// Takes ownership of the naming style and sets it to `None`.
+    pub fn take_decomp_var_names(&mut self) -> Option<String> {
+        self.decomp_var_names.take()
+    }
+
+    /// Returns the level of optimizations performed by the decompiler.
+    pub fn decomp_optimizations(&self) -> Option<&String> {
+        self.decomp_optimizations.as_ref()
+    }
+
+    /// Takes ownership of the optimization level and sets it to `None`.
+    pub fn take_decomp_optimizations(&mut self) -> Option<String> {
+        self.decomp_optimizations.take()
+    }
+
+    /// Returns whether unreachable functions should be decompiled.
+    pub fn decomp_unreachable_funcs(&self) -> Option<bool> {
+        self.decomp_unreachable_funcs
+    }
+
+    /// Returns whether addresses should be emitted in the generated code.
+    pub fn decomp_emit_addresses(&self) -> Option<bool> {
+        self.decomp_emit_addresses
+    }
+
+    /// Should a call graph be generated?
+    pub fn generate_cg(&self) -> Option<bool> {
+        self.generate_cg
+    }
+
+    /// Should control-flow graphs be generated?
+    pub fn generate_cfgs(&self) -> Option<bool> {
+        self.generate_cfgs
+    }
+
+    /// Should an archive with all outputs be generated?
+    pub fn generate_archive(&self) -> Option<bool> {
+        self.generate_archive
+    }
+}
+
+/// Kinds of outputs that a decompilation may generate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OutputKind {
+    /// Code in the target high-level language (e.g. C).
+    Hll,
+
+    /// Disassembled input in the form of assembly code.
+    Dsm,
+
+    /// Call graph of the decompiled input.
+    CallGraph,
+
+    /// Control-flow graphs of functions from the decompiled input.
+    ///
+    /// The graphs are provided per function, so they have to be obtained via
+    /// `Decompilation::get_cfg_as_file()`.
+    ControlFlowGraphs,
+
+    /// Archive containing all generated outputs.
+    Archive,
+
+    /// Compiled version of the input (when decompiling source code).
+    Binary,
+}
+
+impl OutputKind {
+    /// Returns the name of the output, as used by the retdec.com's API (e.g.
+    /// `"hll"`).
+    pub fn name(&self) -> &'static str {
+        match *self {
+            OutputKind::Hll => "hll",
+            OutputKind::Dsm => "dsm",
+            OutputKind::CallGraph => "cg",
+            OutputKind::ControlFlowGraphs => "cfgs",
+            OutputKind::Archive => "archive",
+            OutputKind::Binary => "binary",
+        }
+    }
+
+    /// Returns the output with the given name (e.g. `"hll"`), or `None` when
+    /// there is no such output.
+    pub fn from_name(name: &str) -> Option<OutputKind> {
+        match name {
+            "hll" => Some(OutputKind::Hll),
+            "dsm" => Some(OutputKind::Dsm),
+            "cg" => Some(OutputKind::CallGraph),
+            "cfgs" => Some(OutputKind::ControlFlowGraphs),
+            "archive" => Some(OutputKind::Archive),
+            "binary" => Some(OutputKind::Binary),
+            _ => None,
+        }
+    }
 }
 
+/// A phase of a decompilation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecompilationPhase {
+    name: String,
+    part: Option<String>,
+    description: String,
+    completion: u8,
+    warnings: Vec<String>,
+}
+
+impl DecompilationPhase {
+    /// Creates a phase from its JSON representation in a status of a
+    /// decompilation.
+    fn from_json(phase: &JsonValue) -> Self {
+        DecompilationPhase {
+            name: phase["name"].as_str().unwrap_or_default().to_string(),
+            part: phase["part"].as_str().map(str::to_string),
+            description: phase["description"].as_str().unwrap_or_default().to_string(),
+            completion: phase["completion"].as_u8().unwrap_or(0),
+            warnings: phase["warnings"].members()
+                .filter_map(|warning| warning.as_str())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Returns the name of the phase.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the part of the decompiler to which the phase belongs (if any).
+    pub fn part(&self) -> Option<&str> {
+        self.part.as_ref().map(String::as_str)
+    }
+
+    /// Returns a description of the phase.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the completion of the decompilation (in percentages) when the
+    /// phase started.
+    pub fn completion(&self) -> u8 {
+        self.completion
+    }
+
+    /// Returns warnings emitted by the decompiler during the phase.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+/// How long to wait for an output to be generated by default (in seconds).
+const DEFAULT_OUTPUT_GENERATION_TIMEOUT_SECS: u64 = 600;
+
 /// Decompilation from the decompiler service.
 pub struct Decompilation {
     resource: Resource,
+    output_generation_timeout: Duration,
 }
 
 impl Decompilation {
@@ -72,10 +721,19 @@ impl Decompilation {
     #[doc(hidden)]
     pub fn new<I: Into<String>>(id: I, conn: Box<APIConnection>) -> Self {
         Decompilation {
-            resource: Resource::new("decompiler", "decompilations", id, conn)
+            resource: Resource::new("decompiler", "decompilations", id, conn),
+            output_generation_timeout: Duration::from_secs(DEFAULT_OUTPUT_GENERATION_TIMEOUT_SECS),
         }
     }
 
+    /// Sets how long to wait for an output to be generated (see
+    /// `wait_until_output_generated()`).
+    ///
+    /// By default, it is 10 minutes.
+    pub fn set_output_generation_timeout(&mut self, timeout: Duration) {
+        self.output_generation_timeout = timeout;
+    }
+
     /// Returns the ID of the decompilation.
     ///
     /// Does not access the API.
@@ -164,24 +822,148 @@ impl Decompilation {
         self.resource.get_error()
     }
 
-    /// Waits until the decompilation has finished.
+    /// Returns the completion of the decompilation (in percentages).
+    ///
+    /// Does not access the API, so the returned value may be outdated. If you
+    /// want to have an up-to-date information, use `get_completion()` instead.
+    pub fn completion(&self) -> u8 {
+        self.resource.status["completion"].as_u8().unwrap_or(0)
+    }
+
+    /// Returns the completion of the decompilation (in percentages).
+    ///
+    /// Accesses the API.
+    pub fn get_completion(&mut self) -> Result<u8> {
+        self.resource.has_finished()?;
+        Ok(self.completion())
+    }
+
+    /// Returns the phases through which the decompilation has gone so far.
+    ///
+    /// The last phase is the one that is currently running (or the last one
+    /// when the decompilation has finished).
+    ///
+    /// Does not access the API, so the returned value may be outdated. If you
+    /// want to have an up-to-date information, use `get_phases()` instead.
+    pub fn phases(&self) -> Vec<DecompilationPhase> {
+        self.resource.status["phases"].members()
+            .map(DecompilationPhase::from_json)
+            .collect()
+    }
+
+    /// Returns the phases through which the decompilation has gone so far.
+    ///
+    /// Accesses the API.
+    pub fn get_phases(&mut self) -> Result<Vec<DecompilationPhase>> {
+        self.resource.has_finished()?;
+        Ok(self.phases())
+    }
+
+    /// Waits until the decompilation has finished.
+    ///
+    /// When this method returns `Ok()`, the decompilation has finished.
+    ///
+    /// Accesses the API.
+    pub fn wait_until_finished(&mut self) -> Result<()> {
+        self.wait_until_finished_with_callback(|_| {})
+    }
+
+    /// Waits until the decompilation has finished, calling the given callback
+    /// after each update of the status of the decompilation.
+    ///
+    /// The callback can be used to report progress (see `completion()` and
+    /// `phases()`). When this method returns `Ok()`, the decompilation has
+    /// finished.
+    ///
+    /// Accesses the API.
+    pub fn wait_until_finished_with_callback<F>(&mut self, mut callback: F) -> Result<()>
+        where F: FnMut(&Decompilation)
+    {
+        // Currently, the retdec.com's API does not support push notifications,
+        // so we have to poll for the status ourselves.
+        while !self.finished() {
+            self.resource.wait_for(Duration::from_millis(500));
+
+            self.resource.update_status()
+                .chain_err(|| "failed to update decompilation status")?;
+            callback(self);
+            if self.finished() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until the given output has been generated.
+    ///
+    /// The call graph, control-flow graphs, and the archive are generated
+    /// after the decompilation has finished, so they may not be available
+    /// right away. Other outputs are available as soon as the decompilation
+    /// has succeeded, so this method returns immediately for them. For
+    /// control-flow graphs, it waits for the graphs of all functions (see
+    /// `wait_until_cfg_generated()`).
+    ///
+    /// Fails when the generation of the output failed or when the output has
+    /// not been generated in time (see `set_output_generation_timeout()`).
+    /// When the API does not report the state of the output, the output is
+    /// assumed to have been generated.
+    ///
+    /// Accesses the API.
+    pub fn wait_until_output_generated(&mut self, kind: OutputKind) -> Result<()> {
+        match kind {
+            OutputKind::CallGraph | OutputKind::Archive => {
+                let name = kind.name();
+                self.wait_until_generated(
+                    &format!("{} output", name),
+                    |status| &status[name]
+                )
+            }
+            OutputKind::ControlFlowGraphs => {
+                for func in self.get_funcs_with_cfg()? {
+                    self.wait_until_cfg_generated(&func)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the names of functions for which control-flow graphs are
+    /// generated.
+    ///
+    /// The functions are known only after the decompilation has finished and
+    /// only when the graphs were requested (see
+    /// `DecompilationArguments::with_generate_cfgs()`).
+    ///
+    /// Does not access the API, so the returned value may be outdated. If you
+    /// want to have an up-to-date information, use `get_funcs_with_cfg()`
+    /// instead.
+    pub fn funcs_with_cfg(&self) -> Vec<String> {
+        self.resource.status["cfgs"].entries()
+            .map(|(func, _)| func.to_string())
+            .collect()
+    }
+
+    /// Returns the names of functions for which control-flow graphs are
+    /// generated.
+    ///
+    /// Accesses the API.
+    pub fn get_funcs_with_cfg(&mut self) -> Result<Vec<String>> {
+        self.resource.has_finished()?;
+        Ok(self.funcs_with_cfg())
+    }
+
+    /// Waits until the control-flow graph of the given function has been
+    /// generated.
     ///
-    /// When this method returns `Ok()`, the decompilation has finished.
+    /// Behaves like `wait_until_output_generated()`.
     ///
     /// Accesses the API.
-    pub fn wait_until_finished(&mut self) -> Result<()> {
-        // Currently, the retdec.com's API does not support push notifications,
-        // so we have to poll for the status ourselves.
-        while !self.finished() {
-            self.resource.wait_for(Duration::from_millis(500));
-
-            self.resource.update_status()
-                .chain_err(|| "failed to update decompilation status")?;
-            if self.finished() {
-                break;
-            }
-        }
-        Ok(())
+    pub fn wait_until_cfg_generated(&mut self, func: &str) -> Result<()> {
+        self.wait_until_generated(
+            &format!("control-flow graph of function {}", func),
+            |status| &status["cfgs"][func]
+        )
     }
 
     /// Returns the output code in the target high-level language (HLL).
@@ -193,7 +975,7 @@ impl Decompilation {
     ///
     /// Accesses the API.
     pub fn get_output_hll_code(&mut self) -> Result<String> {
-        let response = self.get_output_response("hll")?;
+        let response = self.get_output_response(OutputKind::Hll)?;
         response.body_as_string()
     }
 
@@ -207,16 +989,154 @@ impl Decompilation {
     ///
     /// Accesses the API.
     pub fn get_output_hll_code_as_file(&mut self) -> Result<File> {
-        let response = self.get_output_response("hll")?;
-        response.body_as_file()
+        self.get_output_as_file(OutputKind::Hll)
+    }
+
+    /// Returns the given output as a file.
+    ///
+    /// The name of the file is provided by the API. When it is missing, the
+    /// file is named after the decompilation and the output (e.g. `ID.dsm`).
+    ///
+    /// This function should be called only after the decompilation has
+    /// successfully finished. Outputs other than the HLL code and disassembly
+    /// have to be requested when starting the decompilation (e.g. via
+    /// `DecompilationArguments::with_generate_cg()`) and may be generated
+    /// later (see `wait_until_output_generated()`).
+    ///
+    /// Accesses the API.
+    pub fn get_output_as_file(&mut self, kind: OutputKind) -> Result<File> {
+        let response = self.get_output_response(kind)?;
+        Ok(response.body_as_file_or_named(self.fallback_output_name(kind)))
+    }
+
+    /// Writes the given output into the given writer.
+    ///
+    /// Unlike `get_output_hll_code()`, the output is not kept in memory but
+    /// written into the writer as it is being received, which is suitable for
+    /// large outputs (e.g. archives). Returns the number of written bytes.
+    ///
+    /// This function should be called only after the decompilation has
+    /// successfully finished.
+    ///
+    /// Accesses the API.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use retdec::error::Result;
+    /// # fn test() -> Result<()> {
+    /// # use retdec::decompiler::Decompiler;
+    /// # use retdec::decompilation::DecompilationArguments;
+    /// # use retdec::file::File;
+    /// # use retdec::settings::Settings;
+    /// use std::fs;
+    /// use retdec::decompilation::OutputKind;
+    /// use retdec::error::ResultExt;
+    ///
+    /// # let decompiler = Decompiler::new(Settings::new());
+    /// # let args = DecompilationArguments::new()
+    /// #     .with_input_file(File::from_path("file.exe")?);
+    /// let mut decompilation = decompiler.start_decompilation(args)?;
+    /// decompilation.wait_until_finished()?;
+    /// let mut archive = fs::File::create("file.zip")
+    ///     .chain_err(|| "failed to create file.zip")?;
+    /// let written = decompilation.write_output_to(OutputKind::Archive, &mut archive)?;
+    /// println!("saved {} bytes", written);
+    /// # Ok(()) } fn main() { test().unwrap() }
+    /// ```
+    pub fn write_output_to<W>(&mut self, kind: OutputKind, writer: &mut W) -> Result<u64>
+        where W: Write
+    {
+        self.ensure_decompilation_has_succeeded()?;
+        let output_url = self.output_url(kind)?;
+        let (response, written) = self.resource.conn.send_get_request_into(
+            &output_url,
+            APIArguments::new(),
+            writer
+        )?;
+        if response.failed() {
+            bail!(ErrorKind::RequestFailed(
+                response.status_code(),
+                format!("request to {} failed: {}", output_url, response.error_reason())
+            ));
+        }
+        Ok(written)
+    }
+
+    /// Returns the control-flow graph of the given function as a file.
+    ///
+    /// The name of the file is provided by the API. When it is missing, the
+    /// file is named after the decompilation and the function (e.g.
+    /// `ID.main.cfg`).
+    ///
+    /// This function should be called only after the decompilation has
+    /// successfully finished and the graph has been generated (see
+    /// `funcs_with_cfg()` and `wait_until_cfg_generated()`).
+    ///
+    /// Accesses the API.
+    pub fn get_cfg_as_file(&mut self, func: &str) -> Result<File> {
+        self.ensure_decompilation_has_succeeded()?;
+        let cfg_url = format!("{}/outputs/cfgs/{}", self.resource.base_url, func);
+        let response = self.resource.conn.send_get_request_without_args(&cfg_url)?;
+        Ok(response.body_as_file_or_named(format!("{}.{}.cfg", self.resource.id, func)))
     }
 
-    fn get_output_response(&mut self, output_type: &str) -> Result<APIResponse> {
+    fn get_output_response(&mut self, kind: OutputKind) -> Result<APIResponse> {
         self.ensure_decompilation_has_succeeded()?;
-        let output_url = format!("{}/outputs/{}", self.resource.base_url, output_type);
+        let output_url = self.output_url(kind)?;
         self.resource.conn.send_get_request_without_args(&output_url)
     }
 
+    fn output_url(&self, kind: OutputKind) -> Result<String> {
+        if kind == OutputKind::ControlFlowGraphs {
+            bail!("control-flow graphs are provided per function (use get_cfg_as_file())");
+        }
+        Ok(format!("{}/outputs/{}", self.resource.base_url, kind.name()))
+    }
+
+    /// Waits until the output whose status is selected from the status of
+    /// the decompilation by `output_status` has been generated.
+    fn wait_until_generated<F>(&mut self, output: &str, output_status: F) -> Result<()>
+        where F: Fn(&JsonValue) -> &JsonValue
+    {
+        if output_status(&self.resource.status).is_null() {
+            self.resource.update_status()
+                .chain_err(|| "failed to update decompilation status")?;
+        }
+        let started = Instant::now();
+        loop {
+            let status = output_status(&self.resource.status).clone();
+            if status.is_null() || status["generated"].as_bool() == Some(true) {
+                return Ok(());
+            }
+            if status["generation_failed"].as_bool() == Some(true) {
+                bail!(ErrorKind::JobFailed(format!(
+                    "generation of the {} of decompilation {} failed: {}",
+                    output,
+                    self.resource.id,
+                    status["generation_error"].as_str().unwrap_or("unknown error")
+                )));
+            }
+            if started.elapsed() >= self.output_generation_timeout {
+                bail!(ErrorKind::JobFailed(format!(
+                    "the {} of decompilation {} has not been generated in {} seconds",
+                    output,
+                    self.resource.id,
+                    self.output_generation_timeout.as_secs()
+                )));
+            }
+            self.resource.wait_for(Duration::from_millis(500));
+            self.resource.update_status()
+                .chain_err(|| "failed to update decompilation status")?;
+        }
+    }
+
+    /// Name of the output file used when the API does not provide one (e.g.
+    /// `ID.hll`).
+    fn fallback_output_name(&self, kind: OutputKind) -> String {
+        format!("{}.{}", self.resource.id, kind.name())
+    }
+
     fn ensure_decompilation_has_succeeded(&mut self) -> Result<()> {
         self.resource.ensure_has_succeeded("decompilation")
     }
@@ -229,10 +1149,10 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use connection::tests::APIArgumentsBuilder;
-    use connection::tests::APIConnectionMock;
-    use connection::tests::APIConnectionMockWrapper;
-    use connection::tests::APIResponseBuilder;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionMock;
+    use testing::APIConnectionMockWrapper;
+    use testing::APIResponseBuilder;
     use settings::Settings;
 
     fn create_decompilation() -> (Rc<RefCell<APIConnectionMock>>, Decompilation) {
@@ -301,6 +1221,34 @@ mod tests {
         conn.borrow_mut().reset();
     }
 
+    #[test]
+    fn decompilation_arguments_streamed_input_file_replaces_input_file() {
+        let streamed_file = StreamedFile::from_reader(
+            ::std::io::Cursor::new(b"content"), 7, "file.exe"
+        );
+
+        let args = DecompilationArguments::new()
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"))
+            .with_streamed_input_file(streamed_file);
+
+        assert!(args.input_file().is_none());
+        assert_eq!(args.streamed_input_file().unwrap().name(), "file.exe");
+    }
+
+    #[test]
+    fn decompilation_arguments_input_file_replaces_streamed_input_file() {
+        let streamed_file = StreamedFile::from_reader(
+            ::std::io::Cursor::new(b"content"), 7, "file.exe"
+        );
+
+        let args = DecompilationArguments::new()
+            .with_streamed_input_file(streamed_file)
+            .with_input_file(File::from_content_with_name(b"content", "file.exe"));
+
+        assert!(args.streamed_input_file().is_none());
+        assert_eq!(args.input_file().unwrap().name(), "file.exe");
+    }
+
     #[test]
     fn decompilation_id_returns_id_of_decompilation() {
         let (_, decompilation) = create_decompilation();
@@ -577,6 +1525,82 @@ mod tests {
         ));
     }
 
+    fn add_status_with_progress(conn: &Rc<RefCell<APIConnectionMock>>) {
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/status",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false,
+                        "completion": 100,
+                        "phases": [
+                            {
+                                "part": null,
+                                "name": "Initialization",
+                                "description": "Initializing",
+                                "completion": 0,
+                                "warnings": []
+                            },
+                            {
+                                "part": "Decompiler",
+                                "name": "Finalization",
+                                "description": "Finalizing",
+                                "completion": 95,
+                                "warnings": ["Unreachable code removed."]
+                            }
+                        ]
+                    }"#)
+                    .build()
+            )
+        );
+    }
+
+    #[test]
+    fn decompilation_completion_and_phases_are_empty_before_status_is_known() {
+        let (_, decompilation) = create_decompilation();
+
+        assert_eq!(decompilation.completion(), 0);
+        assert!(decompilation.phases().is_empty());
+    }
+
+    #[test]
+    fn decompilation_get_completion_and_get_phases_return_values_from_status() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with_progress(&conn);
+
+        let completion = decompilation.get_completion()
+            .expect("get_completion() should have succeeded");
+        let phases = decompilation.get_phases()
+            .expect("get_phases() should have succeeded");
+
+        assert_eq!(completion, 100);
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases[0].name(), "Initialization");
+        assert_eq!(phases[0].part(), None);
+        assert_eq!(phases[1].name(), "Finalization");
+        assert_eq!(phases[1].part(), Some("Decompiler"));
+        assert_eq!(phases[1].description(), "Finalizing");
+        assert_eq!(phases[1].completion(), 95);
+        assert!(phases[0].warnings().is_empty());
+        assert_eq!(phases[1].warnings(), &["Unreachable code removed.".to_string()]);
+    }
+
+    #[test]
+    fn decompilation_wait_until_finished_with_callback_calls_callback_after_each_update() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with_progress(&conn);
+        let mut completions = Vec::new();
+
+        decompilation.wait_until_finished_with_callback(|d| completions.push(d.completion()))
+            .expect("wait_until_finished_with_callback() should have succeeded");
+
+        assert_eq!(completions, vec![100]);
+    }
+
     #[test]
     fn decompilation_get_output_hll_code_checks_if_decompilation_succeeded_and_returns_its_output() {
         let (conn, mut decompilation) = create_decompilation();
@@ -635,4 +1659,266 @@ mod tests {
                 .build()
         ));
     }
+
+    #[test]
+    fn decompilation_get_output_hll_code_as_file_uses_fallback_name_when_api_provides_none() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/outputs/hll",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(b"Output from decompilation")
+                    .build()
+            )
+        );
+
+        let output_file = decompilation.get_output_hll_code_as_file()
+            .expect("get_output_hll_code_as_file() should have succeeded");
+
+        assert_eq!(output_file.name(), "ID.hll");
+        assert_eq!(output_file.content(), b"Output from decompilation");
+    }
+
+    #[test]
+    fn output_kind_name_returns_name_used_by_api() {
+        assert_eq!(OutputKind::Hll.name(), "hll");
+        assert_eq!(OutputKind::Dsm.name(), "dsm");
+        assert_eq!(OutputKind::CallGraph.name(), "cg");
+        assert_eq!(OutputKind::ControlFlowGraphs.name(), "cfgs");
+        assert_eq!(OutputKind::Archive.name(), "archive");
+        assert_eq!(OutputKind::Binary.name(), "binary");
+    }
+
+    #[test]
+    fn output_kind_from_name_returns_output_with_given_name() {
+        for kind in &[OutputKind::Hll, OutputKind::Dsm, OutputKind::CallGraph,
+                      OutputKind::ControlFlowGraphs, OutputKind::Archive, OutputKind::Binary] {
+            assert_eq!(OutputKind::from_name(kind.name()), Some(*kind));
+        }
+        assert_eq!(OutputKind::from_name("unknown"), None);
+    }
+
+    fn add_status_with(conn: &Rc<RefCell<APIConnectionMock>>, output: &str, output_status: &str) {
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/status",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(format!(r#"{{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false,
+                        "{}": {}
+                    }}"#, output, output_status).as_bytes())
+                    .build()
+            )
+        );
+    }
+
+    fn add_status_with_cg(conn: &Rc<RefCell<APIConnectionMock>>, cg_status: &str) {
+        add_status_with(conn, "cg", cg_status);
+    }
+
+    #[test]
+    fn decompilation_wait_until_output_generated_returns_immediately_for_hll() {
+        let (conn, mut decompilation) = create_decompilation();
+
+        decompilation.wait_until_output_generated(OutputKind::Hll)
+            .expect("wait_until_output_generated() should have succeeded");
+
+        assert!(conn.borrow().no_requests_sent());
+    }
+
+    #[test]
+    fn decompilation_wait_until_output_generated_succeeds_when_output_was_generated() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with_cg(
+            &conn,
+            r#"{"generated": true, "generation_failed": false, "generation_error": null}"#
+        );
+
+        decompilation.wait_until_output_generated(OutputKind::CallGraph)
+            .expect("wait_until_output_generated() should have succeeded");
+    }
+
+    #[test]
+    fn decompilation_wait_until_output_generated_returns_error_when_generation_failed() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with_cg(
+            &conn,
+            r#"{"generated": false, "generation_failed": true, "generation_error": "no graph"}"#
+        );
+
+        let err = decompilation.wait_until_output_generated(OutputKind::CallGraph)
+            .err().expect("wait_until_output_generated() should have failed");
+
+        assert!(err.to_string().contains("no graph"));
+    }
+
+    #[test]
+    fn decompilation_wait_until_output_generated_returns_error_when_output_is_never_generated() {
+        let (conn, mut decompilation) = create_decompilation();
+        decompilation.set_output_generation_timeout(Duration::from_secs(0));
+        add_status_with_cg(
+            &conn,
+            r#"{"generated": false, "generation_failed": false, "generation_error": null}"#
+        );
+
+        let err = decompilation.wait_until_output_generated(OutputKind::CallGraph)
+            .err().expect("wait_until_output_generated() should have failed");
+
+        assert_eq!(
+            err.to_string(),
+            "the cg output of decompilation ID has not been generated in 0 seconds"
+        );
+        match *err.kind() {
+            ErrorKind::JobFailed(_) => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn decompilation_funcs_with_cfg_returns_functions_from_status() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with(
+            &conn,
+            "cfgs",
+            r#"{
+                "main": {"generated": true, "generation_failed": false, "generation_error": null},
+                "my_func": {"generated": true, "generation_failed": false, "generation_error": null}
+            }"#
+        );
+
+        let funcs = decompilation.get_funcs_with_cfg()
+            .expect("get_funcs_with_cfg() should have succeeded");
+
+        assert_eq!(funcs, vec!["main".to_string(), "my_func".to_string()]);
+        assert_eq!(decompilation.funcs_with_cfg(), funcs);
+    }
+
+    #[test]
+    fn decompilation_wait_until_output_generated_waits_for_cfgs_of_all_functions() {
+        let (conn, mut decompilation) = create_decompilation();
+        add_status_with(
+            &conn,
+            "cfgs",
+            r#"{
+                "main": {"generated": true, "generation_failed": false, "generation_error": null},
+                "my_func": {"generated": false, "generation_failed": true, "generation_error": "no cfg"}
+            }"#
+        );
+
+        let err = decompilation.wait_until_output_generated(OutputKind::ControlFlowGraphs)
+            .err().expect("wait_until_output_generated() should have failed");
+
+        assert_eq!(
+            err.to_string(),
+            "generation of the control-flow graph of function my_func of decompilation ID \
+             failed: no cfg"
+        );
+    }
+
+    #[test]
+    fn decompilation_get_cfg_as_file_returns_cfg_of_given_function() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/outputs/cfgs/main",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_file(File::from_content_with_name(b"<svg/>", "file.main.svg"))
+                    .build()
+            )
+        );
+
+        let cfg_file = decompilation.get_cfg_as_file("main")
+            .expect("get_cfg_as_file() should have succeeded");
+
+        assert_eq!(cfg_file.name(), "file.main.svg");
+        assert_eq!(cfg_file.content(), b"<svg/>");
+    }
+
+    #[test]
+    fn decompilation_get_output_as_file_returns_error_for_cfgs() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+
+        let result = decompilation.get_output_as_file(OutputKind::ControlFlowGraphs);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decompilation_get_output_as_file_returns_requested_output() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/outputs/dsm",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_file(File::from_content_with_name(b"; dsm", "file.dsm"))
+                    .build()
+            )
+        );
+
+        let output_file = decompilation.get_output_as_file(OutputKind::Dsm)
+            .expect("get_output_as_file() should have succeeded");
+
+        assert_eq!(output_file.name(), "file.dsm");
+        assert_eq!(output_file.content(), b"; dsm");
+    }
+
+    #[test]
+    fn decompilation_write_output_to_writes_output_into_writer() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/outputs/archive",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(b"PK\x03\x04")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let written = decompilation.write_output_to(OutputKind::Archive, &mut output)
+            .expect("write_output_to() should have succeeded");
+
+        assert_eq!(written, 4);
+        assert_eq!(output, b"PK\x03\x04");
+    }
+
+    #[test]
+    fn decompilation_write_output_to_returns_error_and_writes_nothing_when_request_fails() {
+        let (conn, mut decompilation) = create_decompilation();
+        make_decompilation_succeed(&conn, &mut decompilation);
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/outputs/cg",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(404)
+                    .with_status_message("Not Found")
+                    .with_body(b"{}")
+                    .build()
+            )
+        );
+        let mut output = Vec::new();
+
+        let result = decompilation.write_output_to(OutputKind::CallGraph, &mut output);
+
+        assert!(result.is_err());
+        assert!(output.is_empty());
+    }
 }
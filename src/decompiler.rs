@@ -1,12 +1,16 @@
 //! Access to the file-decompiling service
 //! ([decompiler](https://retdec.com/api/docs/decompiler.html)).
 
+use batch::DecompilationBatch;
+use cache::CachingAPIConnectionFactory;
+use cache::ResultCache;
 use connection::APIArguments;
 use connection::APIConnectionFactory;
 use connection::HyperAPIConnectionFactory;
 use connection::ResponseVerifyingAPIConnectionFactory;
 use decompilation::Decompilation;
 use decompilation::DecompilationArguments;
+use error::ErrorKind;
 use error::Result;
 use error::ResultExt;
 use settings::Settings;
@@ -41,11 +45,31 @@ pub struct Decompiler {
 impl Decompiler {
     /// Creates a new instance of the file-decompiling service.
     pub fn new(settings: Settings) -> Self {
+        Self::with_transport(Box::new(HyperAPIConnectionFactory::new(settings)))
+    }
+
+    /// Creates a new instance of the file-decompiling service that uses the given
+    /// transport.
+    ///
+    /// The transport is used to send all requests to the API. By default,
+    /// `HyperAPIConnectionFactory` is used.
+    pub fn with_transport(conn_factory: Box<APIConnectionFactory>) -> Self {
         Decompiler {
             conn_factory: Box::new(
-                ResponseVerifyingAPIConnectionFactory::new(
-                    Box::new(HyperAPIConnectionFactory::new(settings))
-                )
+                ResponseVerifyingAPIConnectionFactory::new(conn_factory)
+            ),
+        }
+    }
+
+    /// Serves results of already finished decompilations from the given cache.
+    ///
+    /// When a decompilation with the same input file and arguments has already
+    /// finished successfully, `start_decompilation()` returns it without
+    /// accessing the network, and its outputs are read from the cache.
+    pub fn with_cache(self, cache: ResultCache) -> Self {
+        Decompiler {
+            conn_factory: Box::new(
+                CachingAPIConnectionFactory::new(self.conn_factory, cache)
             ),
         }
     }
@@ -58,28 +82,69 @@ impl Decompiler {
         let response = conn.send_post_request(&url, api_args)
             .chain_err(|| "failed to start a decompilation")?;
         let id = response.json_value_as_string("id")
-            .ok_or_else(|| format!("{} returned invalid JSON response", url))?;
+            .ok_or_else(|| ErrorKind::InvalidResponse(
+                format!("{} returned invalid JSON response", url)
+            ))?;
         Ok(Decompilation::new(id, conn))
     }
 
+    /// Returns a decompilation that has already been started (e.g. by another
+    /// process) and has the given ID.
+    ///
+    /// The API is not accessed until the status or outputs of the returned
+    /// decompilation are requested. When there is no such decompilation, these requests fail.
+    pub fn attach_to_decompilation<I: Into<String>>(&self, id: I) -> Decompilation {
+        Decompilation::new(id, self.conn_factory.new_connection())
+    }
+
+    /// Creates a batch of decompilations with the given arguments.
+    ///
+    /// The decompilations are started and polled while the returned batch is
+    /// being iterated. See [`DecompilationBatch`](../batch/struct.DecompilationBatch.html)
+    /// for more details.
+    pub fn start_batch<'a, I>(&'a self, args: I) -> DecompilationBatch<'a>
+        where I: IntoIterator<Item = DecompilationArguments>,
+              I::IntoIter: 'a
+    {
+        DecompilationBatch::new(self, args)
+    }
+
     fn create_api_args(&self, mut args: DecompilationArguments) -> Result<APIArguments> {
         let mut api_args = APIArguments::new();
-        api_args.add_string_arg("mode", "bin");
-        match args.take_input_file() {
-            Some(input_file) => {
+        api_args.add_string_arg("mode", args.take_mode().unwrap_or_else(|| "bin".to_string()));
+        api_args.add_opt_string_arg("target_language", args.take_target_language());
+        api_args.add_opt_string_arg("architecture", args.take_architecture());
+        api_args.add_opt_string_arg("endian", args.take_endian());
+        api_args.add_opt_string_arg("raw_entry_point", args.take_raw_entry_point());
+        api_args.add_opt_string_arg("raw_section_vma", args.take_raw_section_vma());
+        api_args.add_opt_string_arg("ar_index", args.ar_index().map(|i| i.to_string()));
+        api_args.add_opt_string_arg("ar_name", args.take_ar_name());
+        api_args.add_opt_string_arg("sel_decomp_funcs", args.take_sel_decomp_funcs());
+        api_args.add_opt_string_arg("sel_decomp_ranges", args.take_sel_decomp_ranges());
+        api_args.add_opt_string_arg("sel_decomp_decoding", args.take_sel_decomp_decoding());
+        api_args.add_opt_string_arg("decomp_var_names", args.take_decomp_var_names());
+        api_args.add_opt_string_arg("decomp_optimizations", args.take_decomp_optimizations());
+        api_args.add_opt_bool_arg("decomp_unreach_funcs", args.decomp_unreachable_funcs());
+        api_args.add_opt_bool_arg("decomp_emit_addresses", args.decomp_emit_addresses());
+        api_args.add_opt_bool_arg("generate_cg", args.generate_cg());
+        api_args.add_opt_bool_arg("generate_cfgs", args.generate_cfgs());
+        api_args.add_opt_bool_arg("generate_archive", args.generate_archive());
+        match (args.take_input_file(), args.take_streamed_input_file()) {
+            (Some(input_file), _) => {
                 api_args.add_file("input", input_file);
             }
-            None => {
+            (None, Some(input_file)) => {
+                api_args.add_streamed_file("input", input_file);
+            }
+            (None, None) => {
                 bail!("no input file given");
             }
         }
+        if let Some(pdb_file) = args.take_pdb_file() {
+            api_args.add_file("pdb", pdb_file);
+        }
         Ok(api_args)
     }
-
-    #[cfg(test)]
-    fn with_conn_factory(conn_factory: Box<APIConnectionFactory>) -> Self {
-        Decompiler { conn_factory: conn_factory }
-    }
 }
 
 #[cfg(test)]
@@ -89,12 +154,13 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use connection::tests::APIArgumentsBuilder;
-    use connection::tests::APIConnectionFactoryMock;
-    use connection::tests::APIConnectionMock;
-    use connection::tests::APIResponseBuilder;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
     use decompilation::DecompilationArguments;
     use file::File;
+    use file::StreamedFile;
 
     fn create_decompiler() -> (Rc<RefCell<APIConnectionMock>>, Decompiler) {
         // We need to force an API URL to prevent it from being overridden by
@@ -104,7 +170,7 @@ mod tests {
             .with_api_url("https://retdec.com/service/api");
         let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings.clone())));
         let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
-        (conn, Decompiler::with_conn_factory(conn_factory))
+        (conn, Decompiler::with_transport(conn_factory))
     }
 
     #[test]
@@ -140,6 +206,140 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn decompiler_start_decompilation_sends_streamed_input_file() {
+        let (conn, decompiler) = create_decompiler();
+        let input_file = StreamedFile::from_reader(
+            ::std::io::Cursor::new(b"content"), 7, "file.exe"
+        );
+        let args = DecompilationArguments::new()
+            .with_streamed_input_file(input_file.clone());
+        conn.borrow_mut().add_response(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "id": "ID"
+                    }"#)
+                    .build()
+            )
+        );
+
+        decompiler.start_decompilation(args)
+            .expect("decompilation should have succeeded");
+
+        assert!(conn.borrow_mut().request_sent(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            APIArgumentsBuilder::new()
+                .with_string_arg("mode", "bin")
+                .with_streamed_file("input", input_file)
+                .build()
+        ));
+    }
+
+    #[test]
+    fn decompiler_start_decompilation_requests_generation_of_given_outputs() {
+        let (conn, decompiler) = create_decompiler();
+        let input_file = File::from_content_with_name(b"content", "file.exe");
+        let args = DecompilationArguments::new()
+            .with_input_file(input_file.clone())
+            .with_generate_cg(true)
+            .with_generate_cfgs(false)
+            .with_generate_archive(true);
+        conn.borrow_mut().add_response(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "id": "ID"
+                    }"#)
+                    .build()
+            )
+        );
+
+        decompiler.start_decompilation(args)
+            .expect("decompilation should have succeeded");
+
+        assert!(conn.borrow_mut().request_sent(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            APIArgumentsBuilder::new()
+                .with_string_arg("mode", "bin")
+                .with_bool_arg("generate_cg", true)
+                .with_bool_arg("generate_cfgs", false)
+                .with_bool_arg("generate_archive", true)
+                .with_file("input", input_file)
+                .build()
+        ));
+    }
+
+    #[test]
+    fn decompiler_start_decompilation_sends_all_given_arguments() {
+        let (conn, decompiler) = create_decompiler();
+        let input_file = File::from_content_with_name(b"content", "file.bin");
+        let pdb_file = File::from_content_with_name(b"pdb", "file.pdb");
+        let args = DecompilationArguments::new()
+            .with_input_file(input_file.clone())
+            .with_mode("raw")
+            .with_target_language("py")
+            .with_architecture("arm")
+            .with_endian("big")
+            .with_raw_entry_point("0x8000")
+            .with_raw_section_vma("0x4000")
+            .with_pdb_file(pdb_file.clone())
+            .with_ar_index(2)
+            .with_sel_decomp_funcs("main,foo")
+            .with_sel_decomp_ranges("0x100-0x200")
+            .with_sel_decomp_decoding("only")
+            .with_decomp_var_names("simple")
+            .with_decomp_optimizations("full")
+            .with_decomp_unreachable_funcs(true)
+            .with_decomp_emit_addresses(false);
+        conn.borrow_mut().add_response(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "id": "ID"
+                    }"#)
+                    .build()
+            )
+        );
+
+        decompiler.start_decompilation(args)
+            .expect("decompilation should have succeeded");
+
+        assert!(conn.borrow_mut().request_sent(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            APIArgumentsBuilder::new()
+                .with_string_arg("mode", "raw")
+                .with_string_arg("target_language", "py")
+                .with_string_arg("architecture", "arm")
+                .with_string_arg("endian", "big")
+                .with_string_arg("raw_entry_point", "0x8000")
+                .with_string_arg("raw_section_vma", "0x4000")
+                .with_string_arg("ar_index", "2")
+                .with_string_arg("sel_decomp_funcs", "main,foo")
+                .with_string_arg("sel_decomp_ranges", "0x100-0x200")
+                .with_string_arg("sel_decomp_decoding", "only")
+                .with_string_arg("decomp_var_names", "simple")
+                .with_string_arg("decomp_optimizations", "full")
+                .with_bool_arg("decomp_unreach_funcs", true)
+                .with_bool_arg("decomp_emit_addresses", false)
+                .with_file("input", input_file)
+                .with_file("pdb", pdb_file)
+                .build()
+        ));
+    }
+
     #[test]
     fn decompiler_start_decompilation_returns_error_when_input_file_is_not_given() {
         let (conn, decompiler) = create_decompiler();
@@ -188,4 +388,64 @@ mod tests {
             "https://retdec.com/service/api/decompiler/decompilations returned invalid JSON response"
         );
     }
+
+    #[test]
+    fn decompiler_start_decompilation_returns_error_when_transport_returns_failed_response() {
+        let (conn, decompiler) = create_decompiler();
+        let input_file = File::from_content_with_name(b"content", "file.exe");
+        let args = DecompilationArguments::new()
+            .with_input_file(input_file);
+        conn.borrow_mut().add_response(
+            "POST",
+            "https://retdec.com/service/api/decompiler/decompilations",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_request_url("https://retdec.com/service/api/decompiler/decompilations")
+                    .with_status_code(500)
+                    .with_status_message("Internal Server Error")
+                    .build()
+            )
+        );
+
+        let result = decompiler.start_decompilation(args);
+
+        let err = result.err().expect("expected start_decompilation() to fail");
+        assert_eq!(err.description(), "failed to start a decompilation");
+    }
+
+    #[test]
+    fn decompiler_attach_to_decompilation_returns_decompilation_with_given_id_without_accessing_api() {
+        let (conn, decompiler) = create_decompiler();
+
+        let decompilation = decompiler.attach_to_decompilation("ID");
+
+        assert_eq!(decompilation.id(), "ID");
+        assert!(conn.borrow().no_requests_sent());
+    }
+
+    #[test]
+    fn decompiler_attach_to_decompilation_returns_decompilation_that_checks_status_of_given_id() {
+        let (conn, decompiler) = create_decompiler();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/status",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false
+                    }"#)
+                    .build()
+            )
+        );
+        let mut decompilation = decompiler.attach_to_decompilation("ID");
+
+        let finished = decompilation.has_finished()
+            .expect("has_finished() should have succeeded");
+
+        assert!(finished);
+        assert!(decompilation.succeeded());
+    }
 }
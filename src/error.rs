@@ -2,7 +2,77 @@
 
 use std::io::Write;
 
-error_chain!{}
+use json::JsonValue;
+
+error_chain!{
+    errors {
+        /// The API could not be reached (e.g. the name of the host could not
+        /// be resolved, the connection was refused, or the TLS handshake
+        /// failed).
+        ConnectionFailed(msg: String) {
+            description("connection to the API failed")
+            display("{}", msg)
+        }
+
+        /// The API rejected the API key.
+        AuthenticationFailed {
+            description("authentication failed")
+            display("authentication failed")
+        }
+
+        /// The API responded with an HTTP status code signaling an error
+        /// (e.g. 404 or 500).
+        RequestFailed(status_code: u16, msg: String) {
+            description(msg.as_str())
+            display("{}", msg)
+        }
+
+        /// The API returned a response that could not be understood (e.g.
+        /// malformed JSON).
+        InvalidResponse(msg: String) {
+            description(msg.as_str())
+            display("{}", msg)
+        }
+
+        /// A decompilation or analysis has failed.
+        JobFailed(msg: String) {
+            description(msg.as_str())
+            display("{}", msg)
+        }
+    }
+}
+
+/// Checks whether the given error or any of its causes is of a kind satisfying
+/// the given predicate.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::error::{Error, ErrorKind, ResultExt, chain_contains};
+///
+/// let err: Result<(), Error> = Err(ErrorKind::AuthenticationFailed.into());
+/// let err = err.chain_err(|| "request failed").unwrap_err();
+///
+/// assert!(chain_contains(&err, |kind| match *kind {
+///     ErrorKind::AuthenticationFailed => true,
+///     _ => false,
+/// }));
+/// ```
+pub fn chain_contains<F>(err: &Error, predicate: F) -> bool
+    where F: Fn(&ErrorKind) -> bool
+{
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if predicate(err.kind()) {
+            return true;
+        }
+        // Causes coming from other crates do not have kinds, so the search
+        // ends at the first of them.
+        current = err.1.next_error.as_ref()
+            .and_then(|cause| cause.downcast_ref::<Error>());
+    }
+    false
+}
 
 /// Prints the given error to the given stream.
 ///
@@ -24,6 +94,22 @@ pub fn print_error(err: &Error, stream: &mut Write) {
     }
 }
 
+/// Returns a JSON representation of the given error.
+///
+/// The representation mirrors the output of `print_error()`: the `message`
+/// key contains the error itself and the `causes` key contains its causes
+/// (from the outermost one).
+pub fn error_to_json(err: &Error) -> JsonValue {
+    let causes = err.iter()
+        .skip(1)
+        .map(|cause| JsonValue::from(cause.to_string()))
+        .collect();
+    object!{
+        "message" => err.to_string(),
+        "causes" => JsonValue::Array(causes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +126,54 @@ mod tests {
         assert_eq!(String::from_utf8_lossy(&stream), "error: invalid key\n");
     }
 
+    #[test]
+    fn error_to_json_returns_message_and_causes() {
+        let err = Error::with_chain(
+            Error::from_kind(ErrorKind::Msg("encoding error".to_string())),
+            ErrorKind::Msg("invalid key".to_string())
+        );
+
+        let json = error_to_json(&err);
+
+        assert_eq!(json["message"], "invalid key");
+        assert_eq!(json["causes"].len(), 1);
+        assert_eq!(json["causes"][0], "encoding error");
+    }
+
+    #[test]
+    fn chain_contains_returns_true_when_error_itself_is_of_given_kind() {
+        let err = Error::from_kind(ErrorKind::AuthenticationFailed);
+
+        assert!(chain_contains(&err, is_auth_failure));
+    }
+
+    #[test]
+    fn chain_contains_returns_true_when_cause_is_of_given_kind() {
+        let err = Error::with_chain(
+            Error::from_kind(ErrorKind::AuthenticationFailed),
+            ErrorKind::Msg("request failed".to_string())
+        );
+
+        assert!(chain_contains(&err, is_auth_failure));
+    }
+
+    #[test]
+    fn chain_contains_returns_false_when_no_error_is_of_given_kind() {
+        let err = Error::with_chain(
+            Error::from_kind(ErrorKind::ConnectionFailed("refused".to_string())),
+            ErrorKind::Msg("request failed".to_string())
+        );
+
+        assert!(!chain_contains(&err, is_auth_failure));
+    }
+
+    fn is_auth_failure(kind: &ErrorKind) -> bool {
+        match *kind {
+            ErrorKind::AuthenticationFailed => true,
+            _ => false,
+        }
+    }
+
     #[test]
     fn print_error_includes_cause_when_present() {
         let err = Error::with_chain(
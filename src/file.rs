@@ -1,11 +1,21 @@
 //! Representation of files.
 
+use std::fmt;
 use std::fs;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process;
 use std::str;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::ATOMIC_USIZE_INIT;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 use unidecode::unidecode;
 
@@ -192,19 +202,7 @@ impl File {
     /// assert_eq!(file.safe_name(), "jalapeno.txt");
     /// ```
     pub fn safe_name(&self) -> String {
-        // There is a limitation in the retdec.com's API concerning file names.
-        // More specifically, file names cannot contain non-ASCII characters.
-        // https://retdec.com/api/docs/essential_information.html#files
-        let safe_name = unidecode(&self.name);
-
-        // Moreover, we replace special characters with an underscore.
-        fn is_special(c: char) -> bool {
-            // unidecode() produces an ASCII string, so the following cast to
-            // u8 is safe.
-            let c = c as u8;
-            c < 32 || c > 127
-        }
-        safe_name.chars().map(|c| if is_special(c) { '_' } else { c }).collect()
+        safe_file_name(&self.name)
     }
 
     /// Stores a copy of the file into the given directory.
@@ -228,7 +226,42 @@ impl File {
     pub fn save_into<P>(&self, dir: P) -> Result<PathBuf>
         where P: AsRef<Path>
     {
-        self.save_into_under_name(dir, self.name())
+        self.save_into_with_options(dir, &SaveOptions::new())
+    }
+
+    /// Stores a copy of the file into the given directory with the given
+    /// options.
+    ///
+    /// As the name of the file may come from an untrusted source (e.g. from
+    /// the `Content-Disposition` header of an API response), it is sanitized
+    /// before use: path separators and characters that are not allowed in
+    /// file names are replaced with underscores and reserved device names
+    /// (e.g. `CON` or `NUL` on Windows) are prefixed with an underscore. An
+    /// error is returned when no usable name remains (e.g. for `..`).
+    ///
+    /// Returns a path to the saved file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use retdec::error::Result;
+    /// # fn test() -> Result<()> {
+    /// use std::path::Path;
+    /// use retdec::file::File;
+    /// use retdec::file::SaveOptions;
+    ///
+    /// let file = File::from_content_with_name(b"content", "../../file.txt");
+    ///
+    /// let options = SaveOptions::new().with_overwrite(false);
+    /// let saved_file_path = file.save_into_with_options("another_dir", &options)?;
+    /// assert_eq!(saved_file_path, Path::new("another_dir/.._.._file.txt"));
+    /// # Ok(()) } fn main() { test().unwrap() }
+    /// ```
+    pub fn save_into_with_options<P>(&self, dir: P, options: &SaveOptions) -> Result<PathBuf>
+        where P: AsRef<Path>
+    {
+        let name = sanitized_file_name(self.name())?;
+        self.save_into_under_name_with_options(dir, &name, options)
     }
 
     /// Stores a copy of the file into the given directory under a custom name.
@@ -252,8 +285,26 @@ impl File {
     pub fn save_into_under_name<P>(&self, dir: P, name: &str) -> Result<PathBuf>
         where P: AsRef<Path>
     {
+        self.save_into_under_name_with_options(dir, name, &SaveOptions::new())
+    }
+
+    /// Stores a copy of the file into the given directory under a custom name
+    /// with the given options.
+    ///
+    /// Unlike `save_into_with_options()`, the name is not sanitized. Instead,
+    /// an error is returned when it is not a plain file name (e.g. when it
+    /// contains a path separator or is `..`).
+    ///
+    /// Returns a path to the saved file.
+    pub fn save_into_under_name_with_options<P>(&self,
+                                                dir: P,
+                                                name: &str,
+                                                options: &SaveOptions) -> Result<PathBuf>
+        where P: AsRef<Path>
+    {
+        ensure_plain_file_name(name)?;
         let file_path = dir.as_ref().join(name);
-        Self::write_file(self.content(), &file_path)?;
+        Self::write_file(self.content(), &file_path, options)?;
         Ok(file_path)
     }
 
@@ -277,8 +328,32 @@ impl File {
     pub fn save_as<P>(&self, path: P) -> Result<()>
         where P: AsRef<Path>
     {
-        Self::write_file(self.content(), path.as_ref())?;
-        Ok(())
+        self.save_as_with_options(path, &SaveOptions::new())
+    }
+
+    /// Stores a copy of the file into the given path with the given options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use retdec::error::Result;
+    /// # fn test() -> Result<()> {
+    /// use retdec::file::File;
+    /// use retdec::file::SaveOptions;
+    ///
+    /// let file = File::from_path("tests/file.exe")?;
+    ///
+    /// // Fails when another_dir/test.exe already exists.
+    /// file.save_as_with_options(
+    ///     "another_dir/test.exe",
+    ///     &SaveOptions::new().with_overwrite(false)
+    /// )?;
+    /// # Ok(()) } fn main() { test().unwrap() }
+    /// ```
+    pub fn save_as_with_options<P>(&self, path: P, options: &SaveOptions) -> Result<()>
+        where P: AsRef<Path>
+    {
+        Self::write_file(self.content(), path.as_ref(), options)
     }
 
     fn read_file(path: &Path) -> Result<Vec<u8>> {
@@ -290,12 +365,22 @@ impl File {
         Ok(content)
     }
 
-    fn write_file(content: &[u8], path: &Path) -> Result<()> {
-        let mut file = fs::File::create(path)
-            .chain_err(|| format!("failed to open {:?} for writing", path))?;
-        file.write(content)
-            .chain_err(|| format!("failed to write content into {:?}", path))?;
-        Ok(())
+    fn write_file(content: &[u8], path: &Path, options: &SaveOptions) -> Result<()> {
+        if !options.overwrite() && path.exists() {
+            bail!("{:?} already exists", path);
+        }
+
+        // To prevent leaving a partially written file behind (e.g. when the
+        // disk becomes full), the content is first written into a temporary
+        // file in the same directory, which is then renamed to the target
+        // path.
+        let tmp_path = temporary_path_for(path)?;
+        let result = write_all_and_sync(content, &tmp_path)
+            .and_then(|_| move_into_place(&tmp_path, path, options));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
     }
 
     fn get_file_name(path: &Path) -> Result<String> {
@@ -306,6 +391,318 @@ impl File {
     }
 }
 
+/// Options for saving files.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::file::SaveOptions;
+///
+/// let options = SaveOptions::new()
+///     .with_overwrite(false);
+///
+/// assert!(!options.overwrite());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SaveOptions {
+    overwrite: bool,
+}
+
+impl SaveOptions {
+    /// Creates default options, which overwrite existing files.
+    pub fn new() -> Self {
+        SaveOptions { overwrite: true }
+    }
+
+    /// Should existing files be overwritten?
+    ///
+    /// When `false`, saving into a path that already exists fails.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.set_overwrite(overwrite);
+        self
+    }
+
+    /// Sets whether existing files should be overwritten.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        self.overwrite = overwrite;
+    }
+
+    /// Should existing files be overwritten?
+    pub fn overwrite(&self) -> bool {
+        self.overwrite
+    }
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reader from which a [`StreamedFile`](struct.StreamedFile.html) can be
+/// read.
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// A file whose content is read only when it is being uploaded.
+///
+/// Unlike [`File`](struct.File.html), the content is never loaded into memory
+/// as a whole, which makes it suitable for large inputs (e.g. firmware
+/// images). The length of the content has to be known upfront because the
+/// retdec.com's API requires requests to include the `Content-Length` header.
+///
+/// Clones share the underlying reader.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use retdec::error::Result;
+/// # fn test() -> Result<()> {
+/// use retdec::decompilation::DecompilationArguments;
+/// use retdec::file::StreamedFile;
+///
+/// let args = DecompilationArguments::new()
+///     .with_streamed_input_file(StreamedFile::from_path("firmware.bin")?);
+/// # Ok(()) } fn main() { test().unwrap() }
+/// ```
+#[derive(Clone)]
+pub struct StreamedFile {
+    name: String,
+    len: u64,
+    reader: Arc<Mutex<Box<ReadSeek>>>,
+}
+
+impl StreamedFile {
+    /// Creates a file streamed from the given path.
+    ///
+    /// The name and length are detected automatically from the path.
+    pub fn from_path<P>(path: P) -> Result<StreamedFile>
+        where P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        let file = fs::File::open(path)
+            .chain_err(|| format!("failed to open {:?}", path))?;
+        let len = file.metadata()
+            .chain_err(|| format!("failed to get the size of {:?}", path))?
+            .len();
+        Ok(Self::from_reader(file, len, File::get_file_name(path)?))
+    }
+
+    /// Creates a file streamed from the given reader.
+    ///
+    /// `len` is the number of bytes that will be read from the reader, starting
+    /// at its beginning.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use retdec::file::StreamedFile;
+    ///
+    /// let file = StreamedFile::from_reader(Cursor::new(b"content"), 7, "file.exe");
+    ///
+    /// assert_eq!(file.name(), "file.exe");
+    /// assert_eq!(file.len(), 7);
+    /// ```
+    pub fn from_reader<R, N>(reader: R, len: u64, name: N) -> StreamedFile
+        where R: Read + Seek + Send + 'static,
+              N: Into<String>
+    {
+        StreamedFile {
+            name: name.into(),
+            len: len,
+            reader: Arc::new(Mutex::new(Box::new(reader))),
+        }
+    }
+
+    /// Returns the name of the file.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a modified version of the file's name that can be passed to the
+    /// retdec.com's API.
+    ///
+    /// See [`File::safe_name()`](struct.File.html#method.safe_name) for
+    /// details.
+    pub fn safe_name(&self) -> String {
+        safe_file_name(&self.name)
+    }
+
+    /// Returns the length of the content (in bytes).
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Is the file empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes the content of the file into the given writer.
+    ///
+    /// The reader is rewound before the content is read, so the content can be
+    /// written repeatedly. Returns the number of written bytes, which is always
+    /// `len()`. When the reader ends prematurely, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use retdec::error::Result;
+    /// # fn test() -> Result<()> {
+    /// use std::io::Cursor;
+    /// use retdec::file::StreamedFile;
+    ///
+    /// let file = StreamedFile::from_reader(Cursor::new(b"content"), 7, "file.exe");
+    /// let mut content = Vec::new();
+    /// file.copy_to(&mut content)?;
+    ///
+    /// assert_eq!(content, b"content");
+    /// # Ok(()) } fn main() { test().unwrap() }
+    /// ```
+    pub fn copy_to(&self, writer: &mut Write) -> Result<u64> {
+        let mut reader = self.reader.lock()
+            .map_err(|_| format!("the reader of {} is unusable", self.name))?;
+        let reader: &mut ReadSeek = &mut **reader;
+        reader.seek(SeekFrom::Start(0))
+            .chain_err(|| format!("failed to rewind {}", self.name))?;
+        let copied = io::copy(&mut reader.take(self.len), writer)
+            .chain_err(|| format!("failed to read {}", self.name))?;
+        if copied != self.len {
+            bail!(
+                "{} ended after {} bytes although its length is {} bytes",
+                self.name, copied, self.len
+            );
+        }
+        Ok(copied)
+    }
+}
+
+impl fmt::Debug for StreamedFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamedFile")
+            .field("name", &self.name)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+fn safe_file_name(name: &str) -> String {
+    // There is a limitation in the retdec.com's API concerning file names.
+    // More specifically, file names cannot contain non-ASCII characters.
+    // https://retdec.com/api/docs/essential_information.html#files
+    let safe_name = unidecode(name);
+
+    // Moreover, we replace special characters with an underscore.
+    fn is_special(c: char) -> bool {
+        // unidecode() produces an ASCII string, so the following cast to
+        // u8 is safe.
+        let c = c as u8;
+        c < 32 || c > 127
+    }
+    safe_name.chars().map(|c| if is_special(c) { '_' } else { c }).collect()
+}
+
+/// Names of devices that cannot be used as file names on Windows (regardless
+/// of the extension).
+const RESERVED_FILE_NAMES: &'static [&'static str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Is the given character disallowed in file names (on any platform)?
+fn is_disallowed_in_file_name(c: char) -> bool {
+    c.is_control() || "/\\:*?\"<>|".contains(c)
+}
+
+fn is_reserved_file_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or("").trim_right();
+    RESERVED_FILE_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Turns the given (possibly untrusted) name into a name that can be safely
+/// used as a name of a file in a directory.
+fn sanitized_file_name(name: &str) -> Result<String> {
+    let sanitized: String = name.chars()
+        .map(|c| if is_disallowed_in_file_name(c) { '_' } else { c })
+        .collect();
+    // Windows silently strips trailing dots and spaces.
+    let sanitized = sanitized.trim().trim_right_matches('.').to_string();
+    if sanitized.is_empty() {
+        bail!("{:?} cannot be used as a file name", name);
+    }
+    if is_reserved_file_name(&sanitized) {
+        return Ok(format!("_{}", sanitized));
+    }
+    Ok(sanitized)
+}
+
+/// Ensures that the given name is a plain file name, i.e. that it does not
+/// refer to a file outside of the directory it is joined with.
+fn ensure_plain_file_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." ||
+       name.chars().any(is_disallowed_in_file_name) ||
+       is_reserved_file_name(name) {
+        bail!("{:?} is not a valid file name", name);
+    }
+    Ok(())
+}
+
+fn temporary_path_for(path: &Path) -> Result<PathBuf> {
+    static COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+    let file_name = path.file_name()
+        .ok_or_else(|| format!("no file name in {:?}", path))?;
+    let tmp_name = format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    );
+    Ok(path.with_file_name(tmp_name))
+}
+
+fn write_all_and_sync(content: &[u8], path: &Path) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .chain_err(|| format!("failed to open {:?} for writing", path))?;
+    file.write_all(content)
+        .chain_err(|| format!("failed to write content into {:?}", path))?;
+    file.sync_all()
+        .chain_err(|| format!("failed to write content into {:?}", path))?;
+    Ok(())
+}
+
+fn move_into_place(tmp_path: &Path, path: &Path, options: &SaveOptions) -> Result<()> {
+    if options.overwrite() {
+        return fs::rename(tmp_path, path)
+            .chain_err(|| format!("failed to move {:?} to {:?}", tmp_path, path));
+    }
+
+    // Unlike a rename, creating a hard link fails when the target exists,
+    // so no file that has been created in the meantime gets overwritten.
+    match fs::hard_link(tmp_path, path) {
+        Ok(()) => {
+            let _ = fs::remove_file(tmp_path);
+            Ok(())
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            bail!("{:?} already exists", path)
+        }
+        Err(_) => {
+            // Hard links are not supported by the filesystem.
+            if path.exists() {
+                bail!("{:?} already exists", path);
+            }
+            fs::rename(tmp_path, path)
+                .chain_err(|| format!("failed to move {:?} to {:?}", tmp_path, path))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,6 +739,63 @@ mod tests {
         assert_eq!(file.safe_name(), "a b");
     }
 
+    #[test]
+    fn sanitized_file_name_keeps_ordinary_names() {
+        assert_eq!(sanitized_file_name("file.c").unwrap(), "file.c");
+        assert_eq!(sanitized_file_name("..file.c").unwrap(), "..file.c");
+    }
+
+    #[test]
+    fn sanitized_file_name_replaces_path_separators_and_disallowed_characters() {
+        assert_eq!(sanitized_file_name("../../etc/passwd").unwrap(), ".._.._etc_passwd");
+        assert_eq!(sanitized_file_name("C:\\file?.c").unwrap(), "C__file_.c");
+        assert_eq!(sanitized_file_name("a\nb.c").unwrap(), "a_b.c");
+    }
+
+    #[test]
+    fn sanitized_file_name_prefixes_reserved_device_names() {
+        assert_eq!(sanitized_file_name("con").unwrap(), "_con");
+        assert_eq!(sanitized_file_name("NUL.txt").unwrap(), "_NUL.txt");
+        assert_eq!(sanitized_file_name("console.txt").unwrap(), "console.txt");
+    }
+
+    #[test]
+    fn sanitized_file_name_strips_trailing_dots_and_spaces() {
+        assert_eq!(sanitized_file_name("file.c. . ").unwrap(), "file.c");
+    }
+
+    #[test]
+    fn sanitized_file_name_returns_error_when_no_usable_name_remains() {
+        assert!(sanitized_file_name("").is_err());
+        assert!(sanitized_file_name(".").is_err());
+        assert!(sanitized_file_name("..").is_err());
+        assert!(sanitized_file_name(" . ").is_err());
+    }
+
+    #[test]
+    fn ensure_plain_file_name_accepts_plain_names() {
+        assert!(ensure_plain_file_name("file.c").is_ok());
+    }
+
+    #[test]
+    fn ensure_plain_file_name_rejects_names_that_are_not_plain() {
+        assert!(ensure_plain_file_name("").is_err());
+        assert!(ensure_plain_file_name("..").is_err());
+        assert!(ensure_plain_file_name("dir/file.c").is_err());
+        assert!(ensure_plain_file_name("dir\\file.c").is_err());
+        assert!(ensure_plain_file_name("AUX.c").is_err());
+    }
+
+    #[test]
+    fn save_options_overwrite_existing_files_by_default() {
+        assert!(SaveOptions::new().overwrite());
+    }
+
+    #[test]
+    fn save_options_with_overwrite_sets_overwrite() {
+        assert!(!SaveOptions::new().with_overwrite(false).overwrite());
+    }
+
     #[test]
     fn file_content_as_text_returns_str_when_content_is_text() {
         let file = File::from_content_with_name(b"content", "file.txt");
@@ -359,6 +813,53 @@ mod tests {
         assert!(file.content_as_text().is_err());
     }
 
+    #[test]
+    fn streamed_file_from_reader_returns_correct_file() {
+        let file = StreamedFile::from_reader(io::Cursor::new(b"content"), 7, "file.exe");
+
+        assert_eq!(file.name(), "file.exe");
+        assert_eq!(file.len(), 7);
+        assert!(!file.is_empty());
+    }
+
+    #[test]
+    fn streamed_file_safe_name_returns_name_with_only_ascii_characters() {
+        let file = StreamedFile::from_reader(io::Cursor::new(b""), 0, "jalapeño.txt");
+
+        assert_eq!(file.safe_name(), "jalapeno.txt");
+    }
+
+    #[test]
+    fn streamed_file_copy_to_can_be_called_repeatedly() {
+        let file = StreamedFile::from_reader(io::Cursor::new(b"content"), 7, "file.exe");
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+
+        file.copy_to(&mut first).unwrap();
+        file.copy_to(&mut second).unwrap();
+
+        assert_eq!(first, b"content");
+        assert_eq!(second, b"content");
+    }
+
+    #[test]
+    fn streamed_file_copy_to_copies_only_len_bytes() {
+        let file = StreamedFile::from_reader(io::Cursor::new(b"content"), 4, "file.exe");
+        let mut content = Vec::new();
+
+        let copied = file.copy_to(&mut content).unwrap();
+
+        assert_eq!(copied, 4);
+        assert_eq!(content, b"cont");
+    }
+
+    #[test]
+    fn streamed_file_copy_to_returns_error_when_reader_is_shorter_than_len() {
+        let file = StreamedFile::from_reader(io::Cursor::new(b"content"), 100, "file.exe");
+
+        assert!(file.copy_to(&mut Vec::new()).is_err());
+    }
+
     #[test]
     fn file_content_len_returns_correct_value() {
         let file = File::from_content_with_name(b"123456", "file.txt");
@@ -3,10 +3,13 @@
 
 use analysis::Analysis;
 use analysis::AnalysisArguments;
+use cache::CachingAPIConnectionFactory;
+use cache::ResultCache;
 use connection::APIArguments;
 use connection::APIConnectionFactory;
 use connection::HyperAPIConnectionFactory;
 use connection::ResponseVerifyingAPIConnectionFactory;
+use error::ErrorKind;
 use error::Result;
 use error::ResultExt;
 use settings::Settings;
@@ -41,11 +44,31 @@ pub struct Fileinfo {
 impl Fileinfo {
     /// Creates a new instance of the file-analyzing service.
     pub fn new(settings: Settings) -> Self {
+        Self::with_transport(Box::new(HyperAPIConnectionFactory::new(settings)))
+    }
+
+    /// Creates a new instance of the file-analyzing service that uses the given
+    /// transport.
+    ///
+    /// The transport is used to send all requests to the API. By default,
+    /// `HyperAPIConnectionFactory` is used.
+    pub fn with_transport(conn_factory: Box<APIConnectionFactory>) -> Self {
         Fileinfo {
             conn_factory: Box::new(
-                ResponseVerifyingAPIConnectionFactory::new(
-                    Box::new(HyperAPIConnectionFactory::new(settings))
-                )
+                ResponseVerifyingAPIConnectionFactory::new(conn_factory)
+            ),
+        }
+    }
+
+    /// Serves results of already finished analyses from the given cache.
+    ///
+    /// When an analysis with the same input file and arguments has already
+    /// finished successfully, `start_analysis()` returns it without
+    /// accessing the network, and its outputs are read from the cache.
+    pub fn with_cache(self, cache: ResultCache) -> Self {
+        Fileinfo {
+            conn_factory: Box::new(
+                CachingAPIConnectionFactory::new(self.conn_factory, cache)
             ),
         }
     }
@@ -58,29 +81,38 @@ impl Fileinfo {
         let response = conn.send_post_request(&url, api_args)
             .chain_err(|| "failed to start an analysis")?;
         let id = response.json_value_as_string("id")
-            .ok_or_else(|| format!("{} returned invalid JSON response", url))?;
+            .ok_or_else(|| ErrorKind::InvalidResponse(
+                format!("{} returned invalid JSON response", url)
+            ))?;
         Ok(Analysis::new(id, conn))
     }
 
+    /// Returns an analysis that has already been started (e.g. by another
+    /// process) and has the given ID.
+    ///
+    /// The API is not accessed until the status or outputs of the returned
+    /// analysis are requested. When there is no such analysis, these requests fail.
+    pub fn attach_to_analysis<I: Into<String>>(&self, id: I) -> Analysis {
+        Analysis::new(id, self.conn_factory.new_connection())
+    }
+
     fn create_api_args(&self, mut args: AnalysisArguments) -> Result<APIArguments> {
         let mut api_args = APIArguments::new();
         api_args.add_opt_string_arg("output_format", args.take_output_format());
         api_args.add_opt_bool_arg("verbose", args.verbose());
-        match args.take_input_file() {
-            Some(input_file) => {
+        match (args.take_input_file(), args.take_streamed_input_file()) {
+            (Some(input_file), _) => {
                 api_args.add_file("input", input_file);
             }
-            None => {
+            (None, Some(input_file)) => {
+                api_args.add_streamed_file("input", input_file);
+            }
+            (None, None) => {
                 bail!("no input file given");
             }
         }
         Ok(api_args)
     }
-
-    #[cfg(test)]
-    fn with_conn_factory(conn_factory: Box<APIConnectionFactory>) -> Self {
-        Fileinfo { conn_factory: conn_factory }
-    }
 }
 
 #[cfg(test)]
@@ -91,10 +123,10 @@ mod tests {
     use std::rc::Rc;
 
     use analysis::AnalysisArguments;
-    use connection::tests::APIArgumentsBuilder;
-    use connection::tests::APIConnectionFactoryMock;
-    use connection::tests::APIConnectionMock;
-    use connection::tests::APIResponseBuilder;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
     use file::File;
 
     fn create_fileinfo() -> (Rc<RefCell<APIConnectionMock>>, Fileinfo) {
@@ -105,7 +137,7 @@ mod tests {
             .with_api_url("https://retdec.com/service/api");
         let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings.clone())));
         let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
-        (conn, Fileinfo::with_conn_factory(conn_factory))
+        (conn, Fileinfo::with_transport(conn_factory))
     }
 
     #[test]
@@ -192,4 +224,40 @@ mod tests {
             "https://retdec.com/service/api/fileinfo/analyses returned invalid JSON response"
         );
     }
+
+    #[test]
+    fn fileinfo_attach_to_analysis_returns_analysis_with_given_id_without_accessing_api() {
+        let (conn, fileinfo) = create_fileinfo();
+
+        let analysis = fileinfo.attach_to_analysis("ID");
+
+        assert_eq!(analysis.id(), "ID");
+        assert!(conn.borrow().no_requests_sent());
+    }
+
+    #[test]
+    fn fileinfo_attach_to_analysis_returns_analysis_that_checks_status_of_given_id() {
+        let (conn, fileinfo) = create_fileinfo();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/fileinfo/analyses/ID/status",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false
+                    }"#)
+                    .build()
+            )
+        );
+        let mut analysis = fileinfo.attach_to_analysis("ID");
+
+        let finished = analysis.has_finished()
+            .expect("has_finished() should have succeeded");
+
+        assert!(finished);
+        assert!(analysis.succeeded());
+    }
 }
@@ -0,0 +1,196 @@
+//! Logging of requests sent to the API and responses to them.
+//!
+//! Every request and response is logged via the `log` crate under the
+//! `retdec::http` target (method, URL, names of arguments, names and sizes of
+//! files, status code, size of the body, and duration). Values of arguments are
+//! not logged. When verbose HTTP logging is enabled (see
+//! `Settings::with_verbose_http()`), headers and bodies are also dumped to the
+//! standard error. Authorization headers are always redacted.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use connection::APIArguments;
+use connection::APIResponse;
+use connection::Header;
+use connection::Headers;
+use error::Error;
+
+const TARGET: &'static str = "retdec::http";
+
+const REDACTED: &'static str = "<redacted>";
+
+/// Logger of a single request and the response to it.
+pub struct RequestLogger {
+    method: &'static str,
+    url: String,
+    started: Instant,
+    verbose: bool,
+}
+
+impl RequestLogger {
+    /// Logs a request that is about to be sent.
+    pub fn start(method: &'static str,
+                 url: &str,
+                 args: &APIArguments,
+                 verbose: bool) -> Self {
+        debug!(
+            target: TARGET,
+            "request: method={} url={} args=[{}] files=[{}]",
+            method,
+            url,
+            arg_names(args).join(","),
+            file_summaries(args).join(",")
+        );
+        RequestLogger {
+            method: method,
+            url: url.to_string(),
+            started: Instant::now(),
+            verbose: verbose,
+        }
+    }
+
+    /// Dumps the headers of the request to the standard error (only when
+    /// verbose).
+    pub fn dump_request_headers(&self, headers: &Headers) {
+        if !self.verbose {
+            return;
+        }
+        eprintln!("> {} {}", self.method, self.url);
+        for header in headers.iter() {
+            eprintln!("> {}", format_header(header));
+        }
+        eprintln!(">");
+    }
+
+    /// Dumps the body of the request to the standard error (only when
+    /// verbose).
+    pub fn dump_request_body(&self, body: &str) {
+        if self.verbose {
+            eprintln!("{}", body);
+        }
+    }
+
+    /// Logs the given response.
+    ///
+    /// `streamed` is the number of bytes of the body that were not stored in
+    /// the response (e.g. because they were written into a file).
+    pub fn finish(&self, response: &APIResponse, streamed: u64) {
+        let size = response.body().len() as u64 + streamed;
+        debug!(
+            target: TARGET,
+            "response: method={} url={} status={} size={} duration_ms={}",
+            self.method,
+            self.url,
+            response.status_code(),
+            size,
+            duration_as_millis(self.started.elapsed())
+        );
+        if !self.verbose {
+            return;
+        }
+        eprintln!("< {} {}", response.status_code(), response.status_message());
+        for header in response.headers().iter() {
+            eprintln!("< {}", format_header(header));
+        }
+        eprintln!("<");
+        if !response.body().is_empty() {
+            eprintln!("{}", String::from_utf8_lossy(response.body()));
+        }
+        if streamed > 0 {
+            eprintln!("<{} bytes written into the output>", streamed);
+        }
+    }
+
+    /// Logs a request that failed without a response.
+    pub fn fail(&self, err: &Error) {
+        debug!(
+            target: TARGET,
+            "failure: method={} url={} duration_ms={} error={}",
+            self.method,
+            self.url,
+            duration_as_millis(self.started.elapsed()),
+            err
+        );
+        if self.verbose {
+            eprintln!("! {}", err);
+        }
+    }
+}
+
+/// Returns sorted names of arguments (without their values, which may be
+/// sensitive).
+fn arg_names(args: &APIArguments) -> Vec<String> {
+    let mut names: Vec<String> = args.args().map(|(name, _)| name.clone()).collect();
+    names.sort();
+    names
+}
+
+/// Returns sorted descriptions of files in the form `NAME=FILE_NAME
+/// (SIZE bytes)`.
+fn file_summaries(args: &APIArguments) -> Vec<String> {
+    let mut summaries: Vec<String> = args.files()
+        .map(|(name, file)| {
+            format!("{}={} ({} bytes)", name, file.name(), file.content().len())
+        })
+        .chain(args.streamed_files().map(|(name, file)| {
+            format!("{}={} ({} bytes)", name, file.name(), file.len())
+        }))
+        .collect();
+    summaries.sort();
+    summaries
+}
+
+/// Formats the given header, redacting the value of authorization headers.
+fn format_header(header: &Header) -> String {
+    let name = header.name.to_lowercase();
+    if name == "authorization" || name == "proxy-authorization" {
+        format!("{}: {}", header.name, REDACTED)
+    } else {
+        format!("{}: {}", header.name, header.value)
+    }
+}
+
+fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos() / 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use file::File;
+
+    #[test]
+    fn arg_names_returns_sorted_names_without_values() {
+        let mut args = APIArguments::new();
+        args.add_string_arg("target_language", "py");
+        args.add_string_arg("mode", "bin");
+
+        assert_eq!(arg_names(&args), vec!["mode", "target_language"]);
+    }
+
+    #[test]
+    fn file_summaries_returns_names_and_sizes_of_files() {
+        let mut args = APIArguments::new();
+        args.add_file("input", File::from_content_with_name(b"content", "file.exe"));
+
+        assert_eq!(file_summaries(&args), vec!["input=file.exe (7 bytes)"]);
+    }
+
+    #[test]
+    fn format_header_redacts_authorization_headers() {
+        let header = Header::new("Authorization", "Basic S0VZOg==");
+        assert_eq!(format_header(&header), "Authorization: <redacted>");
+
+        let header = Header::new("proxy-authorization", "Basic dXNlcjpwYXNz");
+        assert_eq!(format_header(&header), "proxy-authorization: <redacted>");
+    }
+
+    #[test]
+    fn format_header_keeps_value_of_other_headers() {
+        let header = Header::new("Content-Type", "application/json");
+
+        assert_eq!(format_header(&header), "Content-Type: application/json");
+    }
+}
@@ -81,13 +81,19 @@
 #![warn(unused_import_braces)]
 #![warn(unused_qualifications)]
 
+extern crate atty;
 extern crate clap;
 extern crate hyper;
 extern crate hyper_native_tls;
+#[macro_use]
 extern crate json;
-extern crate regex;
-extern crate multipart;
+#[macro_use]
+extern crate log;
+extern crate native_tls;
+extern crate sha2;
 extern crate unidecode;
+#[cfg(test)]
+extern crate tempdir;
 #[macro_use]
 extern crate error_chain;
 
@@ -95,13 +101,23 @@ extern crate error_chain;
 pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
 pub mod analysis;
+pub mod api_key;
+pub mod batch;
+pub mod cache;
+pub mod cassette;
+pub mod config;
+pub mod connection;
 pub mod decompilation;
 pub mod decompiler;
+pub mod doctor;
 pub mod error;
 pub mod file;
 pub mod fileinfo;
+pub mod secret;
 pub mod settings;
 pub mod test;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod tools;
 
 // Reexports.
@@ -109,14 +125,21 @@ pub use analysis::Analysis;
 pub use analysis::AnalysisArguments;
 pub use decompilation::Decompilation;
 pub use decompilation::DecompilationArguments;
+pub use decompilation::DecompilationPhase;
+pub use decompilation::OutputKind;
 pub use decompiler::Decompiler;
 pub use error::Error;
 pub use error::Result;
 pub use file::File;
+pub use file::SaveOptions;
+pub use file::StreamedFile;
 pub use fileinfo::Fileinfo;
 pub use settings::Settings;
 pub use test::Test;
 
-mod connection;
+mod content_disposition;
+mod http_log;
+mod proxy;
 mod resource;
+mod tls;
 mod utils;
@@ -6,6 +6,7 @@ use std::time::Duration;
 use json::JsonValue;
 
 use connection::APIConnection;
+use error::ErrorKind;
 use error::Result;
 
 /// Access to a resource.
@@ -18,6 +19,7 @@ pub struct Resource {
     pub succeeded: bool,
     pub failed: bool,
     pub error: Option<String>,
+    pub status: JsonValue,
 }
 
 impl Resource {
@@ -49,20 +51,23 @@ impl Resource {
             succeeded: false,
             failed: false,
             error: None,
+            status: JsonValue::Null,
         }
     }
 
     /// Updates the status of the resource.
     pub fn update_status(&mut self) -> Result<JsonValue> {
         let err = format!("{} returned invalid JSON response", self.status_url);
+        let invalid_response = || ErrorKind::InvalidResponse(err.clone());
         let response = self.conn.send_get_request_without_args(&self.status_url)?;
         let status = response.body_as_json()?;
-        self.finished = status["finished"].as_bool().ok_or_else(|| err.clone())?;
-        self.succeeded = status["succeeded"].as_bool().ok_or_else(|| err.clone())?;
-        self.failed = status["failed"].as_bool().ok_or_else(|| err.clone())?;
+        self.finished = status["finished"].as_bool().ok_or_else(&invalid_response)?;
+        self.succeeded = status["succeeded"].as_bool().ok_or_else(&invalid_response)?;
+        self.failed = status["failed"].as_bool().ok_or_else(&invalid_response)?;
         if let Some(error) = status["error"].as_str() {
             self.error = Some(error.to_string());
         }
+        self.status = status.clone();
         Ok(status)
     }
 
@@ -104,6 +109,8 @@ impl Resource {
     pub fn ensure_has_succeeded(&mut self, resource_name: &str) -> Result<()> {
         if self.has_succeeded()? {
             Ok(())
+        } else if self.failed {
+            bail!(ErrorKind::JobFailed(format!("{} has not succeeded", resource_name)))
         } else {
             bail!("{} has not succeeded", resource_name)
         }
@@ -0,0 +1,83 @@
+//! Secrets, such as passwords.
+
+use std::fmt;
+
+const REDACTED: &'static str = "<redacted>";
+
+/// A secret value (e.g. a password or a URL with credentials).
+///
+/// Like [`ApiKey`](../api_key/struct.ApiKey.html), the value never appears in
+/// the output of `Debug` or `Display`, so it does not leak into logs or error
+/// messages by accident. Use `expose()` to get the actual value.
+///
+/// # Examples
+///
+/// ```
+/// use retdec::secret::Secret;
+///
+/// let password = Secret::new("PASSWORD");
+///
+/// assert_eq!(password.expose(), "PASSWORD");
+/// assert_eq!(format!("{}", password), "<redacted>");
+/// assert_eq!(format!("{:?}", password), "Secret(<redacted>)");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret {
+    value: String,
+}
+
+impl Secret {
+    /// Creates a secret.
+    pub fn new<V: Into<String>>(value: V) -> Self {
+        Secret { value: value.into() }
+    }
+
+    /// Returns the actual value.
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret::new(value)
+    }
+}
+
+impl<'a> From<&'a str> for Secret {
+    fn from(value: &'a str) -> Self {
+        Secret::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_is_redacted_in_debug_and_display() {
+        let secret = Secret::new("PASSWORD");
+
+        assert!(!format!("{:?}", secret).contains("PASSWORD"));
+        assert!(!format!("{}", secret).contains("PASSWORD"));
+    }
+
+    #[test]
+    fn secret_expose_returns_actual_value() {
+        let secret = Secret::from("PASSWORD");
+
+        assert_eq!(secret.expose(), "PASSWORD");
+    }
+}
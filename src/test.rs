@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use connection::APIArguments;
 use connection::APIConnectionFactory;
 use connection::HyperAPIConnectionFactory;
+use error::ErrorKind;
 use error::Result;
 use settings::Settings;
 
@@ -17,17 +18,25 @@ pub struct Test {
 impl Test {
     /// Creates a new instance of the testing service.
     pub fn new(settings: Settings) -> Self {
-        Test {
-            conn_factory: Box::new(
-                HyperAPIConnectionFactory::new(settings)
-            ),
-        }
+        Self::with_transport(Box::new(HyperAPIConnectionFactory::new(settings)))
+    }
+
+    /// Creates a new instance of the testing service that uses the given
+    /// transport.
+    ///
+    /// The transport is used to send all requests to the API. By default,
+    /// `HyperAPIConnectionFactory` is used.
+    pub fn with_transport(conn_factory: Box<APIConnectionFactory>) -> Self {
+        Test { conn_factory: conn_factory }
     }
 
     /// Tries to authenticate to the `retdec.com`'s API.
     ///
     /// Returns `Ok(())` when the authentication succeeds. Otherwise, it
-    /// returns an error.
+    /// returns an error. When the API rejects the API key, the error is of
+    /// kind `ErrorKind::AuthenticationFailed`. When the API cannot be
+    /// reached, the error (or one of its causes) is of kind
+    /// `ErrorKind::ConnectionFailed`.
     ///
     /// # Examples
     ///
@@ -50,10 +59,13 @@ impl Test {
         if response.succeeded() {
             return Ok(());
         } else if response.status_code() == 401 {
-            bail!("authentication failed");
+            bail!(ErrorKind::AuthenticationFailed);
         }
 
-        bail!("request to {} failed: {}", url, response.error_reason());
+        bail!(ErrorKind::RequestFailed(
+            response.status_code(),
+            format!("request to {} failed: {}", url, response.error_reason())
+        ));
     }
 
     /// Echoes back the given parameters (key-value pairs).
@@ -88,8 +100,13 @@ impl Test {
             args.add_string_arg(key.as_str(), value.as_str());
         }
         let response = conn.send_get_request(&url, args)?;
-        if response.failed() {
-            bail!("request to {} failed: {}", url, response.error_reason());
+        if response.status_code() == 401 {
+            bail!(ErrorKind::AuthenticationFailed);
+        } else if response.failed() {
+            bail!(ErrorKind::RequestFailed(
+                response.status_code(),
+                format!("request to {} failed: {}", url, response.error_reason())
+            ));
         }
 
         let mut out_params = HashMap::new();
@@ -101,11 +118,6 @@ impl Test {
         }
         Ok(out_params)
     }
-
-    #[cfg(test)]
-    fn with_conn_factory(conn_factory: Box<APIConnectionFactory>) -> Self {
-        Test { conn_factory: conn_factory }
-    }
 }
 
 #[cfg(test)]
@@ -115,10 +127,10 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
-    use connection::tests::APIArgumentsBuilder;
-    use connection::tests::APIConnectionFactoryMock;
-    use connection::tests::APIConnectionMock;
-    use connection::tests::APIResponseBuilder;
+    use testing::APIArgumentsBuilder;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
 
     fn create_test() -> (Rc<RefCell<APIConnectionMock>>, Test) {
         // We need to force an API URL to prevent it from being overridden by
@@ -128,7 +140,7 @@ mod tests {
             .with_api_url("https://retdec.com/service/api");
         let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings.clone())));
         let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
-        (conn, Test::with_conn_factory(conn_factory))
+        (conn, Test::with_transport(conn_factory))
     }
 
     #[test]
@@ -172,6 +184,10 @@ mod tests {
 
         let err = result.err().expect("expected auth() to fail");
         assert_eq!(err.to_string(), "authentication failed");
+        match *err.kind() {
+            ErrorKind::AuthenticationFailed => {}
+            ref kind => panic!("unexpected error kind: {:?}", kind),
+        }
     }
 
     #[test]
@@ -227,4 +243,23 @@ mod tests {
                 .build()
         ));
     }
+
+    #[test]
+    fn echo_returns_authentication_error_when_auth_fails() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test/echo",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(401)
+                    .build()
+            )
+        );
+
+        let result = test.echo(&HashMap::new());
+
+        let err = result.err().expect("expected echo() to fail");
+        assert_eq!(err.to_string(), "authentication failed");
+    }
 }
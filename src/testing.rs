@@ -0,0 +1,528 @@
+//! Test doubles for the connection layer.
+//!
+//! They make it possible to unit-test code that uses the services (decompiler,
+//! fileinfo, test) without an API key and without accessing the network.
+//! Responses are scripted in advance, and sent requests can be checked
+//! afterwards.
+//!
+//! The module is available only when the `testing` feature is enabled:
+//!
+//! ```text
+//! [dev-dependencies]
+//! retdec = { version = "...", features = ["testing"] }
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use retdec::decompilation::DecompilationArguments;
+//! use retdec::decompiler::Decompiler;
+//! use retdec::file::File;
+//! use retdec::settings::Settings;
+//! use retdec::testing::APIArgumentsBuilder;
+//! use retdec::testing::APIConnectionFactoryMock;
+//! use retdec::testing::APIConnectionMock;
+//! use retdec::testing::APIResponseBuilder;
+//!
+//! let settings = Settings::new()
+//!     .with_api_key("test")
+//!     .with_api_url("https://retdec.com/service/api");
+//! let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings)));
+//! conn.borrow_mut().add_response(
+//!     "POST",
+//!     "https://retdec.com/service/api/decompiler/decompilations",
+//!     Ok(
+//!         APIResponseBuilder::new()
+//!             .with_status_code(200)
+//!             .with_body(br#"{"id": "ID"}"#)
+//!             .build()
+//!     )
+//! );
+//! let decompiler = Decompiler::with_transport(
+//!     Box::new(APIConnectionFactoryMock::new(conn.clone()))
+//! );
+//! let input_file = File::from_content_with_name(b"content", "file.exe");
+//!
+//! let decompilation = decompiler.start_decompilation(
+//!     DecompilationArguments::new()
+//!         .with_input_file(input_file.clone())
+//! ).unwrap();
+//!
+//! assert_eq!(decompilation.id(), "ID");
+//! conn.borrow().assert_request_sent(
+//!     "POST",
+//!     "https://retdec.com/service/api/decompiler/decompilations",
+//!     APIArgumentsBuilder::new()
+//!         .with_string_arg("mode", "bin")
+//!         .with_file("input", input_file)
+//!         .build()
+//! );
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use connection::APIArguments;
+use connection::APIConnection;
+use connection::APIConnectionFactory;
+use connection::APIResponse;
+use connection::Header;
+use connection::Headers;
+use error::Result;
+use file::File;
+use file::StreamedFile;
+use settings::Settings;
+
+/// A builder of API arguments.
+#[derive(Debug, Default)]
+pub struct APIArgumentsBuilder {
+    args: APIArguments,
+}
+
+impl APIArgumentsBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Self {
+        APIArgumentsBuilder::default()
+    }
+
+    /// Adds a new string argument (`"name=value"`).
+    pub fn with_string_arg<N, V>(mut self, name: N, value: V) -> Self
+        where N: Into<String>,
+              V: Into<String>
+    {
+        self.args.add_string_arg(name, value);
+        self
+    }
+
+    /// Adds a new bool argument (`"name=value"`).
+    pub fn with_bool_arg<N>(mut self, name: N, value: bool) -> Self
+        where N: Into<String>
+    {
+        self.args.add_bool_arg(name, value);
+        self
+    }
+
+    /// Adds a new file with the given name.
+    pub fn with_file<N>(mut self, name: N, file: File) -> Self
+        where N: Into<String>
+    {
+        self.args.add_file(name, file);
+        self
+    }
+
+    /// Adds a new streamed file with the given name.
+    pub fn with_streamed_file<N>(mut self, name: N, file: StreamedFile) -> Self
+        where N: Into<String>
+    {
+        self.args.add_streamed_file(name, file);
+        self
+    }
+
+    /// Builds the arguments.
+    pub fn build(self) -> APIArguments {
+        self.args
+    }
+}
+
+/// A builder of API responses.
+#[derive(Debug, Default)]
+pub struct APIResponseBuilder {
+    request_url: String,
+    status_code: u16,
+    status_message: String,
+    headers: Headers,
+    body: Vec<u8>,
+}
+
+impl APIResponseBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Self {
+        APIResponseBuilder::default()
+    }
+
+    /// Sets the request URL of the response.
+    pub fn with_request_url<U>(mut self, new_request_url: U) -> Self
+        where U: Into<String>
+    {
+        self.request_url = new_request_url.into();
+        self
+    }
+
+    /// Sets the status code of the response.
+    pub fn with_status_code(mut self, new_status_code: u16) -> Self {
+        self.status_code = new_status_code;
+        self
+    }
+
+    /// Sets the status message of the response.
+    pub fn with_status_message<M>(mut self, new_status_message: M) -> Self
+        where M: Into<String>
+    {
+        self.status_message = new_status_message.into();
+        self
+    }
+
+    /// Adds a header to the response.
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> Self
+        where N: Into<String>,
+              V: Into<String>
+    {
+        self.headers.add(Header::new(name, value));
+        self
+    }
+
+    /// Sets the body of the response.
+    pub fn with_body(mut self, new_body: &[u8]) -> Self {
+        self.body = new_body.to_vec();
+        self
+    }
+
+    /// Sets the response to return the given file.
+    pub fn with_file(self, file: File) -> Self {
+        self.with_header(
+                "Content-Disposition",
+                format!("attachment; filename={}", file.name())
+            )
+            .with_body(file.content())
+    }
+
+    /// Builds the response.
+    pub fn build(self) -> APIResponse {
+        APIResponse::new(
+            self.request_url,
+            self.status_code,
+            self.status_message,
+            self.headers,
+            self.body
+        )
+    }
+}
+
+/// Information about a request sent through `APIConnectionMock`.
+#[derive(Debug, PartialEq)]
+pub struct SentRequest {
+    method: &'static str,
+    url: String,
+    args: APIArguments,
+}
+
+impl SentRequest {
+    /// Returns the HTTP method of the request (`"GET"` or `"POST"`).
+    pub fn method(&self) -> &str {
+        self.method
+    }
+
+    /// Returns the URL to which the request was sent.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the arguments that were sent with the request.
+    pub fn args(&self) -> &APIArguments {
+        &self.args
+    }
+}
+
+/// Information about a scripted response.
+#[derive(Debug)]
+struct ScriptedResponse {
+    method: &'static str,
+    url: String,
+    response: Result<APIResponse>,
+}
+
+/// A connection mock to be used in tests.
+///
+/// Responses have to be scripted via `add_response()` before the
+/// corresponding requests are sent. When there are several responses for the
+/// same request, they are returned in the order in which they were added. A
+/// request without a scripted response causes a panic.
+pub struct APIConnectionMock {
+    settings: Settings,
+    requests: Vec<SentRequest>,
+    responses: Vec<ScriptedResponse>,
+}
+
+impl APIConnectionMock {
+    /// Creates a new mock with the given settings.
+    pub fn new(settings: Settings) -> Self {
+        APIConnectionMock {
+            settings: settings,
+            requests: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Adds a response to be returned for the given request.
+    pub fn add_response<U>(&mut self,
+                           method: &'static str,
+                           url: U,
+                           response: Result<APIResponse>)
+        where U: Into<String>
+    {
+        self.responses.push(
+            ScriptedResponse {
+                method: method,
+                url: url.into(),
+                response: response,
+            }
+        );
+    }
+
+    /// Has a request to the given URL and with the given arguments been sent?
+    pub fn request_sent<U>(&self,
+                           method: &'static str,
+                           url: U,
+                           args: APIArguments) -> bool
+        where U: Into<String>
+    {
+        self.requests.contains(
+            &SentRequest {
+                method: method,
+                url: url.into(),
+                args: args,
+            }
+        )
+    }
+
+    /// Ensures that a request to the given URL and with the given arguments
+    /// has been sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics when no such request has been sent. The panic message lists all
+    /// the requests that have been sent.
+    pub fn assert_request_sent<U>(&self,
+                                  method: &'static str,
+                                  url: U,
+                                  args: APIArguments)
+        where U: Into<String>
+    {
+        let url = url.into();
+        if !self.request_sent(method, url.as_str(), args) {
+            panic!(
+                "no {} request to {} with the expected arguments has been sent; sent requests: {:#?}",
+                method,
+                url,
+                self.requests
+            );
+        }
+    }
+
+    /// Returns all sent requests in the order in which they were sent.
+    pub fn sent_requests(&self) -> &[SentRequest] {
+        &self.requests
+    }
+
+    /// Returns the number of scripted responses that have not been used yet.
+    pub fn unused_responses_count(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Removes all sent requests and set responses.
+    pub fn reset(&mut self) {
+        self.requests.clear();
+        self.responses.clear();
+    }
+
+    /// Check if no requests were sent.
+    pub fn no_requests_sent(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    fn add_request<U>(&mut self,
+                      method: &'static str,
+                      url: U,
+                      args: APIArguments)
+        where U: Into<String>
+    {
+        self.requests.push(
+            SentRequest {
+                method: method,
+                url: url.into(),
+                args: args,
+            }
+        );
+    }
+
+    fn find_response(&mut self,
+                     method: &'static str,
+                     url: &str) -> Result<APIResponse> {
+        let mut found_index = None;
+        for (i, ref r) in self.responses.iter().enumerate() {
+            if r.method == method && r.url == url {
+                found_index = Some(i);
+                break;
+            }
+        }
+        if let Some(i) = found_index {
+            return self.responses.remove(i).response;
+        }
+        panic!("no response set for {} request to {}", method, url);
+    }
+}
+
+impl APIConnection for APIConnectionMock {
+    fn api_url(&self) -> &str {
+        self.settings.api_url()
+    }
+
+    fn api_key(&self) -> Option<&str> {
+        self.settings.api_key()
+    }
+
+    fn send_get_request(&mut self,
+                        url: &str,
+                        args: APIArguments) -> Result<APIResponse> {
+        self.add_request("GET", url, args);
+        self.find_response("GET", url)
+    }
+
+    fn send_post_request(&mut self,
+                         url: &str,
+                         args: APIArguments) -> Result<APIResponse> {
+        self.add_request("POST", url, args);
+        self.find_response("POST", url)
+    }
+}
+
+/// A wrapper over `APIConnectionMock` that can be passed to functions that
+/// expect `Box<APIConnection>`.
+pub struct APIConnectionMockWrapper {
+    settings: Settings,
+    conn: Rc<RefCell<APIConnectionMock>>,
+}
+
+impl APIConnectionMockWrapper {
+    /// Creates a new wrapper over the given mock.
+    pub fn new(conn: Rc<RefCell<APIConnectionMock>>) -> Self {
+        let settings = conn.borrow().settings.clone();
+        APIConnectionMockWrapper {
+            settings: settings,
+            conn: conn,
+        }
+    }
+}
+
+impl APIConnection for APIConnectionMockWrapper {
+    fn api_url(&self) -> &str {
+        self.settings.api_url()
+    }
+
+    fn api_key(&self) -> Option<&str> {
+        self.settings.api_key()
+    }
+
+    fn send_get_request(&mut self,
+                        url: &str,
+                        args: APIArguments) -> Result<APIResponse> {
+        self.conn.borrow_mut().send_get_request(url, args)
+    }
+
+    fn send_post_request(&mut self,
+                         url: &str,
+                         args: APIArguments) -> Result<APIResponse> {
+        self.conn.borrow_mut().send_post_request(url, args)
+    }
+}
+
+/// A connection-factory mock to be used in tests.
+///
+/// All the created connections share the given mock.
+pub struct APIConnectionFactoryMock {
+    conn: Rc<RefCell<APIConnectionMock>>,
+}
+
+impl APIConnectionFactoryMock {
+    /// Creates a new factory.
+    pub fn new(conn: Rc<RefCell<APIConnectionMock>>) -> Self {
+        APIConnectionFactoryMock { conn: conn }
+    }
+}
+
+impl APIConnectionFactory for APIConnectionFactoryMock {
+    fn new_connection(&self) -> Box<APIConnection> {
+        Box::new(APIConnectionMockWrapper::new(self.conn.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_mock() -> APIConnectionMock {
+        APIConnectionMock::new(
+            Settings::new()
+                .with_api_url("https://retdec.com/service/api")
+        )
+    }
+
+    #[test]
+    fn api_connection_mock_returns_scripted_responses_in_order() {
+        let mut conn = create_mock();
+        conn.add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Ok(APIResponseBuilder::new().with_status_code(200).build())
+        );
+        conn.add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Ok(APIResponseBuilder::new().with_status_code(401).build())
+        );
+
+        let first = conn.send_get_request_without_args("https://retdec.com/service/api/test")
+            .expect("expected the first request to succeed");
+        let second = conn.send_get_request_without_args("https://retdec.com/service/api/test")
+            .expect("expected the second request to succeed");
+
+        assert_eq!(first.status_code(), 200);
+        assert_eq!(second.status_code(), 401);
+        assert_eq!(conn.unused_responses_count(), 0);
+    }
+
+    #[test]
+    fn api_connection_mock_records_sent_requests() {
+        let mut conn = create_mock();
+        conn.add_response(
+            "GET",
+            "https://retdec.com/service/api/test/echo",
+            Ok(APIResponseBuilder::new().with_status_code(200).build())
+        );
+
+        conn.send_get_request(
+            "https://retdec.com/service/api/test/echo",
+            APIArgumentsBuilder::new()
+                .with_string_arg("param", "value")
+                .build()
+        ).expect("expected the request to succeed");
+
+        let requests = conn.sent_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method(), "GET");
+        assert_eq!(requests[0].url(), "https://retdec.com/service/api/test/echo");
+        assert_eq!(requests[0].args().get_arg("param"), Some(&"value".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "no GET request to https://retdec.com/service/api/test")]
+    fn api_connection_mock_assert_request_sent_panics_when_request_was_not_sent() {
+        let conn = create_mock();
+
+        conn.assert_request_sent(
+            "GET",
+            "https://retdec.com/service/api/test",
+            APIArguments::new()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no response set for GET request to")]
+    fn api_connection_mock_panics_when_no_response_is_scripted() {
+        let mut conn = create_mock();
+
+        let _ = conn.send_get_request_without_args("https://retdec.com/service/api/test");
+    }
+}
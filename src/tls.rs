@@ -0,0 +1,68 @@
+//! Creation of TLS clients according to the settings.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use hyper_native_tls::NativeTlsClient;
+use native_tls::Certificate;
+use native_tls::Pkcs12;
+use native_tls::TlsConnector;
+
+use error::Result;
+use error::ResultExt;
+use settings::Settings;
+
+/// Creates a TLS client that trusts the root certificates and presents the
+/// client certificate from the given settings.
+pub fn create_ssl_client(settings: &Settings) -> Result<NativeTlsClient> {
+    let root_certificates = settings.root_certificates();
+    let client_certificate = settings.client_certificate();
+    if root_certificates.is_empty() && client_certificate.is_none() {
+        return NativeTlsClient::new()
+            .chain_err(|| "failed to create a TLS connector");
+    }
+
+    let mut builder = TlsConnector::builder()
+        .chain_err(|| "failed to create a TLS connector")?;
+    for path in root_certificates {
+        let pem = read_file(path)?;
+        let certificate = Certificate::from_pem(&pem)
+            .chain_err(|| format!("failed to parse a PEM certificate from {:?}", path))?;
+        builder.add_root_certificate(certificate)
+            .chain_err(|| format!("failed to add a root certificate from {:?}", path))?;
+    }
+    if let Some((path, password)) = client_certificate {
+        let der = read_file(path)?;
+        let identity = Pkcs12::from_der(&der, password)
+            .chain_err(|| format!("failed to parse a PKCS #12 archive from {:?}", path))?;
+        builder.identity(identity)
+            .chain_err(|| format!("failed to use a client certificate from {:?}", path))?;
+    }
+    let connector = builder.build()
+        .chain_err(|| "failed to create a TLS connector")?;
+    Ok(NativeTlsClient::from(connector))
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    fs::File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut content))
+        .chain_err(|| format!("failed to read {:?}", path))?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_ssl_client_returns_error_when_root_certificate_cannot_be_read() {
+        let settings = Settings::new()
+            .with_root_certificate("/nonexisting/ca.pem");
+
+        let result = create_ssl_client(&settings);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Obtaining of API keys from the sources given to the tools.
+
+use std::path::Path;
+
+use clap::ArgMatches;
+
+use api_key::ApiKey;
+use api_key::is_world_readable;
+use config::Profile;
+use error::Result;
+use utils::non_empty_env_var;
+
+/// Returns the API key to be used by a tool.
+///
+/// The sources are tried in the following order:
+///
+/// 1. `--api-key`, `--api-key-file`, or `--api-key-command` (at most one of
+///    them can be given),
+/// 2. `api_key` in the profile,
+/// 3. the `RETDEC_API_KEY` environment variable,
+/// 4. the file given in the `RETDEC_API_KEY_FILE` environment variable.
+///
+/// Empty environment variables are ignored (like in `Settings::new()`).
+///
+/// Returns `None` when no source is available. Unlike `Settings::new()`, it
+/// reports problems with the key file. When the file is readable by anybody,
+/// a warning is added into `warnings`.
+pub fn api_key_from_args(args: &ArgMatches,
+                         profile: Option<&Profile>,
+                         warnings: &mut Vec<String>) -> Result<Option<ApiKey>> {
+    if let Some(api_key) = args.value_of("api_key") {
+        return Ok(Some(ApiKey::new(api_key)));
+    }
+    if let Some(path) = args.value_of("api_key_file") {
+        return read_api_key_file(Path::new(path), warnings).map(Some);
+    }
+    if let Some(command) = args.value_of("api_key_command") {
+        return ApiKey::from_command(command).map(Some);
+    }
+    if let Some(api_key) = profile.and_then(|p| p.api_key()) {
+        return Ok(Some(ApiKey::new(api_key)));
+    }
+    if let Some(api_key) = non_empty_env_var("RETDEC_API_KEY") {
+        return Ok(Some(ApiKey::new(api_key)));
+    }
+    if let Some(path) = non_empty_env_var("RETDEC_API_KEY_FILE") {
+        return read_api_key_file(Path::new(&path), warnings).map(Some);
+    }
+    Ok(None)
+}
+
+fn read_api_key_file(path: &Path, warnings: &mut Vec<String>) -> Result<ApiKey> {
+    if let Some(warning) = world_readable_warning(path) {
+        warnings.push(warning);
+    }
+    ApiKey::from_file(path)
+}
+
+fn world_readable_warning(path: &Path) -> Option<String> {
+    if !is_world_readable(path) {
+        return None;
+    }
+    Some(format!(
+        "the API key file {} is readable by anybody (run `chmod 600` on it)",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn world_readable_warning_is_returned_only_for_world_readable_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = TempDir::new("retdec-tools-api-key").unwrap();
+        let path = tmp_dir.path().join("key");
+        fs::File::create(&path).unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(world_readable_warning(&path).is_none());
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(world_readable_warning(&path).unwrap().starts_with("the API key file"));
+    }
+}
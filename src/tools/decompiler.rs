@@ -1,45 +1,288 @@
 //! A tool for decompilation of files.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
 use std::io::Write;
 use std::io;
+use std::path::Path;
+use std::path::PathBuf;
 
 use clap::App;
 use clap::AppSettings;
 use clap::Arg;
 use clap::ArgMatches;
+use clap::SubCommand;
 
 use VERSION;
+use cache::ResultCache;
+use config::Profile;
+use decompilation::Decompilation;
 use decompilation::DecompilationArguments;
+use decompilation::OutputKind;
 use decompiler::Decompiler;
+use error::Error;
 use error::Result;
 use error::ResultExt;
 use file::File;
-use settings::Settings;
+use file::SaveOptions;
+use tools::inputs::InputFile;
+use tools::inputs::InputFilter;
+use tools::inputs::collect_input_files;
+use tools::progress::Progress;
+use tools::report::JobReport;
+use tools::report::print_warnings;
+use tools::settings::connection_flags;
+use tools::settings::create_settings;
+use tools::settings::load_profile;
+
+/// Returns flags that map onto `DecompilationArguments`.
+fn decompilation_flags<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("mode")
+            .long("mode")
+            .takes_value(true)
+            .value_name("MODE")
+            .possible_values(&["bin", "c", "raw"])
+            .help("Decompilation mode (default: bin)"),
+        Arg::with_name("target_language")
+            .long("target-language")
+            .takes_value(true)
+            .value_name("LANG")
+            .possible_values(&["c", "py"])
+            .help("Language of the generated code (default: c)"),
+        Arg::with_name("architecture")
+            .long("architecture")
+            .takes_value(true)
+            .value_name("ARCH")
+            .possible_values(&["x86", "arm", "thumb", "mips", "pic32", "powerpc"])
+            .help("Architecture of the input (required in the raw mode; detected \
+                   automatically in the bin mode when not given)"),
+        Arg::with_name("endian")
+            .long("endian")
+            .takes_value(true)
+            .value_name("ENDIAN")
+            .possible_values(&["little", "big"])
+            .help("Endianness of the input (only for the bin and raw modes; \
+                   required in the raw mode)"),
+        Arg::with_name("raw_entry_point")
+            .long("raw-entry-point")
+            .takes_value(true)
+            .value_name("ADDR")
+            .help("Address of the entry point (only for the raw mode)"),
+        Arg::with_name("raw_section_vma")
+            .long("raw-section-vma")
+            .takes_value(true)
+            .value_name("ADDR")
+            .help("Address where the input is loaded (only for the raw mode)"),
+        Arg::with_name("pdb")
+            .long("pdb")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("PDB file with debugging information (only for the bin mode)"),
+        Arg::with_name("ar_index")
+            .long("ar-index")
+            .takes_value(true)
+            .value_name("N")
+            .conflicts_with("ar_name")
+            .help("Index of the archive member to be decompiled (starting from 0)"),
+        Arg::with_name("ar_name")
+            .long("ar-name")
+            .takes_value(true)
+            .value_name("NAME")
+            .help("Name of the archive member to be decompiled"),
+        Arg::with_name("sel_decomp_funcs")
+            .long("sel-decomp-funcs")
+            .takes_value(true)
+            .value_name("FUNCS")
+            .help("Decompile only the given functions (e.g. main,foo; only for \
+                   the bin mode)"),
+        Arg::with_name("sel_decomp_ranges")
+            .long("sel-decomp-ranges")
+            .takes_value(true)
+            .value_name("RANGES")
+            .help("Decompile only the given address ranges (e.g. \
+                   0x100-0x200,0x300-0x400; only for the bin mode)"),
+        Arg::with_name("sel_decomp_decoding")
+            .long("sel-decomp-decoding")
+            .takes_value(true)
+            .value_name("DECODING")
+            .possible_values(&["everything", "only"])
+            .help("What to decode in a selective decompilation (default: \
+                   everything)"),
+        Arg::with_name("decomp_var_names")
+            .long("decomp-var-names")
+            .takes_value(true)
+            .value_name("STYLE")
+            .possible_values(&["readable", "address", "hungarian", "simple", "unified"])
+            .help("Naming style of variables (default: readable)"),
+        Arg::with_name("decomp_optimizations")
+            .long("decomp-optimizations")
+            .takes_value(true)
+            .value_name("LEVEL")
+            .possible_values(&["none", "limited", "normal", "full"])
+            .help("Level of optimizations (default: normal)"),
+        Arg::with_name("decomp_unreachable_funcs")
+            .long("decomp-unreachable-funcs")
+            .takes_value(true)
+            .value_name("yes|no")
+            .possible_values(&["yes", "no"])
+            .help("Decompile functions unreachable from the entry point"),
+        Arg::with_name("decomp_emit_addresses")
+            .long("decomp-emit-addresses")
+            .takes_value(true)
+            .value_name("yes|no")
+            .possible_values(&["yes", "no"])
+            .help("Emit addresses in comments in the generated code"),
+    ]
+}
+
+fn outputs_flag<'a, 'b>(help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name("outputs")
+        .long("outputs")
+        .takes_value(true)
+        .value_name("LIST")
+        .use_delimiter(true)
+        .default_value("hll")
+        .help(help)
+}
+
+fn quiet_flag<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("quiet")
+        .short("q")
+        .long("quiet")
+        .help("Do not show progress of decompilations on the standard error")
+}
 
 fn parse_args<'a>(args: &[String]) -> ArgMatches<'a> {
     App::new("decompiler")
         .version(VERSION)
         .about("Decompiles the given file via retdec.com's API.")
+        .after_help(
+            "When a single file is given, the decompiled code is printed to the \
+             standard output (unless --output, --output-dir, or outputs other \
+             than hll are requested). When more files or directories are given, \
+             they are decompiled in parallel (see --jobs) and each result is saved \
+             next to its input file, or into a tree mirroring the inputs under \
+             --output-dir. At the end, a summary is printed. When any file fails \
+             to decompile, the exit code is non-zero.\n\n\
+             While a single file is being decompiled, its progress is shown on \
+             the standard error (as a live line on a terminal, as periodic log \
+             lines otherwise) unless --quiet is given.\n\n\
+             With --json, results are saved into files (as when more files are \
+             given) and a report for each input is printed on a separate line \
+             of the standard output as a JSON document.\n\n\
+             Outputs are saved under the names provided by the API. Outputs \
+             other than hll and dsm are generated only when requested via \
+             --outputs (e.g. --outputs hll,dsm,cg,cfgs,archive).\n\n\
+             To decompile a file without waiting for the result, use the submit, \
+             status, and fetch subcommands (see e.g. decompiler help submit)."
+        )
         .setting(AppSettings::ColorNever)
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(Arg::with_name("FILE")
             .required(true)
-            .help("Input file to be decompiled"))
-        .arg(Arg::with_name("api_key")
-            .short("k")
-            .long("api-key")
-            .takes_value(true)
-            .value_name("KEY")
-            // It is important not to require the API key by default because it
-            // enables the use of the RETDEC_API_KEY environment variable.
-            .help("API key to be used."))
-        .arg(Arg::with_name("api_url")
-            .short("u")
-            .long("api-url")
-            .takes_value(true)
-            .value_name("URL")
-            // It is important not to require the API URL by default because it
-            // enables the use of the RETDEC_API_URL environment variable.
-            .help("Custom URL to the retdec.com's API."))
+            .multiple(true)
+            .help("Input files (or directories with --recursive) to be decompiled"))
+        .arg(quiet_flag())
+        .arg(Arg::with_name("json")
+            .long("json")
+            .help("Print a JSON report for each input (ID, status, timings, \
+                   warnings, saved outputs, and error) instead of the usual \
+                   output; implies --quiet"))
+        .arg(Arg::with_name("recursive")
+            .short("r")
+            .long("recursive")
+            .help("Decompile all files in the given directories and their \
+                   subdirectories"))
+        .arg(Arg::with_name("include")
+            .long("include")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("GLOB")
+            .help("Decompile only files from directories matching the pattern \
+                   (e.g. '*.exe' or 'bin/**/*.dll'; can be given multiple times)"))
+        .arg(Arg::with_name("exclude")
+            .long("exclude")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("GLOB")
+            .help("Skip files from directories matching the pattern (can be given \
+                   multiple times)"))
+        .arg(Arg::with_name("jobs")
+            .short("j")
+            .long("jobs")
+            .takes_value(true)
+            .value_name("N")
+            .default_value("4")
+            .help("Maximal number of decompilations running at the same time"))
+        .arg(Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .takes_value(true)
+            .value_name("FILE")
+            .conflicts_with("output_dir")
+            .help("Save the decompiled code into the given file (only for a \
+                   single input file; other outputs are saved next to it)"))
+        .arg(Arg::with_name("output_dir")
+            .long("output-dir")
+            .takes_value(true)
+            .value_name("DIR")
+            .help("Save results into a tree mirroring the inputs under the given \
+                   directory"))
+        .arg(outputs_flag("Comma-separated list of outputs to be saved (hll, dsm, \
+                           cg, cfgs, archive)"))
+        .args(&decompilation_flags())
+        .args(&connection_flags())
+        .arg(Arg::with_name("no_cache")
+            .long("no-cache")
+            .help("Do not use the local cache of results (by default, results \
+                   are cached in RETDEC_CACHE_DIR or ~/.cache/retdec)"))
+        .subcommand(SubCommand::with_name("submit")
+            .about("Starts a decompilation of the given file, prints its ID, and exits.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("FILE")
+                .required(true)
+                .help("Input file to be decompiled"))
+            .arg(outputs_flag("Comma-separated list of outputs to be generated \
+                               (hll, dsm, cg, cfgs, archive)"))
+            .args(&decompilation_flags())
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("status")
+            .about("Prints the status of the decompilation with the given ID.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("ID")
+                .required(true)
+                .help("ID of the decompilation (printed by submit)"))
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("fetch")
+            .about("Fetches outputs of the decompilation with the given ID.")
+            .after_help(
+                "When only hll is requested and no --output-dir is given, the \
+                 decompiled code is printed to the standard output. Otherwise, \
+                 the outputs are saved into the output directory under the names \
+                 provided by the API."
+            )
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("ID")
+                .required(true)
+                .help("ID of the decompilation (printed by submit)"))
+            .arg(outputs_flag("Comma-separated list of outputs to be fetched \
+                               (hll, dsm, cg, cfgs, archive)"))
+            .arg(Arg::with_name("output_dir")
+                .long("output-dir")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Save the outputs into the given directory"))
+            .arg(Arg::with_name("wait")
+                .short("w")
+                .long("wait")
+                .help("Wait until the decompilation finishes (by default, fetching \
+                       outputs of a running decompilation fails)"))
+            .arg(quiet_flag())
+            .args(&connection_flags()))
         .get_matches_from(args)
 }
 
@@ -50,35 +293,570 @@ fn print_decompilation_result(output: &str) -> Result<()> {
     Ok(())
 }
 
-fn run(args: &[String]) -> Result<()> {
-    let args = parse_args(args);
+fn parse_jobs(args: &ArgMatches) -> Result<usize> {
+    let jobs = args.value_of("jobs")
+        .expect("no jobs - this should never happen");
+    match jobs.parse() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => bail!("invalid number of jobs: {}", jobs),
+    }
+}
 
-    let mut settings = Settings::new();
-    if let Some(api_key) = args.value_of("api_key") {
-        settings.set_api_key(api_key);
+fn parse_outputs(args: &ArgMatches) -> Result<Vec<OutputKind>> {
+    let names = args.values_of("outputs")
+        .expect("no outputs - this should never happen");
+    let mut outputs = Vec::new();
+    for name in names {
+        let output = match OutputKind::from_name(name.trim()) {
+            Some(OutputKind::Binary) | None => bail!("unknown output: {}", name),
+            Some(output) => output,
+        };
+        if !outputs.contains(&output) {
+            outputs.push(output);
+        }
     }
-    if let Some(api_url) = args.value_of("api_url") {
-        settings.set_api_url(api_url);
+    Ok(outputs)
+}
+
+/// Creates decompilation arguments (without an input file) from the
+/// command-line arguments and checks them.
+///
+/// Defaults are taken from the given profile and overridden by explicitly
+/// given flags.
+fn create_decompilation_args(args: &ArgMatches,
+                             outputs: &[OutputKind],
+                             profile: Option<&Profile>) -> Result<DecompilationArguments> {
+    let mut decompilation_args = DecompilationArguments::new();
+    if let Some(profile) = profile {
+        profile.apply_to_decompilation_args(&mut decompilation_args)?;
     }
-    let input_file = args.value_of("FILE")
-        .expect("no FILE - this should never happen");
+    if let Some(mode) = args.value_of("mode") {
+        decompilation_args.set_mode(mode);
+    }
+    if let Some(target_language) = args.value_of("target_language") {
+        decompilation_args.set_target_language(target_language);
+    }
+    if let Some(architecture) = args.value_of("architecture") {
+        decompilation_args.set_architecture(architecture);
+    }
+    if let Some(endian) = args.value_of("endian") {
+        decompilation_args.set_endian(endian);
+    }
+    if let Some(raw_entry_point) = args.value_of("raw_entry_point") {
+        decompilation_args.set_raw_entry_point(raw_entry_point);
+    }
+    if let Some(raw_section_vma) = args.value_of("raw_section_vma") {
+        decompilation_args.set_raw_section_vma(raw_section_vma);
+    }
+    if let Some(pdb) = args.value_of("pdb") {
+        decompilation_args.set_pdb_file(File::from_path(pdb)?);
+    }
+    if let Some(ar_index) = args.value_of("ar_index") {
+        let ar_index = ar_index.parse()
+            .chain_err(|| format!("invalid archive member index: {}", ar_index))?;
+        decompilation_args.set_ar_index(ar_index);
+    }
+    if let Some(ar_name) = args.value_of("ar_name") {
+        decompilation_args.set_ar_name(ar_name);
+    }
+    if let Some(sel_decomp_funcs) = args.value_of("sel_decomp_funcs") {
+        decompilation_args.set_sel_decomp_funcs(sel_decomp_funcs);
+    }
+    if let Some(sel_decomp_ranges) = args.value_of("sel_decomp_ranges") {
+        decompilation_args.set_sel_decomp_ranges(sel_decomp_ranges);
+    }
+    if let Some(sel_decomp_decoding) = args.value_of("sel_decomp_decoding") {
+        decompilation_args.set_sel_decomp_decoding(sel_decomp_decoding);
+    }
+    if let Some(decomp_var_names) = args.value_of("decomp_var_names") {
+        decompilation_args.set_decomp_var_names(decomp_var_names);
+    }
+    if let Some(decomp_optimizations) = args.value_of("decomp_optimizations") {
+        decompilation_args.set_decomp_optimizations(decomp_optimizations);
+    }
+    if let Some(value) = args.value_of("decomp_unreachable_funcs") {
+        decompilation_args.set_decomp_unreachable_funcs(value == "yes");
+    }
+    if let Some(value) = args.value_of("decomp_emit_addresses") {
+        decompilation_args.set_decomp_emit_addresses(value == "yes");
+    }
+    for output in outputs {
+        match *output {
+            OutputKind::CallGraph => decompilation_args.set_generate_cg(true),
+            OutputKind::ControlFlowGraphs => decompilation_args.set_generate_cfgs(true),
+            OutputKind::Archive => decompilation_args.set_generate_archive(true),
+            _ => {}
+        }
+    }
+    check_decompilation_args(&decompilation_args)?;
+    Ok(decompilation_args)
+}
+
+/// Rejects combinations of arguments that the API would refuse.
+fn check_decompilation_args(args: &DecompilationArguments) -> Result<()> {
+    let mode = args.mode().map(|m| m.as_str()).unwrap_or("bin");
+    if mode == "raw" {
+        if args.architecture().is_none() || args.endian().is_none() ||
+                args.raw_entry_point().is_none() || args.raw_section_vma().is_none() {
+            bail!("the raw mode requires --architecture, --endian, \
+                   --raw-entry-point, and --raw-section-vma");
+        }
+    } else if args.raw_entry_point().is_some() || args.raw_section_vma().is_some() {
+        bail!("--raw-entry-point and --raw-section-vma can be used only in the raw mode");
+    }
+    if mode == "c" && args.endian().is_some() {
+        bail!("--endian can be used only in the bin and raw modes");
+    }
+    if mode != "bin" {
+        if args.pdb_file().is_some() {
+            bail!("--pdb can be used only in the bin mode");
+        }
+        if args.ar_index().is_some() || args.ar_name().is_some() {
+            bail!("--ar-index and --ar-name can be used only in the bin mode");
+        }
+        if args.sel_decomp_funcs().is_some() || args.sel_decomp_ranges().is_some() ||
+                args.sel_decomp_decoding().is_some() {
+            bail!("--sel-decomp-funcs, --sel-decomp-ranges, and --sel-decomp-decoding \
+                   can be used only in the bin mode");
+        }
+    }
+    for &(name, address) in &[("--raw-entry-point", args.raw_entry_point()),
+                             ("--raw-section-vma", args.raw_section_vma())] {
+        if let Some(address) = address {
+            if parse_address(address).is_none() {
+                bail!("invalid address for {}: {}", name, address);
+            }
+        }
+    }
+    if let Some(ranges) = args.sel_decomp_ranges() {
+        for range in ranges.split(',') {
+            if !is_valid_range(range.trim()) {
+                bail!("invalid address range for --sel-decomp-ranges: {}", range);
+            }
+        }
+    }
+    if args.sel_decomp_decoding().is_some() &&
+            args.sel_decomp_funcs().is_none() && args.sel_decomp_ranges().is_none() {
+        bail!("--sel-decomp-decoding requires --sel-decomp-funcs or --sel-decomp-ranges");
+    }
+    Ok(())
+}
+
+/// Parses a hexadecimal (`0x`-prefixed) or decimal address.
+fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    let result = if address.starts_with("0x") || address.starts_with("0X") {
+        u64::from_str_radix(&address[2..], 16)
+    } else {
+        address.parse()
+    };
+    result.ok()
+}
+
+fn is_valid_range(range: &str) -> bool {
+    let mut parts = range.splitn(2, '-');
+    match (parts.next().and_then(parse_address), parts.next().and_then(parse_address)) {
+        (Some(start), Some(end)) => start <= end,
+        _ => false,
+    }
+}
+
+fn create_input_filter(args: &ArgMatches) -> InputFilter {
+    let mut filter = InputFilter::new();
+    for pattern in args.values_of("include").into_iter().flat_map(|p| p) {
+        filter = filter.with_include(pattern);
+    }
+    for pattern in args.values_of("exclude").into_iter().flat_map(|p| p) {
+        filter = filter.with_exclude(pattern);
+    }
+    filter
+}
+
+/// Returns a directory into which the result for the given input is saved.
+fn output_dir_for(input: &InputFile, output_dir: Option<&Path>) -> PathBuf {
+    let dir = match output_dir {
+        Some(output_dir) => {
+            let relative_dir = input.relative_path.parent().unwrap_or_else(|| Path::new(""));
+            output_dir.join(relative_dir)
+        }
+        None => input.path.parent().unwrap_or_else(|| Path::new("")).to_path_buf(),
+    };
+    if dir.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        dir
+    }
+}
 
-    let decompiler = Decompiler::new(settings);
-    let args = DecompilationArguments::new()
-        .with_input_file(File::from_path(&input_file)?);
+/// Ensures that outputs for no two inputs would be saved into the same files.
+///
+/// The API names outputs after the input file without its extension (e.g.
+/// `a.exe` -> `a.c`), so e.g. `a.exe` and `a.dll` from the same directory
+/// clash.
+fn ensure_outputs_do_not_clash(inputs: &[InputFile], output_dir: Option<&Path>) -> Result<()> {
+    let mut destinations: HashMap<PathBuf, &Path> = HashMap::new();
+    for input in inputs {
+        let stem = input.relative_path.file_stem().unwrap_or_default();
+        let destination = output_dir_for(input, output_dir).join(stem);
+        if let Some(other) = destinations.insert(destination, &input.path) {
+            bail!(
+                "outputs for {:?} and {:?} would be saved into the same files \
+                 (exclude one of them or use --output-dir)",
+                other,
+                input.path
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Saves the given outputs into the given directory and returns paths to the
+/// saved files.
+///
+/// When `hll_path` is given, the decompiled code is saved into it instead.
+fn save_decompilation_results(decompilation: &mut Decompilation,
+                              dir: &Path,
+                              hll_path: Option<&Path>,
+                              outputs: &[OutputKind],
+                              options: &SaveOptions) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)
+        .chain_err(|| format!("failed to create {:?}", dir))?;
+    let mut output_paths = Vec::new();
+    for output in outputs {
+        if *output == OutputKind::ControlFlowGraphs {
+            // Control-flow graphs are provided per function.
+            for func in decompilation.get_funcs_with_cfg()? {
+                decompilation.wait_until_cfg_generated(&func)?;
+                let cfg_file = decompilation.get_cfg_as_file(&func)?;
+                output_paths.push(cfg_file.save_into_with_options(dir, options)?);
+            }
+            continue;
+        }
+        decompilation.wait_until_output_generated(*output)?;
+        let output_file = decompilation.get_output_as_file(*output)?;
+        let output_path = match hll_path {
+            Some(hll_path) if *output == OutputKind::Hll => {
+                output_file.save_as_with_options(hll_path, options)?;
+                hll_path.to_path_buf()
+            }
+            _ => output_file.save_into_with_options(dir, options)?,
+        };
+        output_paths.push(output_path);
+    }
+    Ok(output_paths)
+}
+
+/// Waits until the decompilation finishes, showing its progress on the
+/// standard error unless `quiet` is `true`.
+fn wait_until_finished(decompilation: &mut Decompilation,
+                       label: &str,
+                       quiet: bool) -> Result<()> {
+    if quiet {
+        return decompilation.wait_until_finished();
+    }
+    let mut progress = Progress::on_stderr(label);
+    let result = decompilation.wait_until_finished_with_callback(|d| {
+        let phases = d.phases();
+        progress.update(d.completion(), phases.last().map(|p| p.name()));
+    });
+    progress.finish();
+    result
+}
+
+fn decompile_single_file(decompiler: &Decompiler,
+                         input_file: &Path,
+                         base_args: &DecompilationArguments,
+                         quiet: bool) -> Result<()> {
+    let args = base_args.clone()
+        .with_input_file(File::from_path(input_file)?);
     let mut decompilation = decompiler.start_decompilation(args)?;
-    decompilation.wait_until_finished()?;
+    wait_until_finished(&mut decompilation, &input_file.display().to_string(), quiet)?;
     let output_code = decompilation.get_output_hll_code()?;
-    print_decompilation_result(&output_code)?;
+    print_decompilation_result(&output_code)
+}
+
+/// Decompiles the given file and saves the decompiled code into the given
+/// path (and other outputs next to it), recording the ID and the saved
+/// outputs into the given report.
+fn decompile_single_file_into(decompiler: &Decompiler,
+                              input_file: &Path,
+                              output_path: &Path,
+                              base_args: &DecompilationArguments,
+                              outputs: &[OutputKind],
+                              quiet: bool,
+                              report: &mut JobReport) -> Result<()> {
+    let args = base_args.clone()
+        .with_input_file(File::from_path(input_file)?);
+    let mut decompilation = decompiler.start_decompilation(args)?;
+    report.set_id(decompilation.id());
+    wait_until_finished(&mut decompilation, &input_file.display().to_string(), quiet)?;
+    report.add_warnings(&phase_warnings(&decompilation));
+    let dir = match output_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let output_paths = save_decompilation_results(
+        &mut decompilation, dir, Some(output_path), outputs, &SaveOptions::new()
+    )?;
+    for output_path in output_paths {
+        report.add_output_path(output_path);
+    }
+    Ok(())
+}
+
+/// Returns warnings that the decompiler emitted during the phases of the
+/// given decompilation.
+fn phase_warnings(decompilation: &Decompilation) -> Vec<String> {
+    decompilation.phases().iter()
+        .flat_map(|phase| phase.warnings().iter().cloned())
+        .collect()
+}
+
+/// Prints paths to outputs saved for the input from the given report.
+fn print_output_paths(report: &JobReport) {
+    for output_path in report.output_paths() {
+        println!("{} -> {}", report.input(), output_path.display());
+    }
+}
+
+fn decompile_in_batch(decompiler: &Decompiler,
+                      inputs: Vec<InputFile>,
+                      output_dir: Option<&Path>,
+                      base_args: &DecompilationArguments,
+                      outputs: &[OutputKind],
+                      jobs: usize,
+                      warnings: &[String],
+                      json: bool) -> Result<()> {
+    let total = inputs.len();
+    // Clashing outputs that slipped through ensure_outputs_do_not_clash()
+    // (e.g. files left from earlier runs) fail their input instead of being
+    // silently overwritten.
+    let save_options = SaveOptions::new().with_overwrite(false);
+    let started: RefCell<Vec<(InputFile, JobReport)>> = RefCell::new(Vec::new());
+    let failures: RefCell<Vec<(PathBuf, Error)>> = RefCell::new(Vec::new());
+    let mut succeeded = 0;
+    let report_failure = |input: InputFile, mut report: JobReport, err: Error| {
+        report.fail(&err);
+        if json {
+            report.print_json();
+        } else {
+            eprintln!("{}: {}", input.path.display(), err);
+        }
+        failures.borrow_mut().push((input.path, err));
+    };
+
+    // Input files are loaded only when their decompilation is about to be
+    // started, so they do not have to fit into memory all at once.
+    let args = inputs.into_iter().filter_map(|input| {
+        let mut report = JobReport::start(input.path.display().to_string());
+        report.add_warnings(warnings);
+        match File::from_path(&input.path) {
+            Ok(file) => {
+                started.borrow_mut().push((input, report));
+                Some(base_args.clone().with_input_file(file))
+            }
+            Err(err) => {
+                report_failure(input, report, err);
+                None
+            }
+        }
+    });
+    for result in decompiler.start_batch(args).with_max_in_flight(jobs) {
+        let (input, mut report) = started.borrow()[result.index].clone();
+        if let Some(ref id) = result.id {
+            report.set_id(id.as_str());
+        }
+        let dir = output_dir_for(&input, output_dir);
+        let saved = result.result
+            .and_then(|mut d| {
+                report.add_warnings(&phase_warnings(&d));
+                save_decompilation_results(&mut d, &dir, None, outputs, &save_options)
+            });
+        match saved {
+            Ok(output_paths) => {
+                succeeded += 1;
+                for output_path in output_paths {
+                    report.add_output_path(output_path);
+                }
+                report.succeed();
+                if json {
+                    report.print_json();
+                } else {
+                    print_output_paths(&report);
+                }
+            }
+            Err(err) => report_failure(input, report, err),
+        }
+    }
+
+    let failures = failures.into_inner();
+    if !json {
+        println!();
+        println!("{} succeeded, {} failed", succeeded, failures.len());
+        for &(ref path, ref err) in &failures {
+            println!("  failed: {} ({})", path.display(), err);
+        }
+    }
+    let failed = failures.len();
+    if let Some((_, err)) = failures.into_iter().next() {
+        // The first failure determines the exit code of the tool.
+        let msg = format!("failed to decompile {} of {} files", failed, total);
+        return Err(Error::with_chain(err, msg));
+    }
+    Ok(())
+}
+
+/// Creates a decompiler and prints warnings concerning its settings to the
+/// standard error.
+fn create_decompiler(args: &ArgMatches, profile: Option<&Profile>) -> Result<Decompiler> {
+    let mut warnings = Vec::new();
+    let settings = create_settings(args, profile, &mut warnings)?;
+    print_warnings(&warnings);
+    Ok(Decompiler::new(settings))
+}
+
+fn submit(args: &ArgMatches) -> Result<()> {
+    let input_file = args.value_of("FILE")
+        .expect("no FILE - this should never happen");
+    let outputs = parse_outputs(args)?;
+    let profile = load_profile(args)?;
+    let decompilation_args = create_decompilation_args(args, &outputs, profile.as_ref())?
+        .with_input_file(File::from_path(input_file)?);
+
+    let decompiler = create_decompiler(args, profile.as_ref())?;
+    let decompilation = decompiler.start_decompilation(decompilation_args)?;
+    println!("{}", decompilation.id());
     Ok(())
 }
 
+fn print_status(args: &ArgMatches) -> Result<()> {
+    let id = args.value_of("ID")
+        .expect("no ID - this should never happen");
+
+    let decompiler = create_decompiler(args, load_profile(args)?.as_ref())?;
+    let mut decompilation = decompiler.attach_to_decompilation(id);
+    if !decompilation.has_finished()? {
+        println!("{}: running", id);
+    } else if decompilation.has_succeeded()? {
+        println!("{}: succeeded", id);
+    } else {
+        println!("{}: failed ({})", id, decompilation.error().unwrap_or("unknown error"));
+    }
+    Ok(())
+}
+
+fn fetch(args: &ArgMatches) -> Result<()> {
+    let id = args.value_of("ID")
+        .expect("no ID - this should never happen");
+    let outputs = parse_outputs(args)?;
+    let output_dir = args.value_of("output_dir").map(Path::new);
+
+    let decompiler = create_decompiler(args, load_profile(args)?.as_ref())?;
+    let mut decompilation = decompiler.attach_to_decompilation(id);
+    if args.is_present("wait") {
+        wait_until_finished(&mut decompilation, id, args.is_present("quiet"))?;
+    } else if !decompilation.has_finished()? {
+        bail!("decompilation {} has not finished yet (use --wait to wait for it)", id);
+    }
+    if output_dir.is_none() && outputs == [OutputKind::Hll] {
+        let output_code = decompilation.get_output_hll_code()?;
+        return print_decompilation_result(&output_code);
+    }
+    let dir = output_dir.unwrap_or_else(|| Path::new("."));
+    let output_paths = save_decompilation_results(
+        &mut decompilation, dir, None, &outputs, &SaveOptions::new()
+    )?;
+    for output_path in output_paths {
+        println!("{} -> {}", id, output_path.display());
+    }
+    Ok(())
+}
+
+fn decompile(args: &ArgMatches) -> Result<()> {
+    let profile = load_profile(args)?;
+    let json = args.is_present("json");
+    let mut warnings = Vec::new();
+    let settings = create_settings(args, profile.as_ref(), &mut warnings)?;
+    if !json {
+        print_warnings(&warnings);
+    }
+    let paths: Vec<&str> = args.values_of("FILE")
+        .expect("no FILE - this should never happen")
+        .collect();
+    let jobs = parse_jobs(args)?;
+    let output = args.value_of("output").map(Path::new);
+    let output_dir = args.value_of("output_dir").map(Path::new);
+    let outputs = parse_outputs(args)?;
+    let base_args = create_decompilation_args(args, &outputs, profile.as_ref())?;
+    let quiet = args.is_present("quiet") || json;
+
+    let mut decompiler = Decompiler::new(settings);
+    if !args.is_present("no_cache") {
+        if let Some(cache_dir) = ResultCache::default_dir() {
+            decompiler = decompiler.with_cache(ResultCache::new(cache_dir));
+        }
+    }
+
+    let single_file = paths.len() == 1 && !Path::new(paths[0]).is_dir();
+    if let Some(output) = output {
+        if !single_file {
+            bail!("--output can be used only with a single input file");
+        }
+        let mut report = JobReport::start(paths[0]);
+        report.add_warnings(&warnings);
+        let result = decompile_single_file_into(
+            &decompiler, Path::new(paths[0]), output, &base_args, &outputs, quiet, &mut report
+        );
+        if !json {
+            result?;
+            print_output_paths(&report);
+            return Ok(());
+        }
+        match result {
+            Ok(()) => report.succeed(),
+            Err(ref err) => report.fail(err),
+        }
+        report.print_json();
+        return result.chain_err(|| format!("failed to decompile {}", paths[0]));
+    }
+    if !json && single_file && output_dir.is_none() && outputs == [OutputKind::Hll] {
+        return decompile_single_file(&decompiler, Path::new(paths[0]), &base_args, quiet);
+    }
+    let inputs = collect_input_files(
+        &paths,
+        args.is_present("recursive"),
+        &create_input_filter(args)
+    )?;
+    ensure_outputs_do_not_clash(&inputs, output_dir)?;
+    decompile_in_batch(
+        &decompiler, inputs, output_dir, &base_args, &outputs, jobs, &warnings, json
+    )
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args);
+    match args.subcommand() {
+        ("submit", Some(args)) => submit(args),
+        ("status", Some(args)) => print_status(args),
+        ("fetch", Some(args)) => fetch(args),
+        _ => decompile(&args),
+    }
+}
+
 generate_main_for_tool!(run);
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::rc::Rc;
+
+    use config::Config;
+    use settings::Settings;
+    use testing::APIConnectionMock;
+    use testing::APIConnectionMockWrapper;
+    use testing::APIResponseBuilder;
+
     macro_rules! args {
         ($($arg:expr),*) => {
             {
@@ -98,6 +876,435 @@ mod tests {
         assert_eq!(args.value_of("FILE"), Some("file.exe"));
     }
 
+    #[test]
+    fn parse_args_correctly_parses_multiple_input_files() {
+        let args = parse_args(&args!["a.exe", "dir"]);
+        assert_eq!(args.values_of("FILE").unwrap().collect::<Vec<_>>(), vec!["a.exe", "dir"]);
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_recursive() {
+        let args = parse_args(&args!["-r", "dir"]);
+        assert!(args.is_present("recursive"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_include_and_exclude_patterns() {
+        let args = parse_args(&args![
+            "--include", "*.exe", "--include", "*.dll", "--exclude", "test_*", "dir"
+        ]);
+        assert_eq!(args.values_of("include").unwrap().collect::<Vec<_>>(), vec!["*.exe", "*.dll"]);
+        assert_eq!(args.values_of("exclude").unwrap().collect::<Vec<_>>(), vec!["test_*"]);
+        assert_eq!(args.values_of("FILE").unwrap().collect::<Vec<_>>(), vec!["dir"]);
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_output_dir() {
+        let args = parse_args(&args!["--output-dir", "out", "file.exe"]);
+        assert_eq!(args.value_of("output_dir"), Some("out"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_output_short_form() {
+        let args = parse_args(&args!["-o", "file.c", "file.exe"]);
+        assert_eq!(args.value_of("output"), Some("file.c"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_output_long_form() {
+        let args = parse_args(&args!["--output", "file.c", "file.exe"]);
+        assert_eq!(args.value_of("output"), Some("file.c"));
+    }
+
+    #[test]
+    fn parse_outputs_returns_hll_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert_eq!(parse_outputs(&args).unwrap(), vec![OutputKind::Hll]);
+    }
+
+    #[test]
+    fn parse_outputs_returns_given_outputs_without_duplicates() {
+        let args = parse_args(&args!["--outputs", "hll,dsm,cg,cfgs,archive,hll", "file.exe"]);
+        assert_eq!(parse_outputs(&args).unwrap(), vec![
+            OutputKind::Hll,
+            OutputKind::Dsm,
+            OutputKind::CallGraph,
+            OutputKind::ControlFlowGraphs,
+            OutputKind::Archive,
+        ]);
+    }
+
+    #[test]
+    fn parse_outputs_returns_error_for_unknown_output() {
+        let args = parse_args(&args!["--outputs", "hll,unknown", "file.exe"]);
+        assert!(parse_outputs(&args).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_requests_generation_of_given_outputs() {
+        let args = parse_args(&args!["file.exe"]);
+
+        let args = create_decompilation_args(
+            &args, &[OutputKind::Hll, OutputKind::CallGraph, OutputKind::Archive], None
+        ).unwrap();
+
+        assert_eq!(args.generate_cg(), Some(true));
+        assert_eq!(args.generate_cfgs(), None);
+        assert_eq!(args.generate_archive(), Some(true));
+    }
+
+    #[test]
+    fn phase_warnings_returns_warnings_from_all_phases() {
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("https://retdec.com/service/api");
+        let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings)));
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/decompiler/decompilations/ID/status",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{
+                        "finished": true,
+                        "succeeded": true,
+                        "failed": false,
+                        "phases": [
+                            {"name": "A", "warnings": ["first"]},
+                            {"name": "B", "warnings": []},
+                            {"name": "C", "warnings": ["second", "third"]}
+                        ]
+                    }"#)
+                    .build()
+            )
+        );
+        let mut decompilation = Decompilation::new(
+            "ID", Box::new(APIConnectionMockWrapper::new(conn.clone()))
+        );
+        decompilation.get_phases().unwrap();
+
+        assert_eq!(phase_warnings(&decompilation), vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn create_decompilation_args_requests_generation_of_cfgs() {
+        let args = parse_args(&args!["file.exe"]);
+
+        let args = create_decompilation_args(
+            &args, &[OutputKind::Hll, OutputKind::ControlFlowGraphs], None
+        ).unwrap();
+
+        assert_eq!(args.generate_cfgs(), Some(true));
+    }
+
+    fn decompilation_args_from(args: &[String]) -> Result<DecompilationArguments> {
+        create_decompilation_args(&parse_args(args), &[OutputKind::Hll], None)
+    }
+
+    #[test]
+    fn create_decompilation_args_leaves_all_arguments_unset_by_default() {
+        let args = decompilation_args_from(&args!["file.exe"]).unwrap();
+
+        assert_eq!(args.mode(), None);
+        assert_eq!(args.target_language(), None);
+        assert!(args.pdb_file().is_none());
+        assert_eq!(args.ar_index(), None);
+        assert_eq!(args.decomp_emit_addresses(), None);
+    }
+
+    #[test]
+    fn create_decompilation_args_correctly_maps_raw_mode_arguments() {
+        let args = decompilation_args_from(&args![
+            "--mode", "raw", "--architecture", "arm", "--endian", "big",
+            "--raw-entry-point", "0x8000", "--raw-section-vma", "0x4000", "file.bin"
+        ]).unwrap();
+
+        assert_eq!(args.mode().map(|s| s.as_str()), Some("raw"));
+        assert_eq!(args.architecture().map(|s| s.as_str()), Some("arm"));
+        assert_eq!(args.endian().map(|s| s.as_str()), Some("big"));
+        assert_eq!(args.raw_entry_point().map(|s| s.as_str()), Some("0x8000"));
+        assert_eq!(args.raw_section_vma().map(|s| s.as_str()), Some("0x4000"));
+    }
+
+    #[test]
+    fn create_decompilation_args_correctly_maps_decompilation_options() {
+        let args = decompilation_args_from(&args![
+            "--target-language", "py",
+            "--ar-name", "a.o",
+            "--sel-decomp-funcs", "main,foo",
+            "--sel-decomp-ranges", "0x100-0x200,300-400",
+            "--sel-decomp-decoding", "only",
+            "--decomp-var-names", "simple",
+            "--decomp-optimizations", "full",
+            "--decomp-unreachable-funcs", "yes",
+            "--decomp-emit-addresses", "no",
+            "file.a"
+        ]).unwrap();
+
+        assert_eq!(args.target_language().map(|s| s.as_str()), Some("py"));
+        assert_eq!(args.ar_name().map(|s| s.as_str()), Some("a.o"));
+        assert_eq!(args.sel_decomp_funcs().map(|s| s.as_str()), Some("main,foo"));
+        assert_eq!(args.sel_decomp_ranges().map(|s| s.as_str()), Some("0x100-0x200,300-400"));
+        assert_eq!(args.sel_decomp_decoding().map(|s| s.as_str()), Some("only"));
+        assert_eq!(args.decomp_var_names().map(|s| s.as_str()), Some("simple"));
+        assert_eq!(args.decomp_optimizations().map(|s| s.as_str()), Some("full"));
+        assert_eq!(args.decomp_unreachable_funcs(), Some(true));
+        assert_eq!(args.decomp_emit_addresses(), Some(false));
+    }
+
+    #[test]
+    fn create_decompilation_args_correctly_maps_ar_index() {
+        let args = decompilation_args_from(&args!["--ar-index", "3", "file.a"]).unwrap();
+        assert_eq!(args.ar_index(), Some(3));
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_invalid_ar_index() {
+        assert!(decompilation_args_from(&args!["--ar-index", "first", "file.a"]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_when_raw_mode_lacks_required_arguments() {
+        assert!(decompilation_args_from(&args![
+            "--mode", "raw", "--architecture", "arm", "file.bin"
+        ]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_raw_arguments_outside_raw_mode() {
+        assert!(decompilation_args_from(&args!["--raw-entry-point", "0x0", "file.exe"]).is_err());
+        assert!(decompilation_args_from(&args!["--raw-section-vma", "0x0", "file.exe"]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_allows_architecture_and_endian_in_bin_mode() {
+        let args = decompilation_args_from(&args![
+            "--architecture", "mips", "--endian", "little", "file.exe"
+        ]).unwrap();
+
+        assert_eq!(args.architecture().map(|s| s.as_str()), Some("mips"));
+        assert_eq!(args.endian().map(|s| s.as_str()), Some("little"));
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_endian_in_c_mode() {
+        assert!(decompilation_args_from(&args![
+            "--mode", "c", "--endian", "big", "file.c"
+        ]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_selective_decompilation_outside_bin_mode() {
+        assert!(decompilation_args_from(&args![
+            "--mode", "c", "--sel-decomp-funcs", "main", "file.c"
+        ]).is_err());
+        assert!(decompilation_args_from(&args![
+            "--mode", "raw", "--architecture", "arm", "--endian", "big",
+            "--raw-entry-point", "0x8000", "--raw-section-vma", "0x4000",
+            "--sel-decomp-ranges", "0x8000-0x8100", "file.bin"
+        ]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_archive_member_outside_bin_mode() {
+        assert!(decompilation_args_from(&args![
+            "--mode", "c", "--ar-name", "a.o", "file.c"
+        ]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_invalid_addresses() {
+        assert!(decompilation_args_from(&args![
+            "--mode", "raw", "--architecture", "arm", "--endian", "big",
+            "--raw-entry-point", "0xZZ", "--raw-section-vma", "0x4000", "file.bin"
+        ]).is_err());
+        assert!(decompilation_args_from(&args![
+            "--sel-decomp-ranges", "0x200-0x100", "file.exe"
+        ]).is_err());
+    }
+
+    #[test]
+    fn create_decompilation_args_returns_error_for_decoding_without_selection() {
+        assert!(decompilation_args_from(&args![
+            "--sel-decomp-decoding", "only", "file.exe"
+        ]).is_err());
+    }
+
+    #[test]
+    fn parse_jobs_returns_default_number_of_jobs() {
+        let args = parse_args(&args!["file.exe"]);
+        assert_eq!(parse_jobs(&args).unwrap(), 4);
+    }
+
+    #[test]
+    fn parse_jobs_returns_given_number_of_jobs() {
+        let args = parse_args(&args!["-j", "8", "file.exe"]);
+        assert_eq!(parse_jobs(&args).unwrap(), 8);
+    }
+
+    #[test]
+    fn parse_jobs_returns_error_for_invalid_number_of_jobs() {
+        assert!(parse_jobs(&parse_args(&args!["--jobs", "0", "file.exe"])).is_err());
+        assert!(parse_jobs(&parse_args(&args!["--jobs", "many", "file.exe"])).is_err());
+    }
+
+    #[test]
+    fn output_dir_for_returns_directory_of_input_when_no_output_dir_is_given() {
+        let input = InputFile {
+            path: Path::new("dir").join("a.exe"),
+            relative_path: PathBuf::from("a.exe"),
+        };
+        assert_eq!(output_dir_for(&input, None), Path::new("dir"));
+    }
+
+    #[test]
+    fn output_dir_for_returns_current_directory_for_input_without_directory() {
+        let input = InputFile {
+            path: PathBuf::from("a.exe"),
+            relative_path: PathBuf::from("a.exe"),
+        };
+        assert_eq!(output_dir_for(&input, None), Path::new("."));
+    }
+
+    #[test]
+    fn output_dir_for_mirrors_relative_path_of_input_under_output_dir() {
+        let input = InputFile {
+            path: Path::new("dir").join("sub").join("a.exe"),
+            relative_path: Path::new("sub").join("a.exe"),
+        };
+        assert_eq!(
+            output_dir_for(&input, Some(Path::new("out"))),
+            Path::new("out").join("sub")
+        );
+    }
+
+    #[test]
+    fn ensure_outputs_do_not_clash_accepts_inputs_with_different_names() {
+        let inputs = vec![
+            InputFile { path: PathBuf::from("a.exe"), relative_path: PathBuf::from("a.exe") },
+            InputFile { path: PathBuf::from("b.exe"), relative_path: PathBuf::from("b.exe") },
+        ];
+
+        assert!(ensure_outputs_do_not_clash(&inputs, None).is_ok());
+    }
+
+    #[test]
+    fn ensure_outputs_do_not_clash_rejects_inputs_differing_only_in_extension() {
+        let inputs = vec![
+            InputFile { path: PathBuf::from("x.exe"), relative_path: PathBuf::from("x.exe") },
+            InputFile { path: PathBuf::from("x.dll"), relative_path: PathBuf::from("x.dll") },
+        ];
+
+        assert!(ensure_outputs_do_not_clash(&inputs, None).is_err());
+    }
+
+    #[test]
+    fn ensure_outputs_do_not_clash_rejects_inputs_mapped_to_same_output_dir() {
+        let inputs = vec![
+            InputFile {
+                path: Path::new("a").join("x.exe"),
+                relative_path: PathBuf::from("x.exe"),
+            },
+            InputFile {
+                path: Path::new("b").join("x.exe"),
+                relative_path: PathBuf::from("x.exe"),
+            },
+        ];
+
+        assert!(ensure_outputs_do_not_clash(&inputs, None).is_ok());
+        assert!(ensure_outputs_do_not_clash(&inputs, Some(Path::new("out"))).is_err());
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_submit_subcommand() {
+        let args = parse_args(&args!["submit", "--mode", "bin", "-k", "KEY", "file.exe"]);
+        let (name, args) = args.subcommand();
+        let args = args.unwrap();
+        assert_eq!(name, "submit");
+        assert_eq!(args.value_of("FILE"), Some("file.exe"));
+        assert_eq!(args.value_of("mode"), Some("bin"));
+        assert_eq!(args.value_of("api_key"), Some("KEY"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_status_subcommand() {
+        let args = parse_args(&args!["status", "ID"]);
+        let (name, args) = args.subcommand();
+        assert_eq!(name, "status");
+        assert_eq!(args.unwrap().value_of("ID"), Some("ID"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_fetch_subcommand() {
+        let args = parse_args(&args![
+            "fetch", "--outputs", "hll,dsm", "--output-dir", "out", "--wait", "ID"
+        ]);
+        let (name, args) = args.subcommand();
+        let args = args.unwrap();
+        assert_eq!(name, "fetch");
+        assert_eq!(args.value_of("ID"), Some("ID"));
+        assert_eq!(parse_outputs(args).unwrap(), vec![OutputKind::Hll, OutputKind::Dsm]);
+        assert_eq!(args.value_of("output_dir"), Some("out"));
+        assert!(args.is_present("wait"));
+    }
+
+    #[test]
+    fn parse_args_does_not_use_subcommand_for_input_files() {
+        let args = parse_args(&args!["file.exe"]);
+        assert_eq!(args.subcommand_name(), None);
+    }
+
+    #[test]
+    fn parse_args_shows_progress_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert!(!args.is_present("quiet"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_quiet() {
+        let args = parse_args(&args!["--quiet", "file.exe"]);
+        assert!(args.is_present("quiet"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_json() {
+        let args = parse_args(&args!["--json", "file.exe"]);
+        assert!(args.is_present("json"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_config_and_profile() {
+        let args = parse_args(&args!["--config", "config.ini", "--profile", "work", "file.exe"]);
+        assert_eq!(args.value_of("config"), Some("config.ini"));
+        assert_eq!(args.value_of("profile"), Some("work"));
+    }
+
+    #[test]
+    fn create_decompilation_args_takes_defaults_from_profile_and_overrides_them_by_flags() {
+        let config = Config::parse(
+            "[p]\n\
+             decompiler.decomp_var_names = simple\n\
+             decompiler.target_language = py\n"
+        ).unwrap();
+        let args = parse_args(&args!["--target-language", "c", "file.exe"]);
+
+        let args = create_decompilation_args(
+            &args, &[OutputKind::Hll], config.profile("p")
+        ).unwrap();
+
+        assert_eq!(args.decomp_var_names().map(|s| s.as_str()), Some("simple"));
+        assert_eq!(args.target_language().map(|s| s.as_str()), Some("c"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_api_key_file_and_command() {
+        let args = parse_args(&args!["--api-key-file", "key.txt", "file.exe"]);
+        assert_eq!(args.value_of("api_key_file"), Some("key.txt"));
+
+        let args = parse_args(&args!["--api-key-command", "pass show retdec", "file.exe"]);
+        assert_eq!(args.value_of("api_key_command"), Some("pass show retdec"));
+    }
+
     #[test]
     fn parse_args_correctly_parses_api_key_short_form() {
         let args = parse_args(&args!["-k", "KEY", "file.exe"]);
@@ -121,4 +1328,34 @@ mod tests {
         let args = parse_args(&args!["--api-url", "URL", "file.exe"]);
         assert_eq!(args.value_of("api_url"), Some("URL"));
     }
+
+    #[test]
+    fn parse_args_does_not_allow_plain_http_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert!(!args.is_present("allow_plain_http"));
+    }
+
+    #[test]
+    fn parse_args_uses_cache_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert!(!args.is_present("no_cache"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_no_cache() {
+        let args = parse_args(&args!["--no-cache", "file.exe"]);
+        assert!(args.is_present("no_cache"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_allow_plain_http() {
+        let args = parse_args(&args!["--allow-plain-http", "file.exe"]);
+        assert!(args.is_present("allow_plain_http"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_verbose_http() {
+        let args = parse_args(&args!["--verbose-http", "file.exe"]);
+        assert!(args.is_present("verbose_http"));
+    }
 }
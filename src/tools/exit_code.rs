@@ -0,0 +1,157 @@
+//! Exit codes of the tools.
+//!
+//! Each class of failures has its own exit code, so scripts running the tools
+//! can decide whether it makes sense to retry. Retrying makes sense only for
+//! `EXIT_SERVICE_UNREACHABLE` and `EXIT_SERVICE_UNAVAILABLE`.
+
+use error::Error;
+use error::ErrorKind;
+use error::chain_contains;
+
+/// Exit code when the tool succeeds.
+pub const EXIT_SUCCESS: i32 = 0;
+
+/// Exit code for failures that are not related to the API (e.g. invalid
+/// arguments, an invalid configuration file, no API key, or an unreadable
+/// input file).
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Exit code when the API rejects the API key.
+pub const EXIT_BAD_API_KEY: i32 = 2;
+
+/// Exit code when the API cannot be reached (e.g. the name of the host cannot
+/// be resolved or the connection breaks).
+pub const EXIT_SERVICE_UNREACHABLE: i32 = 3;
+
+/// Exit code when the API returns an unexpected response (e.g. HTTP 404 or
+/// malformed JSON).
+pub const EXIT_UNEXPECTED_RESPONSE: i32 = 4;
+
+/// Exit code when the API is overloaded or broken (HTTP 429 or 5xx).
+pub const EXIT_SERVICE_UNAVAILABLE: i32 = 5;
+
+/// Exit code when a decompilation or analysis fails.
+pub const EXIT_JOB_FAILED: i32 = 6;
+
+/// Returns the exit code for the given error.
+///
+/// When the error chain contains errors of more classes, the first matching
+/// class from the following order wins: a rejected API key, an unreachable
+/// API, an unavailable API, an unexpected response, and a failed job.
+pub fn exit_code_for_error(err: &Error) -> i32 {
+    if chain_contains(err, is_bad_api_key) {
+        EXIT_BAD_API_KEY
+    } else if chain_contains(err, is_connection_failure) {
+        EXIT_SERVICE_UNREACHABLE
+    } else if chain_contains(err, is_unavailability) {
+        EXIT_SERVICE_UNAVAILABLE
+    } else if chain_contains(err, is_unexpected_response) {
+        EXIT_UNEXPECTED_RESPONSE
+    } else if chain_contains(err, is_job_failure) {
+        EXIT_JOB_FAILED
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+fn is_bad_api_key(kind: &ErrorKind) -> bool {
+    match *kind {
+        ErrorKind::AuthenticationFailed => true,
+        ErrorKind::RequestFailed(status_code, _) => status_code == 401,
+        _ => false,
+    }
+}
+
+fn is_connection_failure(kind: &ErrorKind) -> bool {
+    match *kind {
+        ErrorKind::ConnectionFailed(_) => true,
+        _ => false,
+    }
+}
+
+fn is_unavailability(kind: &ErrorKind) -> bool {
+    match *kind {
+        ErrorKind::RequestFailed(status_code, _) => {
+            status_code == 429 || status_code >= 500
+        }
+        _ => false,
+    }
+}
+
+fn is_unexpected_response(kind: &ErrorKind) -> bool {
+    match *kind {
+        ErrorKind::RequestFailed(..) | ErrorKind::InvalidResponse(_) => true,
+        _ => false,
+    }
+}
+
+fn is_job_failure(kind: &ErrorKind) -> bool {
+    match *kind {
+        ErrorKind::JobFailed(_) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_failed(status_code: u16) -> Error {
+        Error::with_chain(
+            Error::from_kind(ErrorKind::RequestFailed(status_code, "request failed".to_string())),
+            ErrorKind::Msg("failed to start a decompilation".to_string())
+        )
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_failure_for_error_unrelated_to_api() {
+        let err = Error::from_kind(ErrorKind::Msg("invalid --jobs".to_string()));
+
+        assert_eq!(exit_code_for_error(&err), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_bad_api_key_for_authentication_failure() {
+        let err = Error::from_kind(ErrorKind::AuthenticationFailed);
+
+        assert_eq!(exit_code_for_error(&err), EXIT_BAD_API_KEY);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_bad_api_key_for_http_401() {
+        assert_eq!(exit_code_for_error(&request_failed(401)), EXIT_BAD_API_KEY);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_service_unreachable_for_connection_failure() {
+        let err = Error::from_kind(ErrorKind::ConnectionFailed("refused".to_string()));
+
+        assert_eq!(exit_code_for_error(&err), EXIT_SERVICE_UNREACHABLE);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_service_unavailable_for_http_429_and_5xx() {
+        assert_eq!(exit_code_for_error(&request_failed(429)), EXIT_SERVICE_UNAVAILABLE);
+        assert_eq!(exit_code_for_error(&request_failed(500)), EXIT_SERVICE_UNAVAILABLE);
+        assert_eq!(exit_code_for_error(&request_failed(503)), EXIT_SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_unexpected_response_for_other_http_errors() {
+        assert_eq!(exit_code_for_error(&request_failed(404)), EXIT_UNEXPECTED_RESPONSE);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_unexpected_response_for_invalid_response() {
+        let err = Error::from_kind(ErrorKind::InvalidResponse("malformed JSON".to_string()));
+
+        assert_eq!(exit_code_for_error(&err), EXIT_UNEXPECTED_RESPONSE);
+    }
+
+    #[test]
+    fn exit_code_for_error_returns_job_failed_for_failed_decompilation() {
+        let err = Error::from_kind(ErrorKind::JobFailed("decompilation failed".to_string()));
+
+        assert_eq!(exit_code_for_error(&err), EXIT_JOB_FAILED);
+    }
+}
@@ -7,52 +7,92 @@ use clap::App;
 use clap::AppSettings;
 use clap::Arg;
 use clap::ArgMatches;
+use clap::SubCommand;
 
 use VERSION;
+use cache::ResultCache;
 use analysis::AnalysisArguments;
 use error::Result;
 use error::ResultExt;
 use file::File;
 use fileinfo::Fileinfo;
-use settings::Settings;
+use tools::report::JobReport;
+use tools::report::print_warnings;
+use tools::settings::connection_flags;
+use tools::settings::create_settings;
+use tools::settings::load_profile;
 
-fn parse_args<'a>(args: &[String]) -> ArgMatches<'a> {
-    App::new("fileinfo")
-        .version(VERSION)
-        .about("Analyzes the given binary file via retdec.com's API.")
-        .after_help("The output from the analysis is printed to the standard output.")
-        .setting(AppSettings::ColorNever)
-        .arg(Arg::with_name("FILE")
-            .required(true)
-            .help("Input binary file to be analyzed"))
-        .arg(Arg::with_name("api_key")
-            .short("k")
-            .long("api-key")
-            .takes_value(true)
-            .value_name("KEY")
-            // It is important not to require the API key by default because it
-            // enables the use of the RETDEC_API_KEY environment variable.
-            .help("API key to be used."))
-        .arg(Arg::with_name("api_url")
-            .short("u")
-            .long("api-url")
-            .takes_value(true)
-            .value_name("URL")
-            // It is important not to require the API URL by default because it
-            // enables the use of the RETDEC_API_URL environment variable.
-            .help("Custom URL to the retdec.com's API."))
-        .arg(Arg::with_name("output_format")
+/// Returns flags that map onto `AnalysisArguments`.
+fn analysis_flags<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("output_format")
             .short("f")
             .long("output-format")
             .takes_value(true)
             .value_name("FORMAT")
             .possible_values(&["plain", "json"])
             .default_value("plain")
-            .help("Format of the output from the analysis"))
-        .arg(Arg::with_name("verbose")
+            .help("Format of the output from the analysis"),
+        Arg::with_name("verbose")
             .short("v")
             .long("verbose")
-            .help("Print all available information about the input file"))
+            .help("Print all available information about the input file"),
+    ]
+}
+
+fn parse_args<'a>(args: &[String]) -> ArgMatches<'a> {
+    App::new("fileinfo")
+        .version(VERSION)
+        .about("Analyzes the given binary file via retdec.com's API.")
+        .after_help(
+            "The output from the analysis is printed to the standard output.\n\n\
+             To analyze a file without waiting for the result, use the submit, \
+             status, and fetch subcommands (see e.g. fileinfo help submit).\n\n\
+             With --json, a JSON document with the ID of the analysis, its \
+             status, timings, warnings, output, and error is printed instead."
+        )
+        .setting(AppSettings::ColorNever)
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(Arg::with_name("FILE")
+            .required(true)
+            .help("Input binary file to be analyzed"))
+        .args(&connection_flags())
+        .arg(Arg::with_name("no_cache")
+            .long("no-cache")
+            .help("Do not use the local cache of results (by default, results \
+                   are cached in RETDEC_CACHE_DIR or ~/.cache/retdec)"))
+        .arg(Arg::with_name("json")
+            .long("json")
+            .help("Print a JSON report (ID, status, timings, warnings, output, \
+                   and error) instead of the bare output"))
+        .args(&analysis_flags())
+        .subcommand(SubCommand::with_name("submit")
+            .about("Starts an analysis of the given file, prints its ID, and exits.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("FILE")
+                .required(true)
+                .help("Input binary file to be analyzed"))
+            .args(&analysis_flags())
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("status")
+            .about("Prints the status of the analysis with the given ID.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("ID")
+                .required(true)
+                .help("ID of the analysis (printed by submit)"))
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("fetch")
+            .about("Prints the output of the analysis with the given ID.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("ID")
+                .required(true)
+                .help("ID of the analysis (printed by submit)"))
+            .arg(Arg::with_name("wait")
+                .short("w")
+                .long("wait")
+                .help("Wait until the analysis finishes (by default, fetching \
+                       the output of a running analysis fails)"))
+            .args(&connection_flags()))
         .get_matches_from(args)
 }
 
@@ -63,32 +103,117 @@ fn print_analysis_result(output: &str) -> Result<()> {
     Ok(())
 }
 
-fn run(args: &[String]) -> Result<()> {
-    let args = parse_args(args);
+fn create_analysis_args(args: &ArgMatches) -> Result<AnalysisArguments> {
+    let input_file = args.value_of("FILE")
+        .expect("no FILE - this should never happen");
+    Ok(
+        AnalysisArguments::new()
+            .with_output_format(
+                args.value_of("output_format")
+                    .expect("no output_format - this should never happen")
+            )
+            .with_verbose(args.is_present("verbose"))
+            .with_input_file(File::from_path(input_file)?)
+    )
+}
+
+/// Creates a fileinfo and prints warnings concerning its settings to the
+/// standard error.
+fn create_fileinfo(args: &ArgMatches) -> Result<Fileinfo> {
+    let mut warnings = Vec::new();
+    let settings = create_settings(args, load_profile(args)?.as_ref(), &mut warnings)?;
+    print_warnings(&warnings);
+    Ok(Fileinfo::new(settings))
+}
+
+fn submit(args: &ArgMatches) -> Result<()> {
+    let analysis_args = create_analysis_args(args)?;
+    let fileinfo = create_fileinfo(args)?;
+    let analysis = fileinfo.start_analysis(analysis_args)?;
+    println!("{}", analysis.id());
+    Ok(())
+}
+
+fn print_status(args: &ArgMatches) -> Result<()> {
+    let id = args.value_of("ID")
+        .expect("no ID - this should never happen");
+
+    let fileinfo = create_fileinfo(args)?;
+    let mut analysis = fileinfo.attach_to_analysis(id);
+    if !analysis.has_finished()? {
+        println!("{}: running", id);
+    } else if analysis.has_succeeded()? {
+        println!("{}: succeeded", id);
+    } else {
+        println!("{}: failed ({})", id, analysis.error().unwrap_or("unknown error"));
+    }
+    Ok(())
+}
+
+fn fetch(args: &ArgMatches) -> Result<()> {
+    let id = args.value_of("ID")
+        .expect("no ID - this should never happen");
 
-    let mut settings = Settings::new();
-    if let Some(api_key) = args.value_of("api_key") {
-        settings.set_api_key(api_key);
+    let fileinfo = create_fileinfo(args)?;
+    let mut analysis = fileinfo.attach_to_analysis(id);
+    if args.is_present("wait") {
+        analysis.wait_until_finished()?;
+    } else if !analysis.has_finished()? {
+        bail!("analysis {} has not finished yet (use --wait to wait for it)", id);
     }
-    if let Some(api_url) = args.value_of("api_url") {
-        settings.set_api_url(api_url);
+    let output = analysis.get_output()?;
+    print_analysis_result(&output)
+}
+
+/// Analyzes the input file, recording the ID of the analysis into the given
+/// report, and returns the output from the analysis.
+fn analyze_into(fileinfo: &Fileinfo,
+                args: &ArgMatches,
+                report: &mut JobReport) -> Result<String> {
+    let mut analysis = fileinfo.start_analysis(create_analysis_args(args)?)?;
+    report.set_id(analysis.id());
+    analysis.wait_until_finished()?;
+    analysis.get_output()
+}
+
+fn analyze(args: &ArgMatches) -> Result<()> {
+    let json = args.is_present("json");
+    let mut warnings = Vec::new();
+    let settings = create_settings(args, load_profile(args)?.as_ref(), &mut warnings)?;
+    if !json {
+        print_warnings(&warnings);
+    }
+    let mut fileinfo = Fileinfo::new(settings);
+    if !args.is_present("no_cache") {
+        if let Some(cache_dir) = ResultCache::default_dir() {
+            fileinfo = fileinfo.with_cache(ResultCache::new(cache_dir));
+        }
     }
     let input_file = args.value_of("FILE")
         .expect("no FILE - this should never happen");
+    let mut report = JobReport::start(input_file);
+    report.add_warnings(&warnings);
+    let result = analyze_into(&fileinfo, args, &mut report);
+    if !json {
+        return print_analysis_result(&result?);
+    }
+    let result = result.map(|output| report.set_output(output));
+    match result {
+        Ok(()) => report.succeed(),
+        Err(ref err) => report.fail(err),
+    }
+    report.print_json();
+    result.chain_err(|| format!("failed to analyze {}", input_file))
+}
 
-    let fileinfo = Fileinfo::new(settings);
-    let args = AnalysisArguments::new()
-        .with_output_format(
-            args.value_of("output_format")
-                .expect("no output_format - this should never happen")
-        )
-        .with_verbose(args.is_present("verbose"))
-        .with_input_file(File::from_path(&input_file)?);
-    let mut analysis = fileinfo.start_analysis(args)?;
-    analysis.wait_until_finished()?;
-    let output = analysis.get_output()?;
-    print_analysis_result(&output)?;
-    Ok(())
+fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args);
+    match args.subcommand() {
+        ("submit", Some(args)) => submit(args),
+        ("status", Some(args)) => print_status(args),
+        ("fetch", Some(args)) => fetch(args),
+        _ => analyze(&args),
+    }
 }
 
 generate_main_for_tool!(run);
@@ -116,6 +241,63 @@ mod tests {
         assert_eq!(args.value_of("FILE"), Some("file.exe"));
     }
 
+    #[test]
+    fn parse_args_correctly_parses_submit_subcommand() {
+        let args = parse_args(&args!["submit", "-f", "json", "-k", "KEY", "file.exe"]);
+        let (name, args) = args.subcommand();
+        let args = args.unwrap();
+        assert_eq!(name, "submit");
+        assert_eq!(args.value_of("FILE"), Some("file.exe"));
+        assert_eq!(args.value_of("output_format"), Some("json"));
+        assert_eq!(args.value_of("api_key"), Some("KEY"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_status_subcommand() {
+        let args = parse_args(&args!["status", "ID"]);
+        let (name, args) = args.subcommand();
+        assert_eq!(name, "status");
+        assert_eq!(args.unwrap().value_of("ID"), Some("ID"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_fetch_subcommand() {
+        let args = parse_args(&args!["fetch", "--wait", "ID"]);
+        let (name, args) = args.subcommand();
+        let args = args.unwrap();
+        assert_eq!(name, "fetch");
+        assert_eq!(args.value_of("ID"), Some("ID"));
+        assert!(args.is_present("wait"));
+    }
+
+    #[test]
+    fn parse_args_does_not_use_subcommand_for_input_file() {
+        let args = parse_args(&args!["file.exe"]);
+        assert_eq!(args.subcommand_name(), None);
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_json() {
+        let args = parse_args(&args!["--json", "file.exe"]);
+        assert!(args.is_present("json"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_config_and_profile() {
+        let args = parse_args(&args!["--config", "config.ini", "--profile", "work", "file.exe"]);
+        assert_eq!(args.value_of("config"), Some("config.ini"));
+        assert_eq!(args.value_of("profile"), Some("work"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_api_key_file_and_command() {
+        let args = parse_args(&args!["--api-key-file", "key.txt", "file.exe"]);
+        assert_eq!(args.value_of("api_key_file"), Some("key.txt"));
+
+        let args = parse_args(&args!["--api-key-command", "pass show retdec", "file.exe"]);
+        assert_eq!(args.value_of("api_key_command"), Some("pass show retdec"));
+    }
+
     #[test]
     fn parse_args_correctly_parses_api_key_short_form() {
         let args = parse_args(&args!["-k", "KEY", "file.exe"]);
@@ -140,6 +322,36 @@ mod tests {
         assert_eq!(args.value_of("api_url"), Some("URL"));
     }
 
+    #[test]
+    fn parse_args_does_not_allow_plain_http_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert!(!args.is_present("allow_plain_http"));
+    }
+
+    #[test]
+    fn parse_args_uses_cache_by_default() {
+        let args = parse_args(&args!["file.exe"]);
+        assert!(!args.is_present("no_cache"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_no_cache() {
+        let args = parse_args(&args!["--no-cache", "file.exe"]);
+        assert!(args.is_present("no_cache"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_allow_plain_http() {
+        let args = parse_args(&args!["--allow-plain-http", "file.exe"]);
+        assert!(args.is_present("allow_plain_http"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_verbose_http() {
+        let args = parse_args(&args!["--verbose-http", "file.exe"]);
+        assert!(args.is_present("verbose_http"));
+    }
+
     #[test]
     fn parse_args_sets_plain_as_default_output_format() {
         let args = parse_args(&args!["file.exe"]);
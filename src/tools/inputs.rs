@@ -0,0 +1,366 @@
+//! Collection of input files given to the tools on the command line.
+
+use std::fs;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use error::Result;
+use error::ResultExt;
+
+/// An input file to be processed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InputFile {
+    /// Path to the file.
+    pub path: PathBuf,
+
+    /// Path to the file relative to the directory in which it was found (or
+    /// just the name of the file when it was given directly).
+    ///
+    /// When more paths were given, the path starts with the name of the
+    /// given directory, so files from different directories do not clash.
+    pub relative_path: PathBuf,
+}
+
+/// A shell-like pattern for matching paths.
+///
+/// Supports `*` (any sequence of characters except `/`), `**` (any sequence
+/// of characters, including `/`), and `?` (any single character except
+/// `/`). A pattern without `/` is matched against the name of the file only.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Glob {
+    pattern: Vec<char>,
+    match_name_only: bool,
+}
+
+impl Glob {
+    /// Creates a new pattern.
+    pub fn new(pattern: &str) -> Self {
+        Glob {
+            pattern: pattern.chars().collect(),
+            match_name_only: !pattern.contains('/'),
+        }
+    }
+
+    /// Does the given relative path match the pattern?
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        let path = if self.match_name_only {
+            relative_path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        } else {
+            path_with_slashes(relative_path)
+        };
+        let path: Vec<char> = path.chars().collect();
+        glob_matches(&self.pattern, &path)
+    }
+}
+
+/// Include and exclude patterns for files found in directories.
+#[derive(Clone, Debug, Default)]
+pub struct InputFilter {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl InputFilter {
+    /// Creates a filter that accepts all files.
+    pub fn new() -> Self {
+        InputFilter::default()
+    }
+
+    /// Accepts only files matching the given pattern (or any other include
+    /// pattern).
+    pub fn with_include(mut self, pattern: &str) -> Self {
+        self.include.push(Glob::new(pattern));
+        self
+    }
+
+    /// Rejects files matching the given pattern.
+    pub fn with_exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(Glob::new(pattern));
+        self
+    }
+
+    /// Does the filter accept a file with the given relative path?
+    pub fn accepts(&self, relative_path: &Path) -> bool {
+        let included = self.include.is_empty() ||
+            self.include.iter().any(|glob| glob.matches(relative_path));
+        included && !self.exclude.iter().any(|glob| glob.matches(relative_path))
+    }
+}
+
+/// Collects input files from the given paths.
+///
+/// Files are used as they are. Directories are searched (in alphabetical
+/// order) only when `recursive` is `true`; otherwise, they result in an
+/// error. Symbolic links to directories are skipped, so links pointing back
+/// to their parent directory do not cause an infinite recursion. The filter
+/// is applied to files found in directories.
+pub fn collect_input_files<P>(paths: &[P],
+                              recursive: bool,
+                              filter: &InputFilter) -> Result<Vec<InputFile>>
+    where P: AsRef<Path>
+{
+    let mut input_files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            if !recursive {
+                bail!("{:?} is a directory (use --recursive to process directories)", path);
+            }
+            let relative_dir = if paths.len() > 1 {
+                PathBuf::from(dir_name(path)?)
+            } else {
+                PathBuf::new()
+            };
+            collect_files_in_dir(path, &relative_dir, filter, &mut input_files)?;
+        } else if path.is_file() {
+            let name = path.file_name()
+                .ok_or_else(|| format!("no file name in {:?}", path))?;
+            input_files.push(InputFile {
+                path: path.to_path_buf(),
+                relative_path: PathBuf::from(name),
+            });
+        } else {
+            bail!("{:?} does not exist", path);
+        }
+    }
+    Ok(input_files)
+}
+
+fn collect_files_in_dir(dir: &Path,
+                        relative_dir: &Path,
+                        filter: &InputFilter,
+                        input_files: &mut Vec<InputFile>) -> Result<()> {
+    let mut entries = Vec::new();
+    let dir_entries = fs::read_dir(dir)
+        .chain_err(|| format!("failed to read {:?}", dir))?;
+    for entry in dir_entries {
+        let entry = entry.chain_err(|| format!("failed to read {:?}", dir))?;
+        // Unlike Path::is_dir(), the file type does not follow symbolic
+        // links, so linked directories are not entered.
+        let file_type = entry.file_type()
+            .chain_err(|| format!("failed to read the type of {:?}", entry.path()))?;
+        entries.push((entry.path(), file_type.is_dir()));
+    }
+    entries.sort();
+
+    for (path, is_dir) in entries {
+        let relative_path = match path.file_name() {
+            Some(name) => relative_dir.join(name),
+            None => continue,
+        };
+        if is_dir {
+            collect_files_in_dir(&path, &relative_path, filter, input_files)?;
+        } else if path.is_file() && filter.accepts(&relative_path) {
+            input_files.push(InputFile {
+                path: path,
+                relative_path: relative_path,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of the given directory, resolving paths like `.`.
+fn dir_name(dir: &Path) -> Result<String> {
+    if let Some(name) = dir.file_name() {
+        return Ok(name.to_string_lossy().into_owned());
+    }
+    let canonical_dir = dir.canonicalize()
+        .chain_err(|| format!("failed to resolve {:?}", dir))?;
+    let name = canonical_dir.file_name()
+        .ok_or_else(|| format!("no directory name in {:?}", dir))?;
+    Ok(name.to_string_lossy().into_owned())
+}
+
+fn path_with_slashes(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn glob_matches(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some(&'*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            // `**/` also matches no directory at all.
+            if rest.first() == Some(&'/') && glob_matches(&rest[1..], path) {
+                return true;
+            }
+            (0..path.len() + 1).any(|i| glob_matches(rest, &path[i..]))
+        }
+        Some(&'*') => {
+            let rest = &pattern[1..];
+            for i in 0..path.len() + 1 {
+                if glob_matches(rest, &path[i..]) {
+                    return true;
+                }
+                if i < path.len() && path[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some(&'?') => {
+            !path.is_empty() && path[0] != '/' && glob_matches(&pattern[1..], &path[1..])
+        }
+        Some(&c) => {
+            !path.is_empty() && path[0] == c && glob_matches(&pattern[1..], &path[1..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use file::File;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        Glob::new(pattern).matches(Path::new(path))
+    }
+
+    #[test]
+    fn glob_without_slash_matches_file_name_only() {
+        assert!(matches("*.exe", "a.exe"));
+        assert!(matches("*.exe", "dir/a.exe"));
+        assert!(!matches("*.exe", "a.dll"));
+        assert!(matches("a?.exe", "ab.exe"));
+        assert!(!matches("a?.exe", "a.exe"));
+    }
+
+    #[test]
+    fn glob_with_slash_matches_whole_relative_path() {
+        assert!(matches("dir/*.exe", "dir/a.exe"));
+        assert!(!matches("dir/*.exe", "dir/sub/a.exe"));
+        assert!(!matches("dir/*.exe", "a.exe"));
+    }
+
+    #[test]
+    fn glob_double_star_matches_any_number_of_directories() {
+        assert!(matches("**/*.exe", "a.exe"));
+        assert!(matches("**/*.exe", "dir/sub/a.exe"));
+        assert!(matches("dir/**", "dir/sub/a.exe"));
+        assert!(!matches("dir/**", "other/a.exe"));
+    }
+
+    #[test]
+    fn filter_without_patterns_accepts_everything() {
+        assert!(InputFilter::new().accepts(Path::new("a.exe")));
+    }
+
+    #[test]
+    fn filter_accepts_only_included_files_that_are_not_excluded() {
+        let filter = InputFilter::new()
+            .with_include("*.exe")
+            .with_include("*.dll")
+            .with_exclude("test_*");
+
+        assert!(filter.accepts(Path::new("a.exe")));
+        assert!(filter.accepts(Path::new("b.dll")));
+        assert!(!filter.accepts(Path::new("c.txt")));
+        assert!(!filter.accepts(Path::new("test_a.exe")));
+    }
+
+    fn create_file(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::from_content_with_name(b"content", "").save_as(path).unwrap();
+    }
+
+    #[test]
+    fn collect_input_files_returns_given_files() {
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+        let path = tmp_dir.path().join("a.exe");
+        create_file(&path);
+
+        let input_files = collect_input_files(&[&path], false, &InputFilter::new()).unwrap();
+
+        assert_eq!(input_files, vec![InputFile {
+            path: path,
+            relative_path: PathBuf::from("a.exe"),
+        }]);
+    }
+
+    #[test]
+    fn collect_input_files_returns_error_for_directory_when_not_recursive() {
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+
+        let result = collect_input_files(&[tmp_dir.path()], false, &InputFilter::new());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn collect_input_files_returns_error_for_nonexisting_path() {
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+
+        let result = collect_input_files(
+            &[tmp_dir.path().join("none.exe")], false, &InputFilter::new()
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn collect_input_files_searches_directories_recursively_and_applies_filter() {
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+        create_file(&tmp_dir.path().join("b.exe"));
+        create_file(&tmp_dir.path().join("a.txt"));
+        create_file(&tmp_dir.path().join("sub").join("c.exe"));
+        let filter = InputFilter::new().with_include("*.exe");
+
+        let input_files = collect_input_files(&[tmp_dir.path()], true, &filter).unwrap();
+
+        let relative_paths: Vec<_> = input_files.iter()
+            .map(|f| f.relative_path.clone())
+            .collect();
+        assert_eq!(relative_paths, vec![
+            PathBuf::from("b.exe"),
+            Path::new("sub").join("c.exe"),
+        ]);
+        assert_eq!(input_files[1].path, tmp_dir.path().join("sub").join("c.exe"));
+    }
+
+    #[test]
+    fn collect_input_files_prefixes_relative_paths_with_directory_names_for_more_paths() {
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+        create_file(&tmp_dir.path().join("a").join("x.exe"));
+        create_file(&tmp_dir.path().join("b").join("x.exe"));
+
+        let input_files = collect_input_files(
+            &[tmp_dir.path().join("a"), tmp_dir.path().join("b")], true, &InputFilter::new()
+        ).unwrap();
+
+        let relative_paths: Vec<_> = input_files.iter()
+            .map(|f| f.relative_path.clone())
+            .collect();
+        assert_eq!(relative_paths, vec![
+            Path::new("a").join("x.exe"),
+            Path::new("b").join("x.exe"),
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_input_files_skips_symlinked_directories() {
+        use std::os::unix::fs::symlink;
+
+        let tmp_dir = TempDir::new("retdec-inputs-test").unwrap();
+        create_file(&tmp_dir.path().join("a.exe"));
+        symlink(".", tmp_dir.path().join("self")).unwrap();
+
+        let input_files = collect_input_files(&[tmp_dir.path()], true, &InputFilter::new()).unwrap();
+
+        assert_eq!(input_files.len(), 1);
+        assert_eq!(input_files[0].relative_path, PathBuf::from("a.exe"));
+    }
+}
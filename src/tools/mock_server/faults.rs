@@ -0,0 +1,337 @@
+//! Injection of faults into responses of the mock server.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use error::Result;
+
+/// Names of endpoints into which faults can be injected.
+pub const ENDPOINTS: &'static [&'static str] = &[
+    "test",
+    "echo",
+    "start-decompilation",
+    "decompilation-status",
+    "decompilation-output",
+    "start-analysis",
+    "analysis-status",
+    "analysis-output",
+];
+
+/// A fault that can be injected into a response.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fault {
+    /// Respond with the given error status code (e.g. 401, 429, or 500).
+    Status(u16),
+
+    /// Send a malformed JSON body.
+    MalformedJson,
+
+    /// Omit the `Content-Disposition` header.
+    MissingContentDisposition,
+
+    /// Delay the response by the given duration.
+    Slow(Duration),
+
+    /// Close the connection in the middle of the body.
+    DropConnection,
+}
+
+/// When a fault is injected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Trigger {
+    /// Into every request.
+    Always,
+
+    /// Into the given requests (1-based, counted per endpoint).
+    At(Vec<u64>),
+
+    /// Into every n-th request (counted per endpoint).
+    Every(u64),
+
+    /// At random with the given probability (0.0 to 1.0).
+    Random(f64),
+}
+
+/// A rule describing which fault to inject into which endpoint and when.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultRule {
+    /// Name of the endpoint (see [`ENDPOINTS`](constant.ENDPOINTS.html)) or
+    /// `*` for all endpoints.
+    pub endpoint: String,
+
+    /// The fault.
+    pub fault: Fault,
+
+    /// When the fault is injected.
+    pub trigger: Trigger,
+}
+
+impl FaultRule {
+    /// Parses a rule from its textual representation.
+    ///
+    /// The representation is `ENDPOINT:FAULT[:WHEN]`, where
+    ///
+    /// * `ENDPOINT` is one of [`ENDPOINTS`](constant.ENDPOINTS.html) or `*`,
+    /// * `FAULT` is `401`, `429`, `500` (or any other 4xx/5xx status code),
+    ///   `malformed-json`, `no-content-disposition`, `slow=MILLISECONDS`, or
+    ///   `drop`,
+    /// * `WHEN` is `always` (the default), `at=N,M,...`, `every=N`, or
+    ///   `p=PROBABILITY`.
+    ///
+    /// For example, `decompilation-status:500:at=2,3` makes the second and
+    /// third status request fail.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut parts = spec.splitn(3, ':');
+        let endpoint = parts.next().unwrap_or("").trim();
+        if endpoint != "*" && !ENDPOINTS.contains(&endpoint) {
+            bail!("unknown endpoint '{}' in fault '{}' (expected one of: *, {})",
+                  endpoint, spec, ENDPOINTS.join(", "));
+        }
+        let fault = match parts.next() {
+            Some(fault) => parse_fault(fault.trim())
+                .ok_or_else(|| format!("invalid fault in '{}'", spec))?,
+            None => bail!("missing fault in '{}' (expected ENDPOINT:FAULT[:WHEN])", spec),
+        };
+        let trigger = match parts.next() {
+            Some(trigger) => parse_trigger(trigger.trim())
+                .ok_or_else(|| format!("invalid trigger in '{}'", spec))?,
+            None => Trigger::Always,
+        };
+        Ok(FaultRule {
+            endpoint: endpoint.to_string(),
+            fault: fault,
+            trigger: trigger,
+        })
+    }
+}
+
+fn parse_fault(fault: &str) -> Option<Fault> {
+    if let Ok(status) = fault.parse::<u16>() {
+        return if status >= 400 && status < 600 { Some(Fault::Status(status)) } else { None };
+    }
+    match fault {
+        "malformed-json" => Some(Fault::MalformedJson),
+        "no-content-disposition" => Some(Fault::MissingContentDisposition),
+        "drop" => Some(Fault::DropConnection),
+        _ if fault.starts_with("slow=") => {
+            fault["slow=".len()..].parse().ok()
+                .map(|millis| Fault::Slow(Duration::from_millis(millis)))
+        }
+        _ => None,
+    }
+}
+
+fn parse_trigger(trigger: &str) -> Option<Trigger> {
+    if trigger == "always" {
+        Some(Trigger::Always)
+    } else if trigger.starts_with("at=") {
+        let requests: Option<Vec<u64>> = trigger["at=".len()..].split(',')
+            .map(|n| n.trim().parse().ok())
+            .collect();
+        requests.map(Trigger::At)
+    } else if trigger.starts_with("every=") {
+        match trigger["every=".len()..].parse() {
+            Ok(0) | Err(_) => None,
+            Ok(n) => Some(Trigger::Every(n)),
+        }
+    } else if trigger.starts_with("p=") {
+        match trigger["p=".len()..].parse::<f64>() {
+            Ok(p) if p >= 0.0 && p <= 1.0 => Some(Trigger::Random(p)),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Decides which faults to inject into which requests.
+///
+/// Random triggers use a pseudo-random generator with a fixed seed, so runs
+/// with the same seed and the same sequence of requests inject the same
+/// faults.
+#[derive(Debug)]
+pub struct FaultInjector {
+    rules: Vec<FaultRule>,
+    state: Mutex<InjectorState>,
+}
+
+#[derive(Debug)]
+struct InjectorState {
+    request_counts: HashMap<String, u64>,
+    rng: XorShiftRng,
+}
+
+impl FaultInjector {
+    /// Creates an injector with the given rules and seed.
+    pub fn new(rules: Vec<FaultRule>, seed: u64) -> Self {
+        FaultInjector {
+            rules: rules,
+            state: Mutex::new(InjectorState {
+                request_counts: HashMap::new(),
+                rng: XorShiftRng::new(seed),
+            }),
+        }
+    }
+
+    /// Creates an injector that does not inject any faults.
+    pub fn none() -> Self {
+        Self::new(Vec::new(), 0)
+    }
+
+    /// Records a request to the given endpoint and returns faults to be
+    /// injected into its response.
+    pub fn faults_for(&self, endpoint: &str) -> Vec<Fault> {
+        let mut state = self.state.lock().expect("poisoned lock");
+        let count = {
+            let count = state.request_counts.entry(endpoint.to_string()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        let mut faults = Vec::new();
+        for rule in &self.rules {
+            if rule.endpoint != "*" && rule.endpoint != endpoint {
+                continue;
+            }
+            let triggered = match rule.trigger {
+                Trigger::Always => true,
+                Trigger::At(ref requests) => requests.contains(&count),
+                Trigger::Every(n) => count % n == 0,
+                Trigger::Random(p) => state.rng.next_f64() < p,
+            };
+            if triggered {
+                faults.push(rule.fault.clone());
+            }
+        }
+        faults
+    }
+}
+
+impl Default for FaultInjector {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// A small pseudo-random generator (xorshift64*), so that random faults are
+/// reproducible without depending on an external crate.
+#[derive(Debug)]
+struct XorShiftRng {
+    state: u64,
+}
+
+impl XorShiftRng {
+    fn new(seed: u64) -> Self {
+        // The state must never be zero.
+        XorShiftRng { state: seed ^ 0x9E37_79B9_7F4A_7C15 }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_returns_correct_rule_for_status_fault() {
+        let rule = FaultRule::parse("decompilation-status:500:at=2,3").unwrap();
+
+        assert_eq!(rule, FaultRule {
+            endpoint: "decompilation-status".to_string(),
+            fault: Fault::Status(500),
+            trigger: Trigger::At(vec![2, 3]),
+        });
+    }
+
+    #[test]
+    fn parse_uses_always_as_default_trigger() {
+        let rule = FaultRule::parse("*:drop").unwrap();
+
+        assert_eq!(rule.trigger, Trigger::Always);
+    }
+
+    #[test]
+    fn parse_correctly_parses_all_faults() {
+        let fault = |spec: &str| FaultRule::parse(spec).unwrap().fault;
+
+        assert_eq!(fault("test:401"), Fault::Status(401));
+        assert_eq!(fault("test:429"), Fault::Status(429));
+        assert_eq!(fault("test:malformed-json"), Fault::MalformedJson);
+        assert_eq!(fault("test:no-content-disposition"), Fault::MissingContentDisposition);
+        assert_eq!(fault("test:slow=250"), Fault::Slow(Duration::from_millis(250)));
+        assert_eq!(fault("test:drop"), Fault::DropConnection);
+    }
+
+    #[test]
+    fn parse_correctly_parses_all_triggers() {
+        let trigger = |spec: &str| FaultRule::parse(spec).unwrap().trigger;
+
+        assert_eq!(trigger("test:500:always"), Trigger::Always);
+        assert_eq!(trigger("test:500:every=3"), Trigger::Every(3));
+        assert_eq!(trigger("test:500:p=0.25"), Trigger::Random(0.25));
+    }
+
+    #[test]
+    fn parse_returns_error_for_invalid_rules() {
+        assert!(FaultRule::parse("unknown:500").is_err());
+        assert!(FaultRule::parse("test").is_err());
+        assert!(FaultRule::parse("test:200").is_err());
+        assert!(FaultRule::parse("test:explode").is_err());
+        assert!(FaultRule::parse("test:500:every=0").is_err());
+        assert!(FaultRule::parse("test:500:p=2").is_err());
+        assert!(FaultRule::parse("test:500:sometimes").is_err());
+    }
+
+    #[test]
+    fn injector_without_rules_injects_nothing() {
+        let injector = FaultInjector::none();
+
+        assert!(injector.faults_for("test").is_empty());
+    }
+
+    #[test]
+    fn injector_injects_scheduled_faults_per_endpoint() {
+        let injector = FaultInjector::new(
+            vec![FaultRule::parse("decompilation-status:500:at=2").unwrap()],
+            0
+        );
+
+        assert!(injector.faults_for("decompilation-status").is_empty());
+        assert!(injector.faults_for("decompilation-output").is_empty());
+        assert_eq!(injector.faults_for("decompilation-status"), vec![Fault::Status(500)]);
+        assert!(injector.faults_for("decompilation-status").is_empty());
+    }
+
+    #[test]
+    fn injector_injects_faults_into_every_nth_request() {
+        let injector = FaultInjector::new(vec![FaultRule::parse("*:429:every=2").unwrap()], 0);
+
+        assert!(injector.faults_for("test").is_empty());
+        assert_eq!(injector.faults_for("test"), vec![Fault::Status(429)]);
+        assert!(injector.faults_for("echo").is_empty());
+    }
+
+    #[test]
+    fn injector_with_same_seed_injects_same_random_faults() {
+        let rules = vec![FaultRule::parse("*:500:p=0.5").unwrap()];
+        let injector1 = FaultInjector::new(rules.clone(), 42);
+        let injector2 = FaultInjector::new(rules, 42);
+
+        let faults1: Vec<_> = (0..100).map(|_| injector1.faults_for("test")).collect();
+        let faults2: Vec<_> = (0..100).map(|_| injector2.faults_for("test")).collect();
+
+        assert_eq!(faults1, faults2);
+        let injected = faults1.iter().filter(|f| !f.is_empty()).count();
+        assert!(injected > 20 && injected < 80);
+    }
+}
@@ -0,0 +1,225 @@
+//! A local server mimicking the retdec.com's API.
+//!
+//! The server implements the decompiler, fileinfo, and test services, so the
+//! library and the provided tools can be used offline by pointing their API
+//! URL to it:
+//!
+//! ```text
+//! $ retdec-mock-server --port 8000
+//! $ decompiler -k KEY -u http://127.0.0.1:8000/service/api --allow-plain-http hello.exe
+//! ```
+
+use clap::App;
+use clap::AppSettings;
+use clap::Arg;
+use clap::ArgMatches;
+
+use VERSION;
+use error::Result;
+
+pub mod faults;
+pub mod server;
+pub mod store;
+pub mod timeline;
+
+mod upload;
+
+pub use self::faults::FaultInjector;
+pub use self::faults::FaultRule;
+pub use self::server::MockServer;
+pub use self::server::RunningMockServer;
+pub use self::store::ResultStore;
+pub use self::timeline::Timeline;
+
+fn parse_args<'a>(args: &[String]) -> ArgMatches<'a> {
+    App::new("retdec-mock-server")
+        .version(VERSION)
+        .about("Runs a local server mimicking the retdec.com's API.")
+        .after_help(
+            "Canned results are read from subdirectories of the results directory \
+             named after the SHA-256 digest or the name of the input file. Each \
+             subdirectory may contain files named after the outputs (hll, dsm, cg, \
+             archive, binary, output), a directory named cfgs with control-flow \
+             graphs (one file per function), and a file named error, which makes \
+             the job fail. Without a canned result, the hll, dsm, and output \
+             outputs are generated and the control-flow graph of main is \
+             generated when cfgs are requested; a requested cg or archive is \
+             reported as failed to generate, and binary is not available.\n\n\
+             Faults are given as ENDPOINT:FAULT[:WHEN], where ENDPOINT is one of \
+             test, echo, start-decompilation, decompilation-status, \
+             decompilation-output, start-analysis, analysis-status, \
+             analysis-output, or * (all endpoints); FAULT is 401, 429, 500 \
+             (or another error status code), malformed-json, \
+             no-content-disposition, slow=MILLISECONDS, or drop (close the \
+             connection in the middle of the body); and WHEN is always (the \
+             default), at=N,M,... (the given requests to the endpoint), every=N, \
+             or p=PROBABILITY (at random, see --fault-seed). For example, \
+             --fault decompilation-status:500:at=2,3"
+        )
+        .setting(AppSettings::ColorNever)
+        .arg(Arg::with_name("host")
+            .long("host")
+            .takes_value(true)
+            .value_name("HOST")
+            .default_value("127.0.0.1")
+            .help("Address on which the server listens"))
+        .arg(Arg::with_name("port")
+            .short("p")
+            .long("port")
+            .takes_value(true)
+            .value_name("PORT")
+            .default_value("8000")
+            .help("Port on which the server listens (0 picks a free port)"))
+        .arg(Arg::with_name("api_key")
+            .short("k")
+            .long("api-key")
+            .takes_value(true)
+            .value_name("KEY")
+            .help("Accept only the given API key (default: accept any key)"))
+        .arg(Arg::with_name("results_dir")
+            .short("r")
+            .long("results-dir")
+            .takes_value(true)
+            .value_name("DIR")
+            .help("Directory with canned results"))
+        .arg(Arg::with_name("timeline")
+            .short("t")
+            .long("timeline")
+            .takes_value(true)
+            .value_name("PHASES")
+            .help("Progress timeline of jobs as NAME=MILLISECONDS,... \
+                   (default: Pre-Processing=200,Front-End=400,Middle-End=400,Back-End=400)"))
+        .arg(Arg::with_name("fault")
+            .short("f")
+            .long("fault")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("RULE")
+            .help("Inject a fault into responses (can be given multiple times)"))
+        .arg(Arg::with_name("fault_seed")
+            .long("fault-seed")
+            .takes_value(true)
+            .value_name("SEED")
+            .default_value("0")
+            .help("Seed for faults injected at random"))
+        .get_matches_from(args)
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let args = parse_args(args);
+
+    let mut server = MockServer::new();
+    if let Some(api_key) = args.value_of("api_key") {
+        server = server.with_api_key(api_key);
+    }
+    if let Some(results_dir) = args.value_of("results_dir") {
+        server = server.with_result_store(ResultStore::from_dir(results_dir));
+    }
+    if let Some(timeline) = args.value_of("timeline") {
+        server = server.with_timeline(Timeline::parse(timeline)?);
+    }
+    let fault_rules = args.values_of("fault")
+        .map(|rules| rules.map(FaultRule::parse).collect::<Result<Vec<_>>>())
+        .unwrap_or_else(|| Ok(Vec::new()))?;
+    let fault_seed: u64 = args.value_of("fault_seed")
+        .expect("no fault_seed - this should never happen")
+        .parse()
+        .map_err(|_| "invalid fault seed")?;
+    server = server.with_faults(FaultInjector::new(fault_rules, fault_seed));
+    let port: u16 = args.value_of("port")
+        .expect("no port - this should never happen")
+        .parse()
+        .map_err(|_| "invalid port")?;
+    let host = args.value_of("host")
+        .expect("no host - this should never happen");
+
+    let server = server.start(&format!("{}:{}", host, port))?;
+    println!("Serving the API on {}", server.api_url());
+    server.wait();
+    Ok(())
+}
+
+generate_main_for_tool!(run);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! args {
+        ($($arg:expr),*) => {
+            {
+                let mut args = Vec::new();
+                args.push("retdec-mock-server".to_string());
+                $(
+                    args.push($arg.to_string());
+                )*
+                args
+            }
+        }
+    }
+
+    #[test]
+    fn parse_args_sets_default_host_and_port() {
+        let args = parse_args(&args![]);
+        assert_eq!(args.value_of("host"), Some("127.0.0.1"));
+        assert_eq!(args.value_of("port"), Some("8000"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_host() {
+        let args = parse_args(&args!["--host", "0.0.0.0"]);
+        assert_eq!(args.value_of("host"), Some("0.0.0.0"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_port_short_form() {
+        let args = parse_args(&args!["-p", "1234"]);
+        assert_eq!(args.value_of("port"), Some("1234"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_port_long_form() {
+        let args = parse_args(&args!["--port", "1234"]);
+        assert_eq!(args.value_of("port"), Some("1234"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_api_key() {
+        let args = parse_args(&args!["--api-key", "KEY"]);
+        assert_eq!(args.value_of("api_key"), Some("KEY"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_results_dir() {
+        let args = parse_args(&args!["--results-dir", "results"]);
+        assert_eq!(args.value_of("results_dir"), Some("results"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_timeline() {
+        let args = parse_args(&args!["--timeline", "A=100"]);
+        assert_eq!(args.value_of("timeline"), Some("A=100"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_multiple_faults() {
+        let args = parse_args(&args!["-f", "test:500", "--fault", "echo:drop"]);
+        assert_eq!(
+            args.values_of("fault").unwrap().collect::<Vec<_>>(),
+            vec!["test:500", "echo:drop"]
+        );
+    }
+
+    #[test]
+    fn parse_args_sets_zero_as_default_fault_seed() {
+        let args = parse_args(&args![]);
+        assert_eq!(args.value_of("fault_seed"), Some("0"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_fault_seed() {
+        let args = parse_args(&args!["--fault-seed", "42"]);
+        assert_eq!(args.value_of("fault_seed"), Some("42"));
+    }
+}
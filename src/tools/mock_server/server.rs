@@ -0,0 +1,1016 @@
+//! The mock server itself.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use hyper::Url as HyperUrl;
+use hyper::header::Authorization;
+use hyper::header::Basic;
+use hyper::header::Connection;
+use hyper::header::ContentLength;
+use hyper::server::Handler;
+use hyper::server::Listening;
+use hyper::server::Request as HyperRequest;
+use hyper::server::Response as HyperResponse;
+use hyper::server::Server as HyperServer;
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use json::JsonValue;
+
+use error::Result;
+use error::ResultExt;
+use file::File;
+use tools::mock_server::faults::Fault;
+use tools::mock_server::faults::FaultInjector;
+use tools::mock_server::store::ResultStore;
+use tools::mock_server::store::cfg_name;
+use tools::mock_server::store::default_cfg;
+use tools::mock_server::store::default_output;
+use tools::mock_server::store::output_name;
+use tools::mock_server::timeline::Timeline;
+use tools::mock_server::upload::parse_multipart;
+
+/// Path under which the API is served (the same as on retdec.com).
+pub const API_PATH: &'static str = "/service/api";
+
+/// A request received by the mock server.
+#[derive(Clone, Debug, Default)]
+pub struct MockRequest {
+    /// HTTP method (e.g. `GET`).
+    pub method: String,
+
+    /// Path, including the query string.
+    pub path: String,
+
+    /// API key from the `Authorization` header.
+    pub api_key: Option<String>,
+
+    /// Value of the `Content-Type` header.
+    pub content_type: Option<String>,
+
+    /// Body of the request.
+    pub body: Vec<u8>,
+}
+
+/// A response sent by the mock server.
+#[derive(Clone, Debug, Default)]
+pub struct MockResponse {
+    /// HTTP status code.
+    pub status: u16,
+
+    /// Headers (name-value pairs).
+    pub headers: Vec<(String, String)>,
+
+    /// Body of the response.
+    pub body: Vec<u8>,
+
+    /// How long to wait before sending the response.
+    pub delay: Option<Duration>,
+
+    /// When set, the connection is closed after sending only the given
+    /// number of bytes from the body.
+    pub drop_after: Option<usize>,
+}
+
+impl MockResponse {
+    fn json(status: u16, json: JsonValue) -> Self {
+        MockResponse {
+            status: status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: json.dump().into_bytes(),
+            ..MockResponse::default()
+        }
+    }
+
+    fn error(status: u16, message: &str, description: &str) -> Self {
+        MockResponse::json(status, object!{
+            "code" => status,
+            "message" => message,
+            "description" => description
+        })
+    }
+
+    fn not_found(description: &str) -> Self {
+        MockResponse::error(404, "Not Found", description)
+    }
+
+    fn file(name: &str, content: Vec<u8>) -> Self {
+        MockResponse {
+            status: 200,
+            headers: vec![
+                ("Content-Type".to_string(), "application/octet-stream".to_string()),
+                ("Content-Disposition".to_string(), format!("attachment; filename={}", name)),
+            ],
+            body: content,
+            ..MockResponse::default()
+        }
+    }
+
+    fn injected_error(status: u16) -> Self {
+        match status {
+            401 => MockResponse::error(
+                401,
+                "Unauthorized by API Key",
+                "API key authentication failed."
+            ),
+            429 => {
+                let mut response = MockResponse::error(
+                    429,
+                    "Too Many Requests",
+                    "You have exceeded the limit of requests."
+                );
+                response.headers.push(("Retry-After".to_string(), "1".to_string()));
+                response
+            }
+            500 => MockResponse::error(
+                500,
+                "Internal Server Error",
+                "The server encountered an internal error."
+            ),
+            _ => MockResponse::error(status, "Error", "An injected error."),
+        }
+    }
+
+    fn inject(&mut self, fault: &Fault) {
+        match *fault {
+            // Already handled when creating the response.
+            Fault::Status(_) => {}
+            Fault::MalformedJson => {
+                if self.header("Content-Type") == Some("application/json") {
+                    // Cutting a JSON object in half always makes it invalid.
+                    let len = self.body.len() / 2;
+                    self.body.truncate(len);
+                }
+            }
+            Fault::MissingContentDisposition => {
+                self.headers.retain(|&(ref name, _)| {
+                    !name.eq_ignore_ascii_case("Content-Disposition")
+                });
+            }
+            Fault::Slow(duration) => {
+                self.delay = Some(self.delay.unwrap_or_default() + duration);
+            }
+            Fault::DropConnection => {
+                self.drop_after = Some(self.body.len() / 2);
+            }
+        }
+    }
+
+    /// Returns the value of the first header with the given name (if any).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|&&(ref n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, ref value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum JobKind {
+    Decompilation,
+    Analysis,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Route {
+    Test,
+    Echo,
+    StartJob(JobKind),
+    JobStatus(JobKind, String),
+    JobOutput(JobKind, String, String),
+    DecompilationCfg(String, String),
+}
+
+impl Route {
+    fn from_request(method: &str, segments: &[&str]) -> Option<Self> {
+        use self::JobKind::*;
+
+        let route = match (method, segments) {
+            ("GET", &["test"]) => Route::Test,
+            ("GET", &["test", "echo"]) => Route::Echo,
+            ("POST", &["decompiler", "decompilations"]) => Route::StartJob(Decompilation),
+            ("GET", &["decompiler", "decompilations", id, "status"]) => {
+                Route::JobStatus(Decompilation, id.to_string())
+            }
+            ("GET", &["decompiler", "decompilations", id, "outputs", output]) => {
+                Route::JobOutput(Decompilation, id.to_string(), output.to_string())
+            }
+            ("GET", &["decompiler", "decompilations", id, "outputs", "cfgs", func]) => {
+                Route::DecompilationCfg(id.to_string(), func.to_string())
+            }
+            ("POST", &["fileinfo", "analyses"]) => Route::StartJob(Analysis),
+            ("GET", &["fileinfo", "analyses", id, "status"]) => {
+                Route::JobStatus(Analysis, id.to_string())
+            }
+            ("GET", &["fileinfo", "analyses", id, "output"]) => {
+                Route::JobOutput(Analysis, id.to_string(), "output".to_string())
+            }
+            _ => return None,
+        };
+        Some(route)
+    }
+
+    /// Name of the endpoint, as used in fault rules.
+    fn endpoint(&self) -> &'static str {
+        match *self {
+            Route::Test => "test",
+            Route::Echo => "echo",
+            Route::StartJob(JobKind::Decompilation) => "start-decompilation",
+            Route::JobStatus(JobKind::Decompilation, _) => "decompilation-status",
+            Route::JobOutput(JobKind::Decompilation, _, _) |
+                Route::DecompilationCfg(_, _) => "decompilation-output",
+            Route::StartJob(JobKind::Analysis) => "start-analysis",
+            Route::JobStatus(JobKind::Analysis, _) => "analysis-status",
+            Route::JobOutput(JobKind::Analysis, _, _) => "analysis-output",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Job {
+    kind: JobKind,
+    input: File,
+    args: Vec<(String, String)>,
+    started: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Jobs {
+    jobs: HashMap<String, Job>,
+    last_id: u64,
+}
+
+/// A local server mimicking the retdec.com's API.
+///
+/// It implements the decompiler, fileinfo, and test services. Outputs are
+/// taken from a [`ResultStore`](../store/struct.ResultStore.html) and jobs
+/// progress according to a [`Timeline`](../timeline/struct.Timeline.html).
+/// Optionally, faults are injected into responses by
+/// a [`FaultInjector`](../faults/struct.FaultInjector.html).
+#[derive(Debug)]
+pub struct MockServer {
+    api_key: Option<String>,
+    store: ResultStore,
+    timeline: Timeline,
+    faults: FaultInjector,
+    jobs: Mutex<Jobs>,
+}
+
+impl MockServer {
+    /// Creates a server that accepts any API key, has no canned results, and
+    /// uses the default timeline.
+    pub fn new() -> Self {
+        MockServer {
+            api_key: None,
+            store: ResultStore::empty(),
+            timeline: Timeline::default(),
+            faults: FaultInjector::none(),
+            jobs: Mutex::new(Jobs::default()),
+        }
+    }
+
+    /// Makes the server accept only the given API key.
+    pub fn with_api_key<K: Into<String>>(mut self, api_key: K) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Sets the store from which canned results are taken.
+    pub fn with_result_store(mut self, store: ResultStore) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Sets the timeline according to which jobs progress.
+    pub fn with_timeline(mut self, timeline: Timeline) -> Self {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Sets the injector of faults into responses.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Starts serving requests on the given address (e.g. `127.0.0.1:8000`)
+    /// in a background thread.
+    ///
+    /// Use port 0 to let the operating system pick a free port.
+    pub fn start(self, address: &str) -> Result<RunningMockServer> {
+        let listening = HyperServer::http(address)
+            .chain_err(|| format!("failed to bind to {}", address))?
+            .handle(HyperHandler { server: self })
+            .chain_err(|| format!("failed to start the server on {}", address))?;
+        Ok(RunningMockServer { listening: Some(listening) })
+    }
+
+    /// Handles the given request.
+    pub fn handle(&self, request: &MockRequest) -> MockResponse {
+        let url = match HyperUrl::parse(&format!("http://localhost{}", request.path)) {
+            Ok(url) => url,
+            Err(_) => return MockResponse::error(400, "Bad Request", "Invalid URL."),
+        };
+        if !url.path().starts_with(API_PATH) {
+            return MockResponse::not_found("The requested URL was not found on the server.");
+        }
+        let path = url.path()[API_PATH.len()..].trim_matches('/').to_string();
+        let segments: Vec<&str> = path.split('/').collect();
+        let route = match Route::from_request(&request.method, &segments) {
+            Some(route) => route,
+            None => {
+                return MockResponse::not_found("The requested URL was not found on the server.");
+            }
+        };
+
+        let faults = self.faults.faults_for(route.endpoint());
+        let injected_status = faults.iter()
+            .filter_map(|fault| match *fault {
+                Fault::Status(status) => Some(status),
+                _ => None,
+            })
+            .next();
+        let mut response = match injected_status {
+            Some(status) => MockResponse::injected_error(status),
+            None => {
+                let args: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+                self.respond(route, request, args)
+            }
+        };
+        for fault in &faults {
+            response.inject(fault);
+        }
+        response
+    }
+
+    fn respond(&self,
+               route: Route,
+               request: &MockRequest,
+               args: Vec<(String, String)>) -> MockResponse {
+        if !self.is_authorized(request) {
+            return MockResponse::injected_error(401);
+        }
+
+        match route {
+            Route::Test => {
+                MockResponse::json(200, JsonValue::new_object())
+            }
+            Route::Echo => {
+                let mut json = JsonValue::new_object();
+                for (key, value) in args {
+                    json[key.as_str()] = value.into();
+                }
+                MockResponse::json(200, json)
+            }
+            Route::StartJob(kind) => self.start_job(kind, request, args),
+            Route::JobStatus(kind, ref id) => self.job_status(kind, id),
+            Route::JobOutput(kind, ref id, ref output) => self.job_output(kind, id, output),
+            Route::DecompilationCfg(ref id, ref func) => self.decompilation_cfg(id, func),
+        }
+    }
+
+    fn is_authorized(&self, request: &MockRequest) -> bool {
+        match (&self.api_key, &request.api_key) {
+            (&Some(ref expected), &Some(ref given)) => expected == given,
+            (&None, &Some(ref given)) => !given.is_empty(),
+            (_, &None) => false,
+        }
+    }
+
+    fn start_job(&self,
+                 kind: JobKind,
+                 request: &MockRequest,
+                 args: Vec<(String, String)>) -> MockResponse {
+        let content_type = request.content_type.as_ref().map(|c| c.as_str()).unwrap_or("");
+        let files = match parse_multipart(content_type, &request.body) {
+            Ok(files) => files,
+            Err(e) => return MockResponse::error(400, "Bad Request", &e.to_string()),
+        };
+        let input = match files.into_iter().find(|&(ref name, _)| name == "input") {
+            Some((_, input)) => input,
+            None => return MockResponse::error(400, "Bad Request", "Missing input file."),
+        };
+
+        let mut jobs = self.jobs.lock().expect("poisoned lock");
+        jobs.last_id += 1;
+        let id = format!("mock{:012}", jobs.last_id);
+        jobs.jobs.insert(id.clone(), Job {
+            kind: kind,
+            input: input,
+            args: args,
+            started: Instant::now(),
+        });
+        MockResponse::json(200, object!{
+            "id" => id.as_str(),
+            "links" => object!{
+                "status" => format!("{}/{}/{}/status", API_PATH, kind_path(kind), id)
+            }
+        })
+    }
+
+    fn job_status(&self, kind: JobKind, id: &str) -> MockResponse {
+        let jobs = self.jobs.lock().expect("poisoned lock");
+        let job = match jobs.jobs.get(id) {
+            Some(job) if job.kind == kind => job,
+            _ => return MockResponse::not_found(&format!("There is no job with ID '{}'.", id)),
+        };
+
+        let progress = self.timeline.progress_at(job.started.elapsed());
+        let error = if progress.finished { self.store.error(&job.input) } else { None };
+        let mut status = object!{
+            "id" => id,
+            "running" => !progress.finished,
+            "finished" => progress.finished,
+            "succeeded" => progress.finished && error.is_none(),
+            "failed" => error.is_some(),
+            "error" => match error {
+                Some(ref error) => JsonValue::from(error.as_str()),
+                None => JsonValue::Null,
+            }
+        };
+        if kind == JobKind::Decompilation {
+            status["completion"] = progress.completion.into();
+            status["phases"] = JsonValue::Array(
+                progress.phases.iter()
+                    .map(|&(ref name, completion)| object!{
+                        "part" => name.as_str(),
+                        "name" => name.as_str(),
+                        "description" => name.as_str(),
+                        "completion" => completion,
+                        "warnings" => array![]
+                    })
+                    .collect()
+            );
+            for output in &["cg", "archive"] {
+                let requested = is_generation_requested(job, output);
+                let generated = progress.finished && error.is_none() &&
+                    self.store.output(&job.input, output).is_some();
+                let generation_failed = progress.finished && requested && !generated;
+                status[*output] = object!{
+                    "generated" => generated,
+                    "generation_failed" => generation_failed,
+                    "generation_error" => if generation_failed {
+                        JsonValue::from(format!("The '{}' output has no canned result.", output))
+                    } else {
+                        JsonValue::Null
+                    }
+                };
+            }
+            let mut cfgs = JsonValue::new_object();
+            if progress.finished && error.is_none() && is_generation_requested(job, "cfgs") {
+                // Without canned graphs, there is a graph for the main
+                // function from the default HLL code.
+                let funcs = self.store.cfg_funcs(&job.input)
+                    .unwrap_or_else(|| vec!["main".to_string()]);
+                for func in funcs {
+                    let generated = self.store.cfg(&job.input, &func)
+                        .or_else(|| default_cfg(&job.input, &func))
+                        .is_some();
+                    cfgs[func.as_str()] = object!{
+                        "generated" => generated,
+                        "generation_failed" => !generated,
+                        "generation_error" => if generated {
+                            JsonValue::Null
+                        } else {
+                            JsonValue::from(format!("The graph of '{}' has no canned result.", func))
+                        }
+                    };
+                }
+            }
+            status["cfgs"] = cfgs;
+        }
+        MockResponse::json(200, status)
+    }
+
+    fn job_output(&self, kind: JobKind, id: &str, output: &str) -> MockResponse {
+        self.with_succeeded_job(kind, id, |job| {
+            let content = self.store.output(&job.input, output)
+                .or_else(|| default_output(&job.input, output, &job.args));
+            match content {
+                Some(content) => MockResponse::file(&output_name(job.input.name(), output), content),
+                None => MockResponse::not_found(&format!("The '{}' output was not generated.", output)),
+            }
+        })
+    }
+
+    fn decompilation_cfg(&self, id: &str, func: &str) -> MockResponse {
+        self.with_succeeded_job(JobKind::Decompilation, id, |job| {
+            let content = if is_generation_requested(job, "cfgs") {
+                self.store.cfg(&job.input, func)
+                    .or_else(|| default_cfg(&job.input, func))
+            } else {
+                None
+            };
+            match content {
+                Some(content) => MockResponse::file(&cfg_name(job.input.name(), func), content),
+                None => MockResponse::not_found(&format!("The graph of '{}' was not generated.", func)),
+            }
+        })
+    }
+
+    /// Calls the given function with the given job when it has finished
+    /// successfully. Otherwise, responds with an error.
+    fn with_succeeded_job<F>(&self, kind: JobKind, id: &str, f: F) -> MockResponse
+        where F: FnOnce(&Job) -> MockResponse
+    {
+        let jobs = self.jobs.lock().expect("poisoned lock");
+        let job = match jobs.jobs.get(id) {
+            Some(job) if job.kind == kind => job,
+            _ => return MockResponse::not_found(&format!("There is no job with ID '{}'.", id)),
+        };
+
+        if !self.timeline.progress_at(job.started.elapsed()).finished {
+            return MockResponse::not_found("The job has not finished yet.");
+        }
+        if self.store.error(&job.input).is_some() {
+            return MockResponse::not_found("The job has failed.");
+        }
+        f(job)
+    }
+}
+
+/// Has the generation of the given output (e.g. `cg`) been requested when
+/// starting the job?
+fn is_generation_requested(job: &Job, output: &str) -> bool {
+    let generate_arg = format!("generate_{}", output);
+    job.args.iter().any(|&(ref key, ref value)| *key == generate_arg && value == "1")
+}
+
+impl Default for MockServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn kind_path(kind: JobKind) -> &'static str {
+    match kind {
+        JobKind::Decompilation => "decompiler/decompilations",
+        JobKind::Analysis => "fileinfo/analyses",
+    }
+}
+
+/// A mock server that is serving requests in a background thread.
+#[derive(Debug)]
+pub struct RunningMockServer {
+    listening: Option<Listening>,
+}
+
+impl RunningMockServer {
+    /// Returns the address on which the server listens.
+    pub fn address(&self) -> SocketAddr {
+        self.listening.as_ref()
+            .map(|listening| listening.socket)
+            .expect("no listening socket - this should never happen")
+    }
+
+    /// Returns the API URL that clients should use to access the server.
+    pub fn api_url(&self) -> String {
+        format!("http://{}{}", self.address(), API_PATH)
+    }
+
+    /// Blocks the current thread for as long as the server runs.
+    pub fn wait(mut self) {
+        // Dropping the listening socket joins the serving thread.
+        drop(self.listening.take());
+    }
+}
+
+impl Drop for RunningMockServer {
+    fn drop(&mut self) {
+        // hyper provides no way of stopping the server, so just detach it
+        // from the serving thread (otherwise, the drop would block forever).
+        if let Some(mut listening) = self.listening.take() {
+            let _ = listening.close();
+        }
+    }
+}
+
+struct HyperHandler {
+    server: MockServer,
+}
+
+impl Handler for HyperHandler {
+    fn handle(&self, mut request: HyperRequest, mut response: HyperResponse) {
+        let path = match request.uri {
+            RequestUri::AbsolutePath(ref path) => path.clone(),
+            ref uri => uri.to_string(),
+        };
+        let api_key = request.headers.get::<Authorization<Basic>>()
+            .map(|auth| auth.username.clone());
+        let content_type = request.headers.get_raw("Content-Type")
+            .and_then(|values| values.first())
+            .map(|value| String::from_utf8_lossy(value).into_owned());
+        let mut body = Vec::new();
+        if request.read_to_end(&mut body).is_err() {
+            return;
+        }
+
+        let mock_response = self.server.handle(&MockRequest {
+            method: request.method.to_string(),
+            path: path,
+            api_key: api_key,
+            content_type: content_type,
+            body: body,
+        });
+
+        if let Some(delay) = mock_response.delay {
+            thread::sleep(delay);
+        }
+        *response.status_mut() = StatusCode::from_u16(mock_response.status);
+        for (name, value) in mock_response.headers {
+            response.headers_mut().set_raw(name, vec![value.into_bytes()]);
+        }
+        let body = mock_response.body;
+        match mock_response.drop_after {
+            Some(sent_len) => {
+                // Announce the whole body but send only a part of it. As the
+                // response asks for the connection to be closed, hyper closes
+                // it once the handler returns.
+                response.headers_mut().set(ContentLength(body.len() as u64));
+                response.headers_mut().set(Connection::close());
+                if let Ok(mut response) = response.start() {
+                    let _ = response.write_all(&body[..sent_len]);
+                    let _ = response.flush();
+                }
+            }
+            None => {
+                let _ = response.send(&body);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    use tempdir::TempDir;
+
+    use tools::mock_server::faults::FaultRule;
+
+    use decompilation::DecompilationArguments;
+    use decompiler::Decompiler;
+    use settings::Settings;
+
+    fn get(path: &str) -> MockRequest {
+        MockRequest {
+            method: "GET".to_string(),
+            path: format!("{}{}", API_PATH, path),
+            api_key: Some("KEY".to_string()),
+            ..MockRequest::default()
+        }
+    }
+
+    fn post_file(path: &str, name: &str, content: &[u8]) -> MockRequest {
+        let mut body = format!(
+            "--XyZ\r\n\
+             Content-Disposition: form-data; name=\"input\"; filename=\"{}\"\r\n\
+             \r\n",
+            name
+        ).into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n--XyZ--\r\n");
+        MockRequest {
+            method: "POST".to_string(),
+            path: format!("{}{}", API_PATH, path),
+            api_key: Some("KEY".to_string()),
+            content_type: Some("multipart/form-data; boundary=XyZ".to_string()),
+            body: body,
+        }
+    }
+
+    fn body_as_json(response: &MockResponse) -> JsonValue {
+        ::json::parse(&String::from_utf8_lossy(&response.body)).unwrap()
+    }
+
+    fn instant_server() -> MockServer {
+        MockServer::new().with_timeline(Timeline::parse("").unwrap())
+    }
+
+    fn start_decompilation(server: &MockServer, name: &str) -> String {
+        start_decompilation_with(server, name, "")
+    }
+
+    fn start_decompilation_with(server: &MockServer, name: &str, query: &str) -> String {
+        let response = server.handle(
+            &post_file(&format!("/decompiler/decompilations{}", query), name, b"content")
+        );
+        assert_eq!(response.status, 200);
+        body_as_json(&response)["id"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_succeeds_when_api_key_is_given() {
+        let server = MockServer::new();
+
+        let response = server.handle(&get("/test"));
+
+        assert_eq!(response.status, 200);
+    }
+
+    #[test]
+    fn request_without_api_key_is_unauthorized() {
+        let server = MockServer::new();
+        let mut request = get("/test");
+        request.api_key = None;
+
+        let response = server.handle(&request);
+
+        assert_eq!(response.status, 401);
+    }
+
+    #[test]
+    fn request_with_wrong_api_key_is_unauthorized_when_key_is_required() {
+        let server = MockServer::new().with_api_key("OTHER");
+
+        let response = server.handle(&get("/test"));
+
+        assert_eq!(response.status, 401);
+    }
+
+    #[test]
+    fn echo_returns_query_parameters() {
+        let server = MockServer::new();
+
+        let response = server.handle(&get("/test/echo?a=1&b=x%20y"));
+
+        assert_eq!(response.status, 200);
+        let json = body_as_json(&response);
+        assert_eq!(json["a"], "1");
+        assert_eq!(json["b"], "x y");
+    }
+
+    #[test]
+    fn unknown_path_results_in_not_found() {
+        let server = MockServer::new();
+
+        let response = server.handle(&get("/unknown"));
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn starting_decompilation_without_input_file_results_in_bad_request() {
+        let server = MockServer::new();
+        let mut request = post_file("/decompiler/decompilations", "file.exe", b"content");
+        request.body = b"--XyZ--\r\n".to_vec();
+
+        let response = server.handle(&request);
+
+        assert_eq!(response.status, 400);
+    }
+
+    #[test]
+    fn decompilation_is_running_until_timeline_elapses() {
+        let server = MockServer::new().with_timeline(Timeline::parse("A=60000").unwrap());
+        let id = start_decompilation(&server, "file.exe");
+
+        let response = server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)));
+
+        let status = body_as_json(&response);
+        assert_eq!(status["running"], true);
+        assert_eq!(status["finished"], false);
+        assert_eq!(status["phases"][0]["name"], "A");
+        let response = server.handle(
+            &get(&format!("/decompiler/decompilations/{}/outputs/hll", id))
+        );
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn finished_decompilation_provides_default_hll_output() {
+        let server = instant_server();
+        let id = start_decompilation(&server, "file.exe");
+
+        let status = server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)));
+        let hll = server.handle(&get(&format!("/decompiler/decompilations/{}/outputs/hll", id)));
+
+        let status = body_as_json(&status);
+        assert_eq!(status["finished"], true);
+        assert_eq!(status["succeeded"], true);
+        assert_eq!(status["completion"], 100);
+        assert_eq!(hll.status, 200);
+        assert_eq!(hll.header("Content-Disposition"), Some("attachment; filename=file.c"));
+        assert!(String::from_utf8_lossy(&hll.body).contains("int main("));
+    }
+
+    #[test]
+    fn finished_decompilation_provides_default_cfg_of_main_when_cfgs_are_requested() {
+        let server = instant_server();
+        let id = start_decompilation_with(&server, "file.exe", "?generate_cfgs=1");
+
+        let status = body_as_json(
+            &server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)))
+        );
+        let cfg = server.handle(
+            &get(&format!("/decompiler/decompilations/{}/outputs/cfgs/main", id))
+        );
+
+        assert_eq!(status["cfgs"]["main"]["generated"], true);
+        assert_eq!(cfg.status, 200);
+        assert!(String::from_utf8_lossy(&cfg.body).contains("digraph main"));
+        assert_eq!(
+            cfg.header("Content-Disposition"),
+            Some("attachment; filename=file.main.cfg")
+        );
+    }
+
+    #[test]
+    fn decompilation_reports_no_cfgs_when_they_were_not_requested() {
+        let server = instant_server();
+        let id = start_decompilation(&server, "file.exe");
+
+        let status = body_as_json(
+            &server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)))
+        );
+        let cfg = server.handle(
+            &get(&format!("/decompiler/decompilations/{}/outputs/cfgs/main", id))
+        );
+
+        assert_eq!(status["cfgs"].len(), 0);
+        assert_eq!(cfg.status, 404);
+    }
+
+    #[test]
+    fn decompilation_fails_when_store_contains_error() {
+        let tmp_dir = TempDir::new("retdec-mock-server").unwrap();
+        ::std::fs::create_dir(tmp_dir.path().join("file.exe")).unwrap();
+        ::std::fs::File::create(tmp_dir.path().join("file.exe").join("error")).unwrap();
+        let server = instant_server()
+            .with_result_store(ResultStore::from_dir(tmp_dir.path()));
+        let id = start_decompilation(&server, "file.exe");
+
+        let status = server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)));
+
+        let status = body_as_json(&status);
+        assert_eq!(status["failed"], true);
+        assert_eq!(status["succeeded"], false);
+    }
+
+    #[test]
+    fn status_of_unknown_job_results_in_not_found() {
+        let server = MockServer::new();
+
+        let response = server.handle(&get("/decompiler/decompilations/xyz/status"));
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn analysis_cannot_be_accessed_as_decompilation() {
+        let server = instant_server();
+        let response = server.handle(&post_file("/fileinfo/analyses", "file.exe", b"content"));
+        let id = body_as_json(&response)["id"].as_str().unwrap().to_string();
+
+        let response = server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)));
+
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn finished_analysis_provides_output() {
+        let server = instant_server();
+        let response = server.handle(&post_file("/fileinfo/analyses", "file.exe", b"content"));
+        let id = body_as_json(&response)["id"].as_str().unwrap().to_string();
+
+        let output = server.handle(&get(&format!("/fileinfo/analyses/{}/output", id)));
+
+        assert_eq!(output.status, 200);
+        assert!(String::from_utf8_lossy(&output.body).contains("file.exe"));
+    }
+
+    fn server_with_faults(rules: &[&str]) -> MockServer {
+        let rules = rules.iter().map(|rule| FaultRule::parse(rule).unwrap()).collect();
+        instant_server().with_faults(FaultInjector::new(rules, 0))
+    }
+
+    #[test]
+    fn injected_status_fault_replaces_response() {
+        let server = server_with_faults(&["test:500:at=1"]);
+
+        let first = server.handle(&get("/test"));
+        let second = server.handle(&get("/test"));
+
+        assert_eq!(first.status, 500);
+        assert_eq!(second.status, 200);
+    }
+
+    #[test]
+    fn injected_too_many_requests_fault_includes_retry_after() {
+        let server = server_with_faults(&["test:429"]);
+
+        let response = server.handle(&get("/test"));
+
+        assert_eq!(response.status, 429);
+        assert_eq!(response.header("Retry-After"), Some("1"));
+    }
+
+    #[test]
+    fn injected_status_fault_into_start_does_not_create_job() {
+        let server = server_with_faults(&["start-decompilation:401:at=1"]);
+
+        let response = server.handle(
+            &post_file("/decompiler/decompilations", "file.exe", b"content")
+        );
+        let id = start_decompilation(&server, "file.exe");
+
+        assert_eq!(response.status, 401);
+        assert_eq!(id, "mock000000000001");
+    }
+
+    #[test]
+    fn injected_malformed_json_fault_makes_status_unparsable() {
+        let server = server_with_faults(&["decompilation-status:malformed-json"]);
+        let id = start_decompilation(&server, "file.exe");
+
+        let response = server.handle(&get(&format!("/decompiler/decompilations/{}/status", id)));
+
+        assert_eq!(response.status, 200);
+        assert!(::json::parse(&String::from_utf8_lossy(&response.body)).is_err());
+    }
+
+    #[test]
+    fn injected_missing_content_disposition_fault_removes_header() {
+        let server = server_with_faults(&["decompilation-output:no-content-disposition"]);
+        let id = start_decompilation(&server, "file.exe");
+
+        let response = server.handle(
+            &get(&format!("/decompiler/decompilations/{}/outputs/hll", id))
+        );
+
+        assert_eq!(response.status, 200);
+        assert!(response.header("Content-Disposition").is_none());
+    }
+
+    #[test]
+    fn injected_slow_and_drop_faults_are_recorded_in_response() {
+        let server = server_with_faults(&["test:slow=100", "test:slow=50", "test:drop"]);
+
+        let response = server.handle(&get("/test"));
+
+        assert_eq!(response.delay, Some(Duration::from_millis(150)));
+        assert_eq!(response.drop_after, Some(1));
+    }
+
+    #[test]
+    fn faults_are_not_injected_into_other_endpoints() {
+        let server = server_with_faults(&["echo:500"]);
+
+        let response = server.handle(&get("/test"));
+
+        assert_eq!(response.status, 200);
+    }
+
+    #[test]
+    fn running_server_closes_connection_in_middle_of_body_when_drop_is_injected() {
+        let server = server_with_faults(&["test:drop"])
+            .start("127.0.0.1:0")
+            .unwrap();
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        stream.write_all(
+            b"GET /service/api/test HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Authorization: Basic S0VZOg==\r\n\
+              \r\n"
+        ).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.contains("Content-Length: 2"));
+        assert!(response.ends_with("\r\n\r\n{"));
+    }
+
+    #[test]
+    fn library_can_decompile_file_via_running_server() {
+        let server = MockServer::new()
+            .with_timeline(Timeline::parse("A=10").unwrap())
+            .start("127.0.0.1:0")
+            .unwrap();
+        // Give the serving thread some time to start accepting connections.
+        thread::sleep(Duration::from_millis(10));
+        let decompiler = Decompiler::new(
+            Settings::new()
+                .with_api_key("KEY")
+                .with_api_url(server.api_url())
+                .with_plain_http_allowed(true)
+        );
+
+        let mut decompilation = decompiler.start_decompilation(
+            DecompilationArguments::new()
+                .with_input_file(File::from_content_with_name(b"content", "file.exe"))
+        ).unwrap();
+        decompilation.wait_until_finished().unwrap();
+        let output = decompilation.get_output_hll_code().unwrap();
+
+        assert!(output.contains("retdec-mock-server"));
+    }
+}
@@ -0,0 +1,342 @@
+//! Store of canned results that are served by the mock server.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use file::File;
+use utils::sha256_hex;
+
+/// Store of canned results.
+///
+/// The store is a directory with one subdirectory per input file. The
+/// subdirectory is named either after the SHA-256 digest of the input file
+/// (preferred) or after its name. It may contain the following files:
+///
+/// * `hll`, `dsm`, `cg`, `archive`, `binary`: Outputs of
+///   a decompilation.
+/// * `cfgs`: A directory with control-flow graphs of functions, one file per
+///   function (e.g. `cfgs/main`).
+/// * `output`: Output of an analysis.
+/// * `error`: When present, the job fails with the error message read from
+///   this file.
+///
+/// The HLL code, disassembly, output of an analysis, and control-flow graph
+/// of `main` are generated on the fly by the server when they are not present
+/// in the store (see [`default_output()`](fn.default_output.html) and
+/// [`default_cfg()`](fn.default_cfg.html)). Other outputs are reported as not
+/// generated.
+#[derive(Clone, Debug, Default)]
+pub struct ResultStore {
+    dir: Option<PathBuf>,
+}
+
+impl ResultStore {
+    /// Creates a store that does not contain any canned results.
+    pub fn empty() -> Self {
+        ResultStore { dir: None }
+    }
+
+    /// Creates a store reading canned results from the given directory.
+    pub fn from_dir<P: Into<PathBuf>>(dir: P) -> Self {
+        ResultStore { dir: Some(dir.into()) }
+    }
+
+    /// Returns the directory from which canned results are read (if any).
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_ref().map(|dir| dir.as_path())
+    }
+
+    /// Returns the canned content of the given output for the given input
+    /// file.
+    pub fn output(&self, input: &File, output: &str) -> Option<Vec<u8>> {
+        self.read(input, output)
+    }
+
+    /// Returns the names of functions whose control-flow graphs are canned
+    /// for the given input file, or `None` when there is no `cfgs` directory.
+    pub fn cfg_funcs(&self, input: &File) -> Option<Vec<String>> {
+        let cfgs_dir = self.candidate_dirs(input).into_iter()
+            .map(|dir| dir.join("cfgs"))
+            .find(|dir| dir.is_dir())?;
+        let mut funcs: Vec<String> = fs::read_dir(cfgs_dir).ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        funcs.sort();
+        Some(funcs)
+    }
+
+    /// Returns the canned control-flow graph of the given function for the
+    /// given input file.
+    pub fn cfg(&self, input: &File, func: &str) -> Option<Vec<u8>> {
+        // The name of the function comes from the client, so make sure that
+        // it cannot be used to read files outside of the store.
+        if !is_plain_name(func) {
+            return None;
+        }
+        self.read(input, &format!("cfgs/{}", func))
+    }
+
+    /// Returns the canned error for the given input file.
+    pub fn error(&self, input: &File) -> Option<String> {
+        self.read(input, "error")
+            .map(|error| String::from_utf8_lossy(&error).trim().to_string())
+    }
+
+    fn read(&self, input: &File, name: &str) -> Option<Vec<u8>> {
+        self.candidate_dirs(input).iter()
+            .map(|dir| dir.join(name))
+            .filter(|path| path.is_file())
+            .filter_map(|path| File::from_path(path).ok())
+            .map(|file| file.content().to_vec())
+            .next()
+    }
+
+    /// Returns directories that may contain canned results for the given
+    /// input file, in the order of preference.
+    fn candidate_dirs(&self, input: &File) -> Vec<PathBuf> {
+        let dir = match self.dir {
+            Some(ref dir) => dir,
+            None => return Vec::new(),
+        };
+        let mut candidates = vec![dir.join(sha256_hex(input.content()))];
+        // The name of the input file comes from the client, so make sure that
+        // it cannot be used to read files outside of the store.
+        if is_plain_name(input.name()) {
+            candidates.push(dir.join(input.name()));
+        }
+        candidates
+    }
+}
+
+/// Checks that the given name does not refer to another directory.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('/') && !name.contains('\\') &&
+        name != "." && name != ".."
+}
+
+/// Returns the name under which the given output of the given input file is
+/// served.
+pub fn output_name(input_name: &str, output: &str) -> String {
+    let stem = file_stem(input_name);
+    match output {
+        "hll" => format!("{}.c", stem),
+        "dsm" => format!("{}.dsm", stem),
+        "cg" => format!("{}.cg.svg", stem),
+        "archive" => format!("{}.a", stem),
+        "binary" => format!("{}.out", stem),
+        "output" => format!("{}.fileinfo", stem),
+        _ => format!("{}.{}", stem, output),
+    }
+}
+
+/// Returns the name under which the control-flow graph of the given function
+/// of the given input file is served.
+pub fn cfg_name(input_name: &str, func: &str) -> String {
+    format!("{}.{}.cfg", file_stem(input_name), func)
+}
+
+fn file_stem(input_name: &str) -> &str {
+    match input_name.rfind('.') {
+        Some(i) if i > 0 => &input_name[..i],
+        _ => input_name,
+    }
+}
+
+/// Returns an output that is served when there is no canned one.
+///
+/// Only the main outputs (HLL code, disassembly, and output from an analysis)
+/// have a default. For other outputs, `None` is returned.
+pub fn default_output(input: &File, output: &str, args: &[(String, String)]) -> Option<Vec<u8>> {
+    let content = match output {
+        "hll" => format!(
+            "//\n\
+             // This file was generated by retdec-mock-server\n\
+             // Input file: {}\n\
+             //\n\
+             \n\
+             int main(int argc, char **argv) {{\n\
+             \x20   return 0;\n\
+             }}\n",
+            input.name()
+        ),
+        "dsm" => format!(
+            ";;\n\
+             ;; This file was generated by retdec-mock-server\n\
+             ;; Input file: {}\n\
+             ;;\n",
+            input.name()
+        ),
+        "output" => {
+            let json_requested = args.iter()
+                .any(|&(ref key, ref value)| key == "output_format" && value == "json");
+            if json_requested {
+                object!{
+                    "inputFile" => input.name(),
+                    "fileSize" => input.content_len(),
+                    "sha256" => sha256_hex(input.content())
+                }.pretty(4) + "\n"
+            } else {
+                format!(
+                    "Input file               : {}\n\
+                     File size                : {}\n\
+                     SHA-256                  : {}\n",
+                    input.name(),
+                    input.content_len(),
+                    sha256_hex(input.content())
+                )
+            }
+        }
+        _ => return None,
+    };
+    Some(content.into_bytes())
+}
+
+/// Returns a control-flow graph that is served when there is no canned one.
+///
+/// Only the `main` function from the default HLL code has a default graph.
+pub fn default_cfg(input: &File, func: &str) -> Option<Vec<u8>> {
+    if func != "main" {
+        return None;
+    }
+    let content = format!(
+        "// This file was generated by retdec-mock-server\n\
+         // Input file: {}\n\
+         digraph main {{\n\
+         \x20   entry -> exit;\n\
+         }}\n",
+        input.name()
+    );
+    Some(content.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::io::Write;
+
+    use tempdir::TempDir;
+
+    fn store_with(key: &str, name: &str, content: &[u8]) -> (TempDir, ResultStore) {
+        let tmp_dir = TempDir::new("retdec-mock-server").unwrap();
+        fs::create_dir(tmp_dir.path().join(key)).unwrap();
+        fs::File::create(tmp_dir.path().join(key).join(name)).unwrap()
+            .write_all(content).unwrap();
+        let store = ResultStore::from_dir(tmp_dir.path());
+        (tmp_dir, store)
+    }
+
+    #[test]
+    fn empty_store_has_no_outputs() {
+        let store = ResultStore::empty();
+
+        let input = File::from_content_with_name(b"content", "file.exe");
+        assert!(store.output(&input, "hll").is_none());
+        assert!(store.error(&input).is_none());
+    }
+
+    #[test]
+    fn store_returns_output_stored_under_input_name() {
+        let (_tmp_dir, store) = store_with("file.exe", "hll", b"int x;");
+
+        let input = File::from_content_with_name(b"content", "file.exe");
+        assert_eq!(store.output(&input, "hll"), Some(b"int x;".to_vec()));
+    }
+
+    #[test]
+    fn store_returns_output_stored_under_input_digest() {
+        let (_tmp_dir, store) = store_with(
+            "ed7002b439e9ac845f22357d822bac1444730fbdb6016d3ec9432297b9ec9f73",
+            "dsm",
+            b"nop"
+        );
+
+        let input = File::from_content_with_name(b"content", "other.exe");
+        assert_eq!(store.output(&input, "dsm"), Some(b"nop".to_vec()));
+    }
+
+    #[test]
+    fn store_returns_trimmed_error() {
+        let (_tmp_dir, store) = store_with("file.exe", "error", b"Unsupported file.\n");
+
+        let input = File::from_content_with_name(b"content", "file.exe");
+        assert_eq!(store.error(&input), Some("Unsupported file.".to_string()));
+    }
+
+    #[test]
+    fn store_ignores_input_names_pointing_outside_of_store() {
+        let (tmp_dir, _) = store_with("secret", "hll", b"int x;");
+        let store = ResultStore::from_dir(tmp_dir.path().join("store"));
+
+        let input = File::from_content_with_name(b"content", "../secret");
+        assert!(store.output(&input, "hll").is_none());
+    }
+
+    #[test]
+    fn output_name_replaces_extension_based_on_output() {
+        assert_eq!(output_name("file.exe", "hll"), "file.c");
+        assert_eq!(output_name("file.exe", "dsm"), "file.dsm");
+        assert_eq!(output_name("file", "output"), "file.fileinfo");
+    }
+
+    #[test]
+    fn default_output_exists_only_for_main_outputs() {
+        let input = File::from_content_with_name(b"content", "file.exe");
+
+        assert!(default_output(&input, "hll", &[]).is_some());
+        assert!(default_output(&input, "dsm", &[]).is_some());
+        assert!(default_output(&input, "output", &[]).is_some());
+        assert!(default_output(&input, "cg", &[]).is_none());
+    }
+
+    #[test]
+    fn store_returns_sorted_cfg_funcs_and_their_cfgs() {
+        let (tmp_dir, store) = store_with("file.exe", "hll", b"int x;");
+        let cfgs_dir = tmp_dir.path().join("file.exe").join("cfgs");
+        fs::create_dir(&cfgs_dir).unwrap();
+        fs::File::create(cfgs_dir.join("main")).unwrap().write_all(b"main cfg").unwrap();
+        fs::File::create(cfgs_dir.join("func")).unwrap().write_all(b"func cfg").unwrap();
+
+        let input = File::from_content_with_name(b"content", "file.exe");
+        assert_eq!(store.cfg_funcs(&input), Some(vec!["func".to_string(), "main".to_string()]));
+        assert_eq!(store.cfg(&input, "main"), Some(b"main cfg".to_vec()));
+        assert!(store.cfg(&input, "../hll").is_none());
+    }
+
+    #[test]
+    fn store_without_cfgs_directory_has_no_cfg_funcs() {
+        let (_tmp_dir, store) = store_with("file.exe", "hll", b"int x;");
+
+        let input = File::from_content_with_name(b"content", "file.exe");
+        assert_eq!(store.cfg_funcs(&input), None);
+    }
+
+    #[test]
+    fn cfg_name_includes_function_name() {
+        assert_eq!(cfg_name("file.exe", "main"), "file.main.cfg");
+    }
+
+    #[test]
+    fn default_cfg_exists_only_for_main() {
+        let input = File::from_content_with_name(b"content", "file.exe");
+
+        assert!(default_cfg(&input, "main").is_some());
+        assert!(default_cfg(&input, "func").is_none());
+    }
+
+    #[test]
+    fn default_analysis_output_honors_requested_output_format() {
+        let input = File::from_content_with_name(b"content", "file.exe");
+        let args = vec![("output_format".to_string(), "json".to_string())];
+
+        let output = default_output(&input, "output", &args).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("{"));
+        assert!(output.contains("\"inputFile\": \"file.exe\""));
+    }
+}
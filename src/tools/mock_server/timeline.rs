@@ -0,0 +1,210 @@
+//! Fake progress timeline of jobs running on the mock server.
+
+use std::time::Duration;
+
+use error::Result;
+
+/// A single phase of a timeline.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Phase {
+    /// Name of the phase.
+    pub name: String,
+
+    /// How long the phase takes.
+    pub duration: Duration,
+}
+
+/// Fake progress timeline of a job.
+///
+/// A job goes through the phases of the timeline one after another. Once all
+/// phases have elapsed, the job is finished.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Timeline {
+    phases: Vec<Phase>,
+}
+
+/// Progress of a job at a particular point of its timeline.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// Phases that have already started, together with the completion (in
+    /// percent) of the job at the time the phase started.
+    pub phases: Vec<(String, u32)>,
+
+    /// Completion of the whole job (in percent).
+    pub completion: u32,
+
+    /// Has the job finished?
+    pub finished: bool,
+}
+
+impl Timeline {
+    /// Creates a timeline from the given phases.
+    pub fn new(phases: Vec<Phase>) -> Self {
+        Timeline { phases: phases }
+    }
+
+    /// Parses a timeline from its textual representation.
+    ///
+    /// The representation is a comma-separated list of `NAME=MILLISECONDS`
+    /// pairs, e.g. `Front-End=500,Back-End=1000`. An empty string denotes
+    /// a timeline in which jobs finish immediately.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut phases = Vec::new();
+        for phase in spec.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let mut parts = phase.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let duration = parts.next()
+                .and_then(|d| d.trim().parse::<u64>().ok());
+            match duration {
+                Some(duration) if !name.is_empty() => {
+                    phases.push(Phase {
+                        name: name.to_string(),
+                        duration: Duration::from_millis(duration),
+                    });
+                }
+                _ => bail!("invalid timeline phase '{}' (expected NAME=MILLISECONDS)", phase),
+            }
+        }
+        Ok(Timeline::new(phases))
+    }
+
+    /// Returns the phases of the timeline.
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    /// Returns the total duration of the timeline.
+    pub fn total_duration(&self) -> Duration {
+        self.phases.iter()
+            .fold(Duration::from_millis(0), |total, phase| total + phase.duration)
+    }
+
+    /// Returns the progress of a job that has been running for the given
+    /// time.
+    pub fn progress_at(&self, elapsed: Duration) -> Progress {
+        let total = duration_as_millis(self.total_duration());
+        let elapsed = duration_as_millis(elapsed);
+        if elapsed >= total {
+            return Progress {
+                phases: self.started_phases(total, total),
+                completion: 100,
+                finished: true,
+            };
+        }
+
+        Progress {
+            phases: self.started_phases(elapsed, total),
+            completion: (elapsed * 100 / total) as u32,
+            finished: false,
+        }
+    }
+
+    fn started_phases(&self, elapsed: u64, total: u64) -> Vec<(String, u32)> {
+        let mut phases = Vec::new();
+        let mut start = 0;
+        for phase in &self.phases {
+            if start > elapsed {
+                break;
+            }
+            phases.push((phase.name.clone(), (start * 100 / total) as u32));
+            start += duration_as_millis(phase.duration);
+        }
+        phases
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline::parse("Pre-Processing=200,Front-End=400,Middle-End=400,Back-End=400")
+            .expect("invalid default timeline - this should never happen")
+    }
+}
+
+fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos() / 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn parse_returns_correct_phases() {
+        let timeline = Timeline::parse("A=100, B=300").unwrap();
+
+        assert_eq!(timeline.phases(), &[
+            Phase { name: "A".to_string(), duration: ms(100) },
+            Phase { name: "B".to_string(), duration: ms(300) },
+        ]);
+        assert_eq!(timeline.total_duration(), ms(400));
+    }
+
+    #[test]
+    fn parse_accepts_empty_timeline() {
+        let timeline = Timeline::parse("").unwrap();
+
+        assert!(timeline.phases().is_empty());
+    }
+
+    #[test]
+    fn parse_returns_error_when_duration_is_missing() {
+        assert!(Timeline::parse("A").is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_when_duration_is_invalid() {
+        assert!(Timeline::parse("A=fast").is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_when_name_is_missing() {
+        assert!(Timeline::parse("=100").is_err());
+    }
+
+    #[test]
+    fn progress_at_start_contains_only_first_phase() {
+        let timeline = Timeline::parse("A=100,B=300").unwrap();
+
+        let progress = timeline.progress_at(ms(0));
+
+        assert_eq!(progress.phases, vec![("A".to_string(), 0)]);
+        assert_eq!(progress.completion, 0);
+        assert!(!progress.finished);
+    }
+
+    #[test]
+    fn progress_in_middle_contains_started_phases() {
+        let timeline = Timeline::parse("A=100,B=300").unwrap();
+
+        let progress = timeline.progress_at(ms(200));
+
+        assert_eq!(progress.phases, vec![("A".to_string(), 0), ("B".to_string(), 25)]);
+        assert_eq!(progress.completion, 50);
+        assert!(!progress.finished);
+    }
+
+    #[test]
+    fn progress_after_end_is_finished() {
+        let timeline = Timeline::parse("A=100,B=300").unwrap();
+
+        let progress = timeline.progress_at(ms(1000));
+
+        assert_eq!(progress.phases.len(), 2);
+        assert_eq!(progress.completion, 100);
+        assert!(progress.finished);
+    }
+
+    #[test]
+    fn job_with_empty_timeline_finishes_immediately() {
+        let timeline = Timeline::parse("").unwrap();
+
+        let progress = timeline.progress_at(ms(0));
+
+        assert!(progress.phases.is_empty());
+        assert!(progress.finished);
+    }
+}
@@ -0,0 +1,179 @@
+//! Parsing of files uploaded to the mock server.
+
+use error::Result;
+use file::File;
+
+/// Parses files from the body of a `multipart/form-data` request.
+///
+/// Returns pairs `(field name, file)`. Parts that are not files (i.e. they do
+/// not have a file name) are skipped.
+pub fn parse_multipart(content_type: &str, body: &[u8]) -> Result<Vec<(String, File)>> {
+    let boundary = boundary_from_content_type(content_type)
+        .ok_or_else(|| format!("no multipart boundary in '{}'", content_type))?;
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut inner_delimiter = b"\r\n".to_vec();
+    inner_delimiter.extend_from_slice(&delimiter);
+
+    let mut files = Vec::new();
+    let mut pos = find(body, &delimiter, 0)
+        .ok_or("multipart body does not contain any boundary")?
+        + delimiter.len();
+    loop {
+        if body[pos..].starts_with(b"--") {
+            // The closing delimiter.
+            break;
+        }
+        if body[pos..].starts_with(b"\r\n") {
+            pos += 2;
+        }
+        let headers_end = find(body, b"\r\n\r\n", pos)
+            .ok_or("multipart part without a header terminator")?;
+        let headers = String::from_utf8_lossy(&body[pos..headers_end]).into_owned();
+        let content_start = headers_end + 4;
+        let content_end = find(body, &inner_delimiter, content_start)
+            .ok_or("multipart body is not terminated")?;
+        if let Some((name, filename)) = field_from_headers(&headers) {
+            if let Some(filename) = filename {
+                let file = File::from_content_with_name(
+                    &body[content_start..content_end],
+                    filename
+                );
+                files.push((name, file));
+            }
+        }
+        pos = content_end + inner_delimiter.len();
+    }
+    Ok(files)
+}
+
+fn boundary_from_content_type(content_type: &str) -> Option<String> {
+    content_type.split(';')
+        .map(|param| param.trim())
+        .filter_map(|param| split_param(param))
+        .find(|&(ref key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value)
+}
+
+fn field_from_headers(headers: &str) -> Option<(String, Option<String>)> {
+    let disposition = headers.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next()?;
+            if name.eq_ignore_ascii_case("Content-Disposition") {
+                Some(value.trim())
+            } else {
+                None
+            }
+        })
+        .next()?;
+    let mut name = None;
+    let mut filename = None;
+    for (key, value) in disposition.split(';').skip(1).filter_map(|p| split_param(p.trim())) {
+        if key == "name" {
+            name = Some(value);
+        } else if key == "filename" {
+            filename = Some(value);
+        }
+    }
+    name.map(|name| (name, filename))
+}
+
+fn split_param(param: &str) -> Option<(String, String)> {
+    let mut parts = param.splitn(2, '=');
+    let key = parts.next()?.trim();
+    let value = parts.next()?.trim().trim_matches('"');
+    Some((key.to_string(), value.to_string()))
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from > haystack.len() {
+        return None;
+    }
+    haystack[from..].windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| i + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT_TYPE: &'static str = "multipart/form-data; boundary=XyZ";
+
+    #[test]
+    fn parse_multipart_returns_uploaded_file() {
+        let body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+            Content-Type: application/octet-stream\r\n\
+            \r\n\
+            content\r\n\
+            --XyZ--\r\n";
+
+        let files = parse_multipart(CONTENT_TYPE, body).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "input");
+        assert_eq!(files[0].1.name(), "file.exe");
+        assert_eq!(files[0].1.content(), b"content");
+    }
+
+    #[test]
+    fn parse_multipart_keeps_binary_content_intact() {
+        let mut body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+            \r\n".to_vec();
+        body.extend_from_slice(b"\x00\r\n--X\xff");
+        body.extend_from_slice(b"\r\n--XyZ--\r\n");
+
+        let files = parse_multipart(CONTENT_TYPE, &body).unwrap();
+
+        assert_eq!(files[0].1.content(), b"\x00\r\n--X\xff");
+    }
+
+    #[test]
+    fn parse_multipart_skips_parts_without_file_name() {
+        let body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"mode\"\r\n\
+            \r\n\
+            bin\r\n\
+            --XyZ\r\n\
+            Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+            \r\n\
+            content\r\n\
+            --XyZ--\r\n";
+
+        let files = parse_multipart(CONTENT_TYPE, body).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "input");
+    }
+
+    #[test]
+    fn parse_multipart_accepts_quoted_boundary() {
+        let body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+            \r\n\
+            content\r\n\
+            --XyZ--\r\n";
+
+        let files = parse_multipart("multipart/form-data; boundary=\"XyZ\"", body).unwrap();
+
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn parse_multipart_returns_error_when_there_is_no_boundary() {
+        assert!(parse_multipart("multipart/form-data", b"").is_err());
+    }
+
+    #[test]
+    fn parse_multipart_returns_error_when_body_is_not_terminated() {
+        let body = b"--XyZ\r\n\
+            Content-Disposition: form-data; name=\"input\"; filename=\"file.exe\"\r\n\
+            \r\n\
+            content";
+
+        assert!(parse_multipart(CONTENT_TYPE, body).is_err());
+    }
+}
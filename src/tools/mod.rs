@@ -6,16 +6,25 @@ macro_rules! generate_main_for_tool {
         ///
         /// Runs the tool. If the tool fails, it prints the error to the
         /// standard error. Then, it terminates the process. If the tool
-        /// finished successfully, the exit code will be 0, otherwise 1.
+        /// finished successfully, the exit code will be 0, otherwise it
+        /// depends on the class of the failure (see `tools::exit_code`).
         pub fn main() {
             let args: Vec<String> = ::std::env::args().collect();
             if let Err(ref e) = $main(&args) {
                 ::error::print_error(e, &mut ::std::io::stderr());
-                ::std::process::exit(1);
+                ::std::process::exit(::tools::exit_code::exit_code_for_error(e));
             }
         }
     }
 }
 
+pub mod api_key;
 pub mod decompiler;
+pub mod exit_code;
 pub mod fileinfo;
+pub mod inputs;
+pub mod mock_server;
+pub mod progress;
+pub mod report;
+pub mod settings;
+pub mod test;
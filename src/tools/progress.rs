@@ -0,0 +1,179 @@
+//! Display of progress of decompilations on the standard error.
+
+use std::io::Write;
+use std::io;
+use std::time::Duration;
+use std::time::Instant;
+
+use atty;
+
+/// How often a progress line is printed when the output is not a terminal
+/// (unless the phase changes).
+const LOG_INTERVAL_SECS: u64 = 10;
+
+/// Progress of a running job.
+///
+/// When the output is a terminal, a single line is kept updated. Otherwise,
+/// a new line is printed whenever the phase changes and periodically in
+/// between, so the output is suitable for logs.
+pub struct Progress<W: Write> {
+    writer: W,
+    label: String,
+    is_tty: bool,
+    started: Instant,
+    last_logged: Option<(Instant, String)>,
+    line_len: usize,
+}
+
+impl Progress<io::Stderr> {
+    /// Creates a progress display on the standard error.
+    pub fn on_stderr<L: Into<String>>(label: L) -> Self {
+        Progress::new(io::stderr(), label, atty::is(atty::Stream::Stderr))
+    }
+}
+
+impl<W: Write> Progress<W> {
+    /// Creates a progress display that writes into the given writer.
+    pub fn new<L: Into<String>>(writer: W, label: L, is_tty: bool) -> Self {
+        Progress {
+            writer: writer,
+            label: label.into(),
+            is_tty: is_tty,
+            started: Instant::now(),
+            last_logged: None,
+            line_len: 0,
+        }
+    }
+
+    /// Shows the given completion (in percentages) and phase.
+    pub fn update(&mut self, completion: u8, phase: Option<&str>) {
+        let elapsed = self.started.elapsed();
+        let line = format_progress_line(&self.label, completion, phase, elapsed);
+        // Failures to display progress are not worth failing the tool.
+        let _ = if self.is_tty {
+            self.rewrite_line(&line)
+        } else {
+            self.log_line(line, phase.unwrap_or(""))
+        };
+    }
+
+    /// Ends the display (moves to a new line on a terminal).
+    pub fn finish(&mut self) {
+        if self.is_tty && self.line_len > 0 {
+            let _ = writeln!(self.writer);
+            self.line_len = 0;
+        }
+    }
+
+    fn rewrite_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.chars().count();
+        // Overwrite leftovers from a previous, longer line.
+        let padding = self.line_len.saturating_sub(len);
+        write!(self.writer, "\r{}{}", line, " ".repeat(padding))?;
+        self.line_len = len;
+        self.writer.flush()
+    }
+
+    fn log_line(&mut self, line: String, phase: &str) -> io::Result<()> {
+        let now = Instant::now();
+        let should_log = match self.last_logged {
+            Some((ref at, ref last_phase)) => {
+                last_phase != phase ||
+                    now.duration_since(*at) >= Duration::from_secs(LOG_INTERVAL_SECS)
+            }
+            None => true,
+        };
+        if should_log {
+            writeln!(self.writer, "{}", line)?;
+            self.last_logged = Some((now, phase.to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Returns a line describing the progress (e.g.
+/// `file.exe: 42% Decompiling [0:12]`).
+fn format_progress_line(label: &str,
+                        completion: u8,
+                        phase: Option<&str>,
+                        elapsed: Duration) -> String {
+    match phase {
+        Some(phase) if !phase.is_empty() => {
+            format!("{}: {:3}% {} [{}]", label, completion, phase, format_elapsed(elapsed))
+        }
+        _ => format!("{}: {:3}% [{}]", label, completion, format_elapsed(elapsed)),
+    }
+}
+
+/// Formats the given duration as `M:SS` or `H:MM:SS`.
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_of(progress: Progress<Vec<u8>>) -> String {
+        String::from_utf8(progress.writer).unwrap()
+    }
+
+    #[test]
+    fn format_elapsed_returns_minutes_and_seconds() {
+        assert_eq!(format_elapsed(Duration::from_secs(0)), "0:00");
+        assert_eq!(format_elapsed(Duration::from_secs(65)), "1:05");
+    }
+
+    #[test]
+    fn format_elapsed_returns_hours_when_needed() {
+        assert_eq!(format_elapsed(Duration::from_secs(3723)), "1:02:03");
+    }
+
+    #[test]
+    fn format_progress_line_includes_completion_phase_and_elapsed_time() {
+        assert_eq!(
+            format_progress_line("file.exe", 42, Some("Decompiling"), Duration::from_secs(12)),
+            "file.exe:  42% Decompiling [0:12]"
+        );
+    }
+
+    #[test]
+    fn format_progress_line_omits_missing_phase() {
+        assert_eq!(
+            format_progress_line("file.exe", 5, None, Duration::from_secs(1)),
+            "file.exe:   5% [0:01]"
+        );
+    }
+
+    #[test]
+    fn progress_on_tty_rewrites_single_line() {
+        let mut progress = Progress::new(Vec::new(), "f", true);
+
+        progress.update(10, Some("Long phase"));
+        progress.update(20, Some("B"));
+        progress.finish();
+
+        assert_eq!(
+            output_of(progress),
+            "\rf:  10% Long phase [0:00]\rf:  20% B [0:00]         \n"
+        );
+    }
+
+    #[test]
+    fn progress_without_tty_logs_line_only_when_phase_changes() {
+        let mut progress = Progress::new(Vec::new(), "f", false);
+
+        progress.update(10, Some("A"));
+        progress.update(15, Some("A"));
+        progress.update(20, Some("B"));
+        progress.finish();
+
+        assert_eq!(output_of(progress), "f:  10% A [0:00]\nf:  20% B [0:00]\n");
+    }
+}
@@ -0,0 +1,201 @@
+//! Reports of jobs (decompilations, analyses) run by the tools.
+//!
+//! With `--json`, the tools print one report per input as a JSON document on
+//! a single line of the standard output, so scripts do not have to scrape the
+//! standard error.
+
+use std::io::Write;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use json::JsonValue;
+
+use error::Error;
+use error::error_to_json;
+
+/// A report of a single job.
+#[derive(Clone, Debug)]
+pub struct JobReport {
+    input: String,
+    id: Option<String>,
+    started_at: SystemTime,
+    started: Instant,
+    duration: Option<Duration>,
+    warnings: Vec<String>,
+    outputs: Vec<PathBuf>,
+    output: Option<String>,
+    error: Option<JsonValue>,
+}
+
+impl JobReport {
+    /// Creates a report of a job for the given input that starts now.
+    pub fn start<I: Into<String>>(input: I) -> Self {
+        JobReport {
+            input: input.into(),
+            id: None,
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+            duration: None,
+            warnings: Vec::new(),
+            outputs: Vec::new(),
+            output: None,
+            error: None,
+        }
+    }
+
+    /// Sets the ID of the job.
+    pub fn set_id<I: Into<String>>(&mut self, id: I) {
+        self.id = Some(id.into());
+    }
+
+    /// Adds the given warnings.
+    pub fn add_warnings(&mut self, warnings: &[String]) {
+        self.warnings.extend(warnings.iter().cloned());
+    }
+
+    /// Adds a path to a saved output.
+    pub fn add_output_path(&mut self, path: PathBuf) {
+        self.outputs.push(path);
+    }
+
+    /// Sets an output that was not saved into a file (e.g. the output of an
+    /// analysis).
+    pub fn set_output<O: Into<String>>(&mut self, output: O) {
+        self.output = Some(output.into());
+    }
+
+    /// Marks the job as finished successfully.
+    pub fn succeed(&mut self) {
+        self.duration = Some(self.started.elapsed());
+    }
+
+    /// Marks the job as failed with the given error.
+    pub fn fail(&mut self, err: &Error) {
+        self.duration = Some(self.started.elapsed());
+        self.error = Some(error_to_json(err));
+    }
+
+    /// Returns the input of the job.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns paths to saved outputs.
+    pub fn output_paths(&self) -> &[PathBuf] {
+        &self.outputs
+    }
+
+    /// Has the job failed?
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Returns a JSON representation of the report.
+    pub fn to_json(&self) -> JsonValue {
+        let duration = self.duration.unwrap_or_else(|| self.started.elapsed());
+        let started_at = self.started_at.duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut json = object!{
+            "input" => self.input.as_str(),
+            "id" => self.id.clone(),
+            "status" => if self.failed() { "failed" } else { "succeeded" },
+            "timings" => object!{
+                "started_at" => started_at,
+                "duration_ms" => duration_as_millis(duration)
+            },
+            "warnings" => JsonValue::Array(
+                self.warnings.iter().map(|w| JsonValue::from(w.as_str())).collect()
+            ),
+            "outputs" => JsonValue::Array(
+                self.outputs.iter()
+                    .map(|p| JsonValue::from(p.display().to_string()))
+                    .collect()
+            ),
+            "error" => self.error.clone()
+        };
+        if let Some(ref output) = self.output {
+            json["output"] = output.as_str().into();
+        }
+        json
+    }
+
+    /// Prints the report as a single line of JSON to the standard output.
+    pub fn print_json(&self) {
+        println!("{}", self.to_json().dump());
+    }
+}
+
+/// Prints the given warnings to the standard error.
+pub fn print_warnings(warnings: &[String]) {
+    let mut stderr = io::stderr();
+    for warning in warnings {
+        // Failures to print a warning are not worth failing the tool.
+        let _ = writeln!(stderr, "warning: {}", warning);
+    }
+}
+
+fn duration_as_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_nanos() / 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use error::ErrorKind;
+
+    #[test]
+    fn to_json_returns_report_of_successful_job() {
+        let mut report = JobReport::start("file.exe");
+        report.set_id("ID");
+        report.add_warnings(&["warning".to_string()]);
+        report.add_output_path(PathBuf::from("file.c"));
+        report.succeed();
+
+        let json = report.to_json();
+
+        assert_eq!(json["input"], "file.exe");
+        assert_eq!(json["id"], "ID");
+        assert_eq!(json["status"], "succeeded");
+        assert!(json["timings"]["started_at"].as_u64().unwrap() > 0);
+        assert!(json["timings"]["duration_ms"].is_number());
+        assert_eq!(json["warnings"][0], "warning");
+        assert_eq!(json["outputs"][0], "file.c");
+        assert!(json["error"].is_null());
+        assert!(!json.has_key("output"));
+    }
+
+    #[test]
+    fn to_json_returns_report_of_failed_job() {
+        let mut report = JobReport::start("file.exe");
+        report.fail(&Error::with_chain(
+            Error::from_kind(ErrorKind::Msg("cause".to_string())),
+            ErrorKind::Msg("error".to_string())
+        ));
+
+        let json = report.to_json();
+
+        assert!(json["id"].is_null());
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["error"]["message"], "error");
+        assert_eq!(json["error"]["causes"][0], "cause");
+    }
+
+    #[test]
+    fn to_json_includes_output_when_set() {
+        let mut report = JobReport::start("file.exe");
+        report.set_output("output");
+
+        assert_eq!(report.to_json()["output"], "output");
+    }
+
+    #[test]
+    fn duration_as_millis_returns_correct_value() {
+        assert_eq!(duration_as_millis(Duration::new(2, 5_000_000)), 2005);
+    }
+}
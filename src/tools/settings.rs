@@ -0,0 +1,171 @@
+//! Settings of the tools that access the API.
+//!
+//! All tools accept the same flags concerning the connection to the API (see
+//! `connection_flags()`) and create their settings from them in the same way
+//! (see `create_settings()`).
+
+use std::path::Path;
+
+use clap::Arg;
+use clap::ArgMatches;
+
+use config::Config;
+use config::Profile;
+use error::Result;
+use settings::Settings;
+use tools::api_key::api_key_from_args;
+
+/// Returns flags concerning the connection to the API.
+pub fn connection_flags<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("api_key")
+            .short("k")
+            .long("api-key")
+            .takes_value(true)
+            .value_name("KEY")
+            // It is important not to require the API key by default because it
+            // enables the use of the RETDEC_API_KEY environment variable.
+            .help("API key to be used."),
+        Arg::with_name("api_key_file")
+            .long("api-key-file")
+            .takes_value(true)
+            .value_name("FILE")
+            .conflicts_with_all(&["api_key", "api_key_command"])
+            .help("File containing the API key to be used \
+                   (default: $RETDEC_API_KEY_FILE)."),
+        Arg::with_name("api_key_command")
+            .long("api-key-command")
+            .takes_value(true)
+            .value_name("CMD")
+            .conflicts_with("api_key")
+            .help("Command printing the API key to be used \
+                   (e.g. 'pass show retdec')."),
+        Arg::with_name("api_url")
+            .short("u")
+            .long("api-url")
+            .takes_value(true)
+            .value_name("URL")
+            // It is important not to require the API URL by default because it
+            // enables the use of the RETDEC_API_URL environment variable.
+            .help("Custom URL to the retdec.com's API."),
+        Arg::with_name("config")
+            .long("config")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Configuration file with profiles (default: \
+                   $XDG_CONFIG_HOME/retdec/config.ini or ~/.config/retdec/config.ini)"),
+        Arg::with_name("profile")
+            .short("p")
+            .long("profile")
+            .takes_value(true)
+            .value_name("NAME")
+            .help("Profile from the configuration file to be used (default: \
+                   default)"),
+        Arg::with_name("allow_plain_http")
+            .long("allow-plain-http")
+            .help("Allow an http:// API URL (only for local test servers, \
+                   e.g. retdec-mock-server)"),
+        Arg::with_name("verbose_http")
+            .long("verbose-http")
+            .help("Dump headers and bodies of HTTP requests and responses to \
+                   the standard error (the API key is redacted)"),
+    ]
+}
+
+/// Loads the profile selected on the command line (if any).
+pub fn load_profile(args: &ArgMatches) -> Result<Option<Profile>> {
+    Config::load_profile(args.value_of("config").map(Path::new), args.value_of("profile"))
+}
+
+/// Creates settings from environment variables, overridden by the profile,
+/// overridden by explicitly given flags.
+///
+/// Warnings (e.g. about an unprotected API key file or allowed plain HTTP)
+/// are added into `warnings`.
+pub fn create_settings(args: &ArgMatches,
+                       profile: Option<&Profile>,
+                       warnings: &mut Vec<String>) -> Result<Settings> {
+    let mut settings = Settings::new();
+    if let Some(profile) = profile {
+        settings.apply_profile(profile);
+    }
+    if let Some(api_key) = api_key_from_args(args, profile, warnings)? {
+        settings.set_api_key(api_key);
+    }
+    if let Some(api_url) = args.value_of("api_url") {
+        settings.set_api_url(api_url);
+    }
+    settings.set_plain_http_allowed(args.is_present("allow_plain_http"));
+    settings.set_verbose_http(args.is_present("verbose_http"));
+    if settings.plain_http_allowed() {
+        warnings.push(
+            "plain HTTP is allowed, so your API key and files may be sent \
+             unencrypted; never do this outside of local testing".to_string()
+        );
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::io::Write;
+
+    use clap::App;
+    use tempdir::TempDir;
+
+    fn parse_args<'a>(args: &[&str]) -> ArgMatches<'a> {
+        App::new("tool")
+            .args(&connection_flags())
+            .get_matches_from(args)
+    }
+
+    #[test]
+    fn create_settings_overrides_profile_by_flags() {
+        let config = Config::parse("[p]\napi_key = PROFILE-KEY\napi_url = PROFILE-URL\n").unwrap();
+        let args = parse_args(&["tool", "-k", "FLAG-KEY"]);
+
+        let settings = create_settings(&args, config.profile("p"), &mut Vec::new()).unwrap();
+
+        assert_eq!(settings.api_key(), Some("FLAG-KEY"));
+        assert_eq!(settings.api_url(), "PROFILE-URL");
+    }
+
+    #[test]
+    fn create_settings_reads_api_key_from_given_file() {
+        let tmp_dir = TempDir::new("retdec-tool-settings").unwrap();
+        let path = tmp_dir.path().join("key.txt");
+        fs::File::create(&path).unwrap().write_all(b"FILE-KEY\n").unwrap();
+        let args = parse_args(&["tool", "--api-key-file", path.to_str().unwrap()]);
+
+        let settings = create_settings(&args, None, &mut Vec::new()).unwrap();
+
+        assert_eq!(settings.api_key(), Some("FILE-KEY"));
+    }
+
+    #[test]
+    fn create_settings_warns_when_plain_http_is_allowed() {
+        let args = parse_args(&["tool", "-k", "KEY", "--allow-plain-http"]);
+        let mut warnings = Vec::new();
+
+        let settings = create_settings(&args, None, &mut warnings).unwrap();
+
+        assert!(settings.plain_http_allowed());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("plain HTTP"));
+    }
+
+    #[test]
+    fn load_profile_loads_selected_profile_from_given_config_file() {
+        let tmp_dir = TempDir::new("retdec-tool-settings").unwrap();
+        let path = tmp_dir.path().join("config.ini");
+        fs::File::create(&path).unwrap().write_all(b"[work]\napi_key = WORK-KEY\n").unwrap();
+        let args = parse_args(&["tool", "--config", path.to_str().unwrap(), "-p", "work"]);
+
+        let profile = load_profile(&args).unwrap().unwrap();
+
+        assert_eq!(profile.api_key(), Some("WORK-KEY"));
+    }
+}
@@ -0,0 +1,426 @@
+//! A tool for checking authentication to and connectivity with the API.
+//!
+//! It is meant for health checks, so it exits with a distinct code for each
+//! class of failures (see `tools::exit_code`).
+
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::process;
+
+use clap::App;
+use clap::AppSettings;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use VERSION;
+use doctor::Diagnosis;
+use doctor::StepKind;
+use error::Error;
+use error::Result;
+use error::print_error;
+use settings::Settings;
+use test::Test;
+use tools::exit_code::EXIT_BAD_API_KEY;
+use tools::exit_code::EXIT_FAILURE;
+use tools::exit_code::EXIT_SERVICE_UNREACHABLE;
+use tools::exit_code::EXIT_SUCCESS;
+use tools::exit_code::EXIT_UNEXPECTED_RESPONSE;
+use tools::exit_code::exit_code_for_error;
+use tools::report::print_warnings;
+use tools::settings::connection_flags;
+use tools::settings::create_settings;
+use tools::settings::load_profile;
+
+/// A check to be performed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Check {
+    /// Authentication to the API.
+    Auth,
+
+    /// Echoing of the given parameters.
+    Echo(Vec<(String, String)>),
+
+    /// Diagnosis of the connection to the API, step by step.
+    Doctor,
+}
+
+fn parse_args<'a>(args: &[String]) -> ArgMatches<'a> {
+    App::new("retdec-test")
+        .version(VERSION)
+        .about("Checks authentication to and connectivity with retdec.com's API.")
+        .after_help(
+            "Exit codes: 0 (success), 1 (the check could not be performed, e.g. \
+             invalid arguments or no API key), 2 (the API key was rejected), \
+             3 (the API could not be reached), 4 (unexpected response from the \
+             API), 5 (the API is overloaded or broken, e.g. HTTP 429 or 5xx)."
+        )
+        .setting(AppSettings::ColorNever)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("auth")
+            .about("Checks that the API key is accepted by the API.")
+            .setting(AppSettings::ColorNever)
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("echo")
+            .about("Checks that the API echoes back the given parameters.")
+            .setting(AppSettings::ColorNever)
+            .arg(Arg::with_name("PARAM")
+                .multiple(true)
+                .help("Parameter to be echoed in the form key=value"))
+            .args(&connection_flags()))
+        .subcommand(SubCommand::with_name("doctor")
+            .about("Diagnoses the connection to the API step by step (URL, name \
+                    resolution, TCP connection, TLS handshake, HTTP round-trip, and \
+                    authentication) and prints a report with hints.")
+            .setting(AppSettings::ColorNever)
+            .args(&connection_flags()))
+        .get_matches_from(args)
+}
+
+/// Parses parameters in the form `key=value`.
+fn parse_echo_params<'a, I>(params: I) -> Result<Vec<(String, String)>>
+    where I: IntoIterator<Item = &'a str>
+{
+    params.into_iter()
+        .map(|param| {
+            match param.find('=') {
+                Some(i) if i > 0 => Ok((param[..i].to_string(), param[i + 1..].to_string())),
+                _ => bail!("invalid parameter: {} (expected key=value)", param),
+            }
+        })
+        .collect()
+}
+
+/// Prepares the check and settings from the command-line arguments.
+///
+/// Nothing is sent to the API, so all errors are the user's.
+fn prepare(args: &ArgMatches) -> Result<(Check, Settings)> {
+    let (check, args) = match args.subcommand() {
+        ("auth", Some(args)) => (Check::Auth, args),
+        ("echo", Some(args)) => {
+            let params = parse_echo_params(args.values_of("PARAM").into_iter().flat_map(|v| v))?;
+            (Check::Echo(params), args)
+        }
+        ("doctor", Some(args)) => (Check::Doctor, args),
+        _ => unreachable!("clap requires a subcommand"),
+    };
+    let mut warnings = Vec::new();
+    let settings = create_settings(args, load_profile(args)?.as_ref(), &mut warnings)?;
+    print_warnings(&warnings);
+    // The doctor reports a missing API key as one of its steps.
+    if settings.api_key().is_none() && check != Check::Doctor {
+        bail!("no API key (use --api-key, --api-key-file, or set RETDEC_API_KEY)");
+    }
+    Ok((check, settings))
+}
+
+/// Performs the given check and returns a message describing the result.
+fn run_check(test: &Test, check: &Check) -> Result<String> {
+    match *check {
+        Check::Auth => {
+            test.auth()?;
+            Ok("authentication succeeded".to_string())
+        }
+        Check::Echo(ref params) => {
+            let sent: HashMap<String, String> = params.iter().cloned().collect();
+            let echoed = test.echo(&sent)?;
+            let mut lines = Vec::new();
+            for (key, value) in params {
+                match echoed.get(key) {
+                    Some(echoed_value) if echoed_value == value => {
+                        lines.push(format!("{}={}", key, echoed_value));
+                    }
+                    Some(echoed_value) => {
+                        bail!("parameter {} was echoed as '{}' instead of '{}'",
+                              key, echoed_value, value);
+                    }
+                    None => bail!("parameter {} was not echoed", key),
+                }
+            }
+            Ok(lines.join("\n"))
+        }
+        Check::Doctor => unreachable!("the doctor is run via run_doctor()"),
+    }
+}
+
+/// Diagnoses the connection to the API, prints a report, and returns the
+/// exit code corresponding to the failed step (if any).
+fn run_doctor(settings: &Settings) -> i32 {
+    let diagnosis = Diagnosis::run(settings);
+    // Failures to print the report are not worth a different exit code.
+    let _ = diagnosis.write_report(&mut io::stdout());
+    exit_code_for_diagnosis(&diagnosis)
+}
+
+/// Returns the exit code for the result of a diagnosis.
+fn exit_code_for_diagnosis(diagnosis: &Diagnosis) -> i32 {
+    match diagnosis.failed_step().map(|step| step.kind()) {
+        None => EXIT_SUCCESS,
+        Some(StepKind::Url) => EXIT_FAILURE,
+        Some(StepKind::Authentication) => EXIT_BAD_API_KEY,
+        Some(StepKind::HttpRoundTrip) => EXIT_UNEXPECTED_RESPONSE,
+        Some(_) => EXIT_SERVICE_UNREACHABLE,
+    }
+}
+
+/// Returns the exit code for an error that occurred during a check.
+///
+/// Errors unrelated to the API cannot occur during a check, so the remaining
+/// errors concern responses from the API (e.g. differing echoed parameters).
+fn exit_code_for_check_error(err: &Error) -> i32 {
+    match exit_code_for_error(err) {
+        EXIT_FAILURE => EXIT_UNEXPECTED_RESPONSE,
+        code => code,
+    }
+}
+
+fn run(args: &[String]) -> i32 {
+    let args = parse_args(args);
+    let (check, settings) = match prepare(&args) {
+        Ok(prepared) => prepared,
+        Err(ref err) => {
+            print_error(err, &mut io::stderr());
+            return EXIT_FAILURE;
+        }
+    };
+
+    if check == Check::Doctor {
+        return run_doctor(&settings);
+    }
+    match run_check(&Test::new(settings), &check) {
+        Ok(message) => {
+            if !message.is_empty() {
+                println!("{}", message);
+            }
+            EXIT_SUCCESS
+        }
+        Err(ref err) => {
+            print_error(err, &mut io::stderr());
+            exit_code_for_check_error(err)
+        }
+    }
+}
+
+/// Implementation of the `main()` function for the tool.
+///
+/// Runs the tool and terminates the process with one of the `EXIT_*` codes.
+/// Unlike the other tools, it does not use `generate_main_for_tool!` because
+/// it also classifies failures of the doctor.
+pub fn main() {
+    let args: Vec<String> = env::args().collect();
+    process::exit(run(&args));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use error::ErrorKind;
+    use testing::APIConnectionFactoryMock;
+    use testing::APIConnectionMock;
+    use testing::APIResponseBuilder;
+    use tools::exit_code::EXIT_SERVICE_UNAVAILABLE;
+
+    macro_rules! args {
+        ($($arg:expr),*) => {
+            {
+                let mut args = Vec::new();
+                args.push("retdec-test".to_string());
+                $(
+                    args.push($arg.to_string());
+                )*
+                args
+            }
+        }
+    }
+
+    fn create_test() -> (Rc<RefCell<APIConnectionMock>>, Test) {
+        let settings = Settings::new()
+            .with_api_key("test")
+            .with_api_url("https://retdec.com/service/api");
+        let conn = Rc::new(RefCell::new(APIConnectionMock::new(settings.clone())));
+        let conn_factory = Box::new(APIConnectionFactoryMock::new(conn.clone()));
+        (conn, Test::with_transport(conn_factory))
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_auth_subcommand() {
+        let args = parse_args(&args!["auth", "-k", "KEY"]);
+        let (name, args) = args.subcommand();
+        assert_eq!(name, "auth");
+        assert_eq!(args.unwrap().value_of("api_key"), Some("KEY"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_verbose_http() {
+        let args = parse_args(&args!["auth", "--verbose-http"]);
+        assert!(args.subcommand_matches("auth").unwrap().is_present("verbose_http"));
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_echo_subcommand() {
+        let args = parse_args(&args!["echo", "a=1", "b=2"]);
+        let (name, args) = args.subcommand();
+        assert_eq!(name, "echo");
+        assert_eq!(args.unwrap().values_of("PARAM").unwrap().collect::<Vec<_>>(), ["a=1", "b=2"]);
+    }
+
+    #[test]
+    fn parse_args_correctly_parses_doctor_subcommand() {
+        let args = parse_args(&args!["doctor", "-u", "URL"]);
+        let (name, args) = args.subcommand();
+        assert_eq!(name, "doctor");
+        assert_eq!(args.unwrap().value_of("api_url"), Some("URL"));
+    }
+
+    #[test]
+    fn prepare_returns_doctor_check_even_without_api_key() {
+        let args = parse_args(&args!["doctor"]);
+
+        let (check, _) = prepare(&args).unwrap();
+
+        assert_eq!(check, Check::Doctor);
+    }
+
+    #[test]
+    fn exit_code_for_diagnosis_returns_failure_for_invalid_url() {
+        let settings = Settings::new()
+            .with_api_key("KEY")
+            .with_api_url("not a url");
+
+        let diagnosis = Diagnosis::run(&settings);
+
+        assert_eq!(exit_code_for_diagnosis(&diagnosis), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn parse_echo_params_splits_params_at_first_equals_sign() {
+        assert_eq!(
+            parse_echo_params(vec!["a=1", "b=x=y", "c="]).unwrap(),
+            [
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "x=y".to_string()),
+                ("c".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_echo_params_returns_error_for_param_without_key() {
+        assert!(parse_echo_params(vec!["a"]).is_err());
+        assert!(parse_echo_params(vec!["=1"]).is_err());
+    }
+
+    #[test]
+    fn prepare_returns_echo_check_with_given_params() {
+        let args = parse_args(&args!["echo", "-k", "KEY", "a=1"]);
+
+        let (check, settings) = prepare(&args).unwrap();
+
+        assert_eq!(check, Check::Echo(vec![("a".to_string(), "1".to_string())]));
+        assert_eq!(settings.api_key(), Some("KEY"));
+    }
+
+    #[test]
+    fn run_check_returns_message_when_auth_succeeds() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Ok(APIResponseBuilder::new().with_status_code(200).with_body(b"{}").build())
+        );
+
+        let message = run_check(&test, &Check::Auth).unwrap();
+
+        assert_eq!(message, "authentication succeeded");
+    }
+
+    #[test]
+    fn run_check_returns_error_with_bad_key_exit_code_when_auth_fails() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Ok(APIResponseBuilder::new().with_status_code(401).build())
+        );
+
+        let err = run_check(&test, &Check::Auth).unwrap_err();
+
+        assert_eq!(exit_code_for_check_error(&err), EXIT_BAD_API_KEY);
+    }
+
+    #[test]
+    fn run_check_returns_error_with_unavailable_exit_code_when_auth_returns_500() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Ok(APIResponseBuilder::new().with_status_code(500).build())
+        );
+
+        let err = run_check(&test, &Check::Auth).unwrap_err();
+
+        assert_eq!(exit_code_for_check_error(&err), EXIT_SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn run_check_returns_error_with_unreachable_exit_code_when_connection_fails() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test",
+            Err(ErrorKind::ConnectionFailed("connection refused".to_string()).into())
+        );
+
+        let err = run_check(&test, &Check::Auth).unwrap_err();
+
+        assert_eq!(exit_code_for_check_error(&err), EXIT_SERVICE_UNREACHABLE);
+    }
+
+    #[test]
+    fn run_check_returns_echoed_params_when_echo_succeeds() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test/echo",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{"a": "1", "b": "2"}"#)
+                    .build()
+            )
+        );
+        let check = Check::Echo(vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ]);
+
+        let message = run_check(&test, &check).unwrap();
+
+        assert_eq!(message, "a=1\nb=2");
+    }
+
+    #[test]
+    fn run_check_returns_error_when_echoed_params_differ() {
+        let (conn, test) = create_test();
+        conn.borrow_mut().add_response(
+            "GET",
+            "https://retdec.com/service/api/test/echo",
+            Ok(
+                APIResponseBuilder::new()
+                    .with_status_code(200)
+                    .with_body(br#"{"a": "2"}"#)
+                    .build()
+            )
+        );
+        let check = Check::Echo(vec![("a".to_string(), "1".to_string())]);
+
+        let err = run_check(&test, &check).unwrap_err();
+
+        assert_eq!(exit_code_for_check_error(&err), EXIT_UNEXPECTED_RESPONSE);
+    }
+}
@@ -1,5 +1,12 @@
 //! Internal utilities.
 
+use std::env;
+use std::io::Write;
+use std::io;
+
+use sha2::Digest;
+use sha2::Sha256;
+
 /// Returns the name of the current platform (e.g. `"Linux"`).
 ///
 /// When the name cannot be detected, it returns `"Unknown"`.
@@ -24,3 +31,110 @@ pub fn current_platform_name() -> &'static str {
         "Unknown"
     }
 }
+
+/// Returns the value of the given environment variable, or `None` when it is
+/// not set or is empty.
+pub fn non_empty_env_var(name: &str) -> Option<String> {
+    match env::var(name) {
+        Ok(ref value) if !value.is_empty() => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns a hexadecimal representation of the SHA-256 digest of the given
+/// data.
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::default();
+    hasher.input(data);
+    hasher.result().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes a percent-encoded string into bytes.
+///
+/// Returns `None` when the string contains an invalid escape sequence.
+pub fn percent_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hi = hex_value(iter.next()?)?;
+            let lo = hex_value(iter.next()?)?;
+            bytes.push(hi << 4 | lo);
+        } else {
+            bytes.push(b);
+        }
+    }
+    Some(bytes)
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'...b'9' => Some(b - b'0'),
+        b'a'...b'f' => Some(b - b'a' + 10),
+        b'A'...b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// A writer computing the SHA-256 digest of the data written into it.
+#[derive(Default)]
+pub struct Sha256Writer {
+    hasher: Sha256,
+}
+
+impl Sha256Writer {
+    /// Creates a new writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a hexadecimal representation of the digest of the written data.
+    pub fn hex_digest(self) -> String {
+        self.hasher.result().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl Write for Sha256Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.input(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_writer_computes_same_digest_as_sha256_hex() {
+        let mut writer = Sha256Writer::new();
+
+        writer.write_all(b"con").unwrap();
+        writer.write_all(b"tent").unwrap();
+
+        assert_eq!(writer.hex_digest(), sha256_hex(b"content"));
+    }
+
+    #[test]
+    fn percent_decode_decodes_escape_sequences() {
+        assert_eq!(percent_decode("p%40ss%3a%25"), Some(b"p@ss:%".to_vec()));
+    }
+
+    #[test]
+    fn percent_decode_returns_none_for_invalid_escape_sequence() {
+        assert_eq!(percent_decode("%4"), None);
+        assert_eq!(percent_decode("%zz"), None);
+    }
+
+    #[test]
+    fn sha256_hex_returns_correct_digest() {
+        assert_eq!(
+            sha256_hex(b"content"),
+            "ed7002b439e9ac845f22357d822bac1444730fbdb6016d3ec9432297b9ec9f73"
+        );
+    }
+}
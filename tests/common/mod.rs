@@ -1,32 +1,78 @@
 //! Common functionality for integration tests.
+//!
+//! The tests run against a local mock server (see
+//! `retdec::tools::mock_server`), so they need neither an API key nor access
+//! to retdec.com. Canned results are in `tests/results`.
 
 use std::env;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Output;
 
+use retdec::settings::Settings;
+use retdec::tools::mock_server::MockServer;
+use retdec::tools::mock_server::ResultStore;
+use retdec::tools::mock_server::RunningMockServer;
+use retdec::tools::mock_server::Timeline;
+
+/// API key accepted by the mock server.
+pub const API_KEY: &'static str = "test";
+
 pub fn run_tool(tool: &str, args: &[&str]) -> Output {
-    ensure_api_key_is_set();
+    let server = start_mock_server();
+    let api_url = server.api_url();
     Command::new("cargo")
         .args(&["run", "--quiet", "--bin", tool, "--"])
+        .args(&["-k", API_KEY, "-u", &api_url, "--allow-plain-http"])
         .args(args)
         .output()
         .expect("failed to execute the command")
 }
 
+/// Starts a mock server on a free port, serving canned results from
+/// `tests/results`.
+///
+/// The server is stopped when the returned value is dropped.
+pub fn start_mock_server() -> RunningMockServer {
+    MockServer::new()
+        .with_api_key(API_KEY)
+        .with_result_store(ResultStore::from_dir(path_to_test_dir("results")))
+        .with_timeline(Timeline::parse("").expect("failed to parse the timeline"))
+        .start("127.0.0.1:0")
+        .expect("failed to start the mock server")
+}
+
+/// Returns settings for accessing the given mock server.
+pub fn settings_for(server: &RunningMockServer) -> Settings {
+    Settings::new()
+        .with_api_key(API_KEY)
+        .with_api_url(server.api_url())
+        .with_plain_http_allowed(true)
+}
+
+/// Returns the standard error of the given output without warnings (e.g.
+/// the one about plain HTTP being allowed to access the mock server).
+pub fn stderr_without_warnings(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| !line.starts_with("warning: "))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
 pub fn path_to_sample(name: &str) -> String {
+    path_to_test_dir("samples")
+        .join(name)
+        .to_str()
+        .expect("failed to convert the path into a string")
+        .to_string()
+}
+
+fn path_to_test_dir(name: &str) -> PathBuf {
     let cwd = env::current_dir()
         .expect("failed to get the current working directory");
     let mut path = PathBuf::from(cwd);
     path.push("tests");
-    path.push("samples");
     path.push(name);
-    path.as_path().to_str()
-        .expect("failed to convert the path into a string")
-        .to_string()
-}
-
-pub fn ensure_api_key_is_set() {
-    env::var("RETDEC_API_KEY")
-        .expect("RETDEC_API_KEY has to be set to run this test");
+    path
 }
@@ -10,17 +10,22 @@ mod common;
 
 use common::path_to_sample;
 use common::run_tool;
+use common::stderr_without_warnings;
 
 #[test]
 fn fileinfo_correctly_decompiles_input_file() {
     let output = run_tool(
         "decompiler", &[
+            "--quiet",
+            // Every run uses a new port of the mock server, so cached results
+            // would never be reused.
+            "--no-cache",
             &path_to_sample("pe-hello.exe")
         ]
     );
 
     // There should be no errors.
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr_without_warnings(&output);
     assert!(output.status.success(), "decompiler failed; reason:\n{}", stderr);
     assert_eq!(stderr, "");
     // The output should contain the decompiled C code.
@@ -12,6 +12,8 @@ use tempdir::TempDir;
 
 use common::path_to_sample;
 use retdec::file::File;
+use retdec::file::SaveOptions;
+use retdec::file::StreamedFile;
 
 #[test]
 fn file_from_path_returns_correct_file() {
@@ -32,6 +34,18 @@ fn file_from_path_with_custom_name_returns_correct_file() {
     assert_eq!(file.name(), "file.exe");
 }
 
+#[test]
+fn streamed_file_from_path_returns_correct_file() {
+    let file = StreamedFile::from_path(path_to_sample("pe-hello.exe"))
+        .expect("failed to create a file for 'pe-hello.exe'");
+
+    assert_eq!(file.len(), 75292);
+    assert_eq!(file.name(), "pe-hello.exe");
+    let mut content = Vec::new();
+    file.copy_to(&mut content).expect("failed to read 'pe-hello.exe'");
+    assert_eq!(content.len(), 75292);
+}
+
 #[test]
 fn file_save_into_stores_file_into_given_directory() {
     let file = File::from_content_with_name(b"content", "file.txt");
@@ -76,3 +90,90 @@ fn file_save_as_stores_file_into_given_path() {
     assert_eq!(file.name(), "new-file.txt");
     assert_eq!(file.content(), b"content");
 }
+
+#[test]
+fn file_save_into_sanitizes_name_of_file() {
+    let file = File::from_content_with_name(b"content", "../file.txt");
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+
+    let file_path = file.save_into(tmp_dir.path())
+        .expect("failed to save the file");
+
+    assert_eq!(file_path, tmp_dir.path().join(".._file.txt"));
+    assert!(file_path.exists());
+}
+
+#[test]
+fn file_save_into_returns_error_when_name_is_not_usable() {
+    let file = File::from_content_with_name(b"content", "..");
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+
+    assert!(file.save_into(tmp_dir.path()).is_err());
+}
+
+#[test]
+fn file_save_into_under_name_returns_error_when_name_contains_path_separator() {
+    let file = File::from_content_with_name(b"content", "file.txt");
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+
+    assert!(file.save_into_under_name(tmp_dir.path(), "../file.txt").is_err());
+}
+
+#[test]
+fn file_save_as_overwrites_existing_file_and_leaves_no_temporary_files_behind() {
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+    let file_path = tmp_dir.path().join("file.txt");
+    File::from_content_with_name(b"old content that is longer", "file.txt")
+        .save_as(&file_path)
+        .expect("failed to save the file");
+
+    File::from_content_with_name(b"new content", "file.txt")
+        .save_as(&file_path)
+        .expect("failed to save the file");
+
+    let file = File::from_path(&file_path)
+        .expect("failed to read the stored file");
+    assert_eq!(file.content(), b"new content");
+    let entries = tmp_dir.path().read_dir()
+        .expect("failed to read the temporary directory")
+        .count();
+    assert_eq!(entries, 1);
+}
+
+#[test]
+fn file_save_as_with_options_refuses_to_overwrite_existing_file_when_requested() {
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+    let file_path = tmp_dir.path().join("file.txt");
+    File::from_content_with_name(b"old content", "file.txt")
+        .save_as(&file_path)
+        .expect("failed to save the file");
+
+    let result = File::from_content_with_name(b"new content", "file.txt")
+        .save_as_with_options(&file_path, &SaveOptions::new().with_overwrite(false));
+
+    assert!(result.is_err());
+    let file = File::from_path(&file_path)
+        .expect("failed to read the stored file");
+    assert_eq!(file.content(), b"old content");
+}
+
+#[test]
+fn file_save_into_with_options_saves_new_file_when_overwriting_is_disabled() {
+    let file = File::from_content_with_name(b"content", "file.txt");
+    let tmp_dir = TempDir::new("retdec-file-test")
+        .expect("failed to create a temporary directory");
+
+    let file_path = file.save_into_with_options(
+        tmp_dir.path(),
+        &SaveOptions::new().with_overwrite(false)
+    ).expect("failed to save the file");
+
+    let file = File::from_path(file_path)
+        .expect("failed to read the stored file");
+    assert_eq!(file.content(), b"content");
+}
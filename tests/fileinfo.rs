@@ -10,6 +10,7 @@ mod common;
 
 use common::path_to_sample;
 use common::run_tool;
+use common::stderr_without_warnings;
 
 #[test]
 fn fileinfo_correctly_analyzes_input_file() {
@@ -22,7 +23,7 @@ fn fileinfo_correctly_analyzes_input_file() {
     );
 
     // There should be no errors.
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr_without_warnings(&output);
     assert!(output.status.success(), "fileinfo failed; reason:\n{}", stderr);
     assert_eq!(stderr, "");
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -9,15 +9,14 @@ mod common;
 
 use std::collections::HashMap;
 
-use retdec::settings::Settings;
 use retdec::test::Test;
-use common::ensure_api_key_is_set;
+use common::settings_for;
+use common::start_mock_server;
 
 #[test]
 fn auth_succeeds() {
-    ensure_api_key_is_set();
-    let settings = Settings::new();
-    let test = Test::new(settings);
+    let server = start_mock_server();
+    let test = Test::new(settings_for(&server));
 
     let result = test.auth();
 
@@ -26,9 +25,8 @@ fn auth_succeeds() {
 
 #[test]
 fn echo_returns_back_input_parameters() {
-    ensure_api_key_is_set();
-    let settings = Settings::new();
-    let test = Test::new(settings);
+    let server = start_mock_server();
+    let test = Test::new(settings_for(&server));
     let mut params = HashMap::new();
     params.insert("param1".to_string(), "value1".to_string());
     params.insert("param2".to_string(), "value2".to_string());